@@ -1,19 +1,49 @@
 use super::core::App;
 use super::persistence::*;
+use super::rest;
+use super::session;
 use super::state::*;
+use crate::combat::{CombatEncounter, new_encounter_id, next_copy_names, split_copy_number};
+use crate::dice::{DiceExpr, DiceRoll, DiceRoller};
 use crate::models::{
-    Combatant, CombatantTemplate, ConcentrationInfo, ConditionType, DeathSaveOutcome, LogEntry,
-    StatusEffect,
+    Combatant, CombatantKind, CombatantTemplate, ConcentrationInfo, ConditionType, DamageType,
+    DeathSaveOutcome, DefenseKind, EconomySlot, LegendaryPool, LogEntry, MAX_EXHAUSTION,
+    OngoingEffect, RepeatSave, SaveOutcome, StatusEffect, TurnBoundary,
 };
+use crate::models::{difficulty, resources};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 impl App {
     pub fn complete_add_combatant(&mut self, state: AddCombatantState) -> Result<(), String> {
-        let initiative = state
-            .initiative
-            .parse::<i32>()
-            .map_err(|_| "Invalid initiative value")?;
-        let hp = state.hp.parse::<i32>().map_err(|_| "Invalid HP value")?;
+        let built = self.build_combatant(&state)?;
+        self.record_history(&format!("Add {}", built.combatant.name));
+        self.insert_combatant(built);
+        self.input_mode = InputMode::Normal;
+        self.set_message(format!("Added combatant: {}", state.name));
+        Ok(())
+    }
+
+    /// Parses the add-combatant prompts and rolls initiative and HP without touching
+    /// the encounter. HP accepts a dice formula, so templates can roll their hit dice.
+    fn build_combatant(&mut self, state: &AddCombatantState) -> Result<BuiltCombatant, String> {
+        let initiative_modifier = parse_initiative_modifier(&state.initiative_modifier)?;
+        let initiative_roll = if state.initiative.trim().is_empty() {
+            self.dice.roll(&DiceExpr::d20(initiative_modifier))
+        } else {
+            self.dice
+                .roll_str(&state.initiative)
+                .map_err(|e| format!("Invalid initiative value: {}", e))?
+        };
+        let hp_roll = self
+            .dice
+            .roll_str(&state.hp)
+            .map_err(|_| "Invalid HP value")?;
+        // A low roll such as 1d8-1 still leaves the creature standing
+        let hp = if hp_roll.is_fixed() {
+            hp_roll.total
+        } else {
+            hp_roll.total.max(1)
+        };
         let ac = state.ac.parse::<i32>().map_err(|_| "Invalid AC value")?;
         let is_player =
             state.is_player.to_lowercase() == "y" || state.is_player.to_lowercase() == "yes";
@@ -22,30 +52,647 @@ impl App {
             return Err("Name cannot be empty".to_string());
         }
 
-        let combatant = Combatant::new(state.name.clone(), initiative, hp, ac, is_player);
+        let mut combatant =
+            Combatant::new(state.name.clone(), initiative_roll.total, hp, ac, is_player);
+        combatant.initiative_modifier = initiative_modifier;
+        combatant.legendary_actions = LegendaryPool::from_max(state.legendary_actions);
+        combatant.legendary_resistances = LegendaryPool::from_max(state.legendary_resistances);
+        combatant.defenses = state.defenses.clone();
+        combatant.stat_block = state.stat_block.clone().map(|block| *block);
+        combatant.hit_dice = state.hit_dice.clone();
+        combatant.resources = state.resources.clone();
+        if let Some(member) = &state.roster_member {
+            member.apply_to(&mut combatant);
+        }
+        Ok(BuiltCombatant {
+            combatant,
+            initiative_roll,
+            hp_roll,
+        })
+    }
+
+    /// Adds a built combatant to the order and logs it with its initiative and HP rolls.
+    fn insert_combatant(&mut self, built: BuiltCombatant) {
+        let BuiltCombatant {
+            combatant,
+            initiative_roll,
+            hp_roll,
+        } = built;
+        let entry = format!(
+            "Added {} (HP {}, AC {}, Init {}, {})",
+            combatant.name,
+            combatant.hp_max,
+            combatant.armor_class,
+            combatant.initiative,
+            if combatant.is_player { "PC" } else { "NPC" }
+        );
+        self.add_to_encounter(combatant);
+        self.log_roll("Initiative", &initiative_roll);
+        self.log_roll("Hit points", &hp_roll);
+        self.push_log(entry);
+    }
+
+    /// Validates an index that must point at a creature rather than a turn entry.
+    fn require_creature(&self, index: usize) -> Result<(), String> {
+        match self.encounter.combatants.get(index) {
+            None => Err("Invalid combatant index".to_string()),
+            Some(c) if !c.is_creature() => Err(format!(
+                "{} is a {} entry, not a creature",
+                c.name,
+                c.kind.as_str().to_lowercase()
+            )),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// The creature whose turn it is, credited with damage dealt without a named
+    /// attacker. Lair actions, hazards and other turn entries credit nobody.
+    pub fn acting_creature(&self) -> Option<usize> {
+        let index = self.encounter.current_turn_index;
+        self.encounter
+            .combatants
+            .get(index)
+            .filter(|c| c.is_creature())
+            .map(|_| index)
+    }
+
+    /// Adds a combatant with a freshly rolled d20 tiebreaker for initiative ties.
+    fn add_to_encounter(&mut self, mut combatant: Combatant) {
+        combatant.tiebreaker = self.dice.roll_die(20);
         self.encounter.add_combatant(combatant);
+    }
+
+    pub fn complete_add_turn_entry(&mut self, state: AddTurnEntryState) -> Result<(), String> {
+        let kind = CombatantKind::turn_entries()
+            .get(state.kind_index)
+            .copied()
+            .ok_or("Invalid entry type")?;
+        let name = if state.name.trim().is_empty() {
+            kind.as_str().to_string()
+        } else {
+            state.name.trim().to_string()
+        };
+        let initiative = if state.initiative.trim().is_empty() {
+            20
+        } else {
+            state
+                .initiative
+                .trim()
+                .parse::<i32>()
+                .map_err(|_| "Invalid initiative count".to_string())?
+        };
+        let reminder = Some(state.reminder.trim().to_string()).filter(|r| !r.is_empty());
+
+        self.record_history(&format!("Add {}", name));
+        let entry = Combatant::new_turn_entry(name.clone(), initiative, kind, reminder);
+        self.add_to_encounter(entry);
         self.input_mode = InputMode::Normal;
-        self.set_message(format!("Added combatant: {}", state.name));
+        self.set_message(format!("Added {}: {}", kind.as_str(), name));
         self.push_log(format!(
-            "Added {} (HP {}, AC {}, Init {}, {})",
-            state.name,
-            hp,
-            ac,
-            initiative,
-            if is_player { "PC" } else { "NPC" }
+            "Added {} '{}' at initiative {}",
+            kind.as_str(),
+            name,
+            initiative
         ));
         Ok(())
     }
 
-    pub fn complete_deal_damage(&mut self, index: usize, damage: i32) -> Result<(), String> {
-        if index >= self.encounter.combatants.len() {
-            return Err("Invalid combatant index".to_string());
+    pub fn complete_resolve_tie(&mut self, index: usize) -> Result<(), String> {
+        if !self.encounter.is_unresolved_tie(index) {
+            return Err("That combatant is not in an unresolved tie".to_string());
+        }
+
+        self.record_history("Resolve initiative tie");
+        let new_index = self.encounter.swap_tied(index)?;
+        let moved = self.encounter.combatants[new_index].name.clone();
+        let other_index = if new_index > index {
+            new_index - 1
+        } else {
+            new_index + 1
+        };
+        let other = self.encounter.combatants[other_index].name.clone();
+        let relation = if new_index > index { "after" } else { "before" };
+        self.input_mode = InputMode::Normal;
+        self.set_message(format!("{} now acts {} {}", moved, relation, other));
+        self.push_log(format!(
+            "Initiative tie resolved: {} acts {} {}",
+            moved, relation, other
+        ));
+        Ok(())
+    }
+
+    pub fn complete_set_legendary(&mut self, state: SetLegendaryState) -> Result<(), String> {
+        self.require_creature(state.combatant_index)?;
+        let parse = |input: &str, label: &str| -> Result<u32, String> {
+            if input.trim().is_empty() {
+                return Ok(0);
+            }
+            input
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| format!("Invalid {} count", label))
+        };
+        let actions = parse(&state.actions, "legendary action")?;
+        let resistances = parse(&state.resistances, "legendary resistance")?;
+
+        self.record_history(&format!(
+            "Legendary pools for {}",
+            self.encounter.combatants[state.combatant_index].name
+        ));
+        let combatant = &mut self.encounter.combatants[state.combatant_index];
+        combatant.legendary_actions = LegendaryPool::from_max(actions);
+        combatant.legendary_resistances = LegendaryPool::from_max(resistances);
+        let name = combatant.name.clone();
+        self.input_mode = InputMode::Normal;
+        self.set_message(format!(
+            "{} has {} legendary action(s) and {} legendary resistance(s).",
+            name, actions, resistances
+        ));
+        self.push_log(format!(
+            "{} set to {} legendary action(s), {} legendary resistance(s)",
+            name, actions, resistances
+        ));
+        Ok(())
+    }
+
+    /// Spends legendary actions. Only allowed outside the creature's own turn.
+    pub fn complete_spend_legendary_action(
+        &mut self,
+        index: usize,
+        cost: u32,
+    ) -> Result<(), String> {
+        let combatant = self
+            .encounter
+            .combatants
+            .get(index)
+            .ok_or("Invalid combatant index")?;
+        let mut pool = combatant
+            .legendary_actions
+            .ok_or(format!("{} has no legendary actions", combatant.name))?;
+        if index == self.encounter.current_turn_index {
+            return Err(format!(
+                "{} can only take legendary actions on another creature's turn",
+                combatant.name
+            ));
+        }
+        pool.spend(cost)?;
+
+        let name = combatant.name.clone();
+        self.record_history(&format!("Legendary action by {}", name));
+        self.encounter.combatants[index].legendary_actions = Some(pool);
+        self.input_mode = InputMode::Normal;
+        let message = format!(
+            "{} uses a legendary action (cost {}, {}/{} left)",
+            name, cost, pool.remaining, pool.max
+        );
+        self.set_message(message.clone());
+        self.push_log(message);
+        Ok(())
+    }
+
+    pub fn complete_use_legendary_resistance(&mut self, index: usize) -> Result<(), String> {
+        let combatant = self
+            .encounter
+            .combatants
+            .get(index)
+            .ok_or("Invalid combatant index")?;
+        let mut pool = combatant
+            .legendary_resistances
+            .ok_or(format!("{} has no legendary resistances", combatant.name))?;
+        pool.spend(1)?;
+
+        let name = combatant.name.clone();
+        self.record_history(&format!("Legendary resistance by {}", name));
+        self.encounter.combatants[index].legendary_resistances = Some(pool);
+        self.input_mode = InputMode::Normal;
+        let message = format!(
+            "{} uses a legendary resistance to succeed ({}/{} left)",
+            name, pool.remaining, pool.max
+        );
+        self.set_message(message.clone());
+        self.push_log(message);
+        Ok(())
+    }
+
+    /// Spends or restores one use of a combatant's slot or feature; the
+    /// resources view stays open.
+    pub fn adjust_resource(
+        &mut self,
+        index: usize,
+        resource_index: usize,
+        spend: bool,
+    ) -> Result<(), String> {
+        self.require_creature(index)?;
+        let combatant = &self.encounter.combatants[index];
+        let name = combatant.name.clone();
+        let mut resource = combatant
+            .resources
+            .get(resource_index)
+            .cloned()
+            .ok_or("Invalid resource selection")?;
+        if spend && !resource.spend() {
+            return Err(format!("{} has no {} left", name, resource.label()));
+        }
+        if !spend && !resource.restore() {
+            return Err(format!("{}'s {} are already full", name, resource.label()));
+        }
+
+        let verb = if spend { "uses" } else { "regains" };
+        self.record_history(&format!("{} {} {}", name, verb, resource.label()));
+        let message = format!(
+            "{} {} {} ({}/{} left)",
+            name,
+            verb,
+            resource.one_use(),
+            resource.remaining(),
+            resource.max
+        );
+        self.encounter.combatants[index].resources[resource_index] = resource;
+        self.set_message(message.clone());
+        self.push_log(message);
+        Ok(())
+    }
+
+    /// Replaces a combatant's resources from a spec, keeping uses already spent.
+    pub fn complete_set_resources(&mut self, index: usize, spec: &str) -> Result<(), String> {
+        self.require_creature(index)?;
+        let mut parsed = resources::parse_resources(spec)?;
+        let combatant = &self.encounter.combatants[index];
+        resources::carry_over_used(&mut parsed, &combatant.resources);
+        let name = combatant.name.clone();
+
+        self.record_history(&format!("Resources for {}", name));
+        self.encounter.combatants[index].resources = parsed;
+        self.input_mode = InputMode::ManagingResources(ResourceState {
+            combatant_index: index,
+            selected_index: 0,
+        });
+        self.set_message(format!("Updated resources for {}", name));
+        Ok(())
+    }
+
+    /// Adds an ongoing damage or healing effect from a spec such as
+    /// `Burning: 1d6 fire start 3`; the effects view stays open.
+    pub fn complete_add_ongoing_effect(&mut self, index: usize, spec: &str) -> Result<(), String> {
+        self.require_creature(index)?;
+        let effect = OngoingEffect::parse(spec)?;
+        DiceExpr::parse(&effect.dice)?;
+        let name = self.encounter.combatants[index].name.clone();
+
+        self.record_history(&format!("{} on {}", effect.name, name));
+        let message = format!("{} gains {}", name, effect.describe());
+        let effects = &mut self.encounter.combatants[index].ongoing_effects;
+        effects.push(effect);
+        self.input_mode = InputMode::ManagingOngoingEffects(StatusSelectionState {
+            combatant_index: index,
+            selected_status_index: effects.len() - 1,
+        });
+        self.set_message(message.clone());
+        self.push_log(message);
+        Ok(())
+    }
+
+    pub fn remove_ongoing_effect(
+        &mut self,
+        index: usize,
+        effect_index: usize,
+    ) -> Result<(), String> {
+        self.require_creature(index)?;
+        let combatant = &self.encounter.combatants[index];
+        let effect = combatant
+            .ongoing_effects
+            .get(effect_index)
+            .ok_or("No ongoing effect selected")?;
+        let message = format!("{} ends on {}", effect.name, combatant.name);
+
+        self.record_history(&message);
+        self.encounter.combatants[index]
+            .ongoing_effects
+            .remove(effect_index);
+        self.set_message(message.clone());
+        self.push_log(message);
+        Ok(())
+    }
+
+    /// Toggles an action economy slot for the combatant whose turn it is.
+    pub fn toggle_economy(&mut self, slot: EconomySlot) {
+        let index = self.encounter.current_turn_index;
+        if let Err(e) = self.require_creature(index) {
+            self.set_message(e);
+            return;
+        }
+
+        let name = self.encounter.combatants[index].name.clone();
+        self.record_history(&format!("Toggle {} for {}", slot.as_str(), name));
+        let used = self.encounter.combatants[index].economy.toggle(slot);
+        if used && slot == EconomySlot::Reaction {
+            self.push_log(format!("{} uses its reaction", name));
+        }
+        self.set_message(format!(
+            "{}: {} {}",
+            name,
+            slot.as_str(),
+            if used { "used" } else { "available" }
+        ));
+    }
+
+    /// Spends a reaction for any creature, typically outside its own turn.
+    pub fn complete_use_reaction(&mut self, index: usize) -> Result<(), String> {
+        self.require_creature(index)?;
+        let name = self.encounter.combatants[index].name.clone();
+        if self.encounter.combatants[index].economy.reaction {
+            return Err(format!("{} has already used its reaction this round", name));
+        }
+
+        self.record_history(&format!("Reaction by {}", name));
+        self.encounter.combatants[index].economy.reaction = true;
+        self.input_mode = InputMode::Normal;
+        let message = format!("{} uses its reaction", name);
+        self.set_message(message.clone());
+        self.push_log(message);
+        Ok(())
+    }
+
+    /// Takes the current creature out of the order until the DM brings it back.
+    pub fn delay_current_turn(&mut self) {
+        let index = self.encounter.current_turn_index;
+        if let Err(e) = self.require_creature(index) {
+            self.set_message(e);
+            return;
+        }
+
+        let name = self.encounter.combatants[index].name.clone();
+        self.record_history(&format!("Delay {}", name));
+        if let Err(e) = self.encounter.delay_current() {
+            self.set_message(e);
+            return;
         }
+        self.push_log(format!("{} delays its turn", name));
+        self.set_message(format!("{} delays its turn", name));
+        self.begin_current_turn();
+    }
+
+    /// Re-enters a delayed creature directly after `after_index` (or alone in an empty order).
+    pub fn complete_resume_delayed(
+        &mut self,
+        delayed_index: usize,
+        after_index: Option<usize>,
+    ) -> Result<(), String> {
+        let name = self
+            .encounter
+            .delayed
+            .get(delayed_index)
+            .map(|c| c.name.clone())
+            .ok_or_else(|| "Invalid delayed combatant".to_string())?;
+        let after = after_index
+            .and_then(|i| self.encounter.combatants.get(i))
+            .map(|c| c.name.clone());
+
+        self.record_history(&format!("Resume {}", name));
+        let position = self.encounter.resume_delayed(delayed_index, after_index)?;
+        let initiative = self.encounter.combatants[position].initiative;
+
+        self.input_mode = InputMode::Normal;
+        let message = match after {
+            Some(after) => format!(
+                "{} re-enters after {} at initiative {}",
+                name, after, initiative
+            ),
+            None => format!("{} re-enters the initiative order", name),
+        };
+        self.set_message(message.clone());
+        self.push_log(message);
+        Ok(())
+    }
+
+    /// Stores a readied action trigger for the current creature.
+    pub fn complete_ready_action(&mut self, trigger: String) -> Result<(), String> {
+        let trigger = trigger.trim().to_string();
+        if trigger.is_empty() {
+            return Err("Describe the trigger for the readied action".to_string());
+        }
+        let index = self.encounter.current_turn_index;
+        self.require_creature(index)?;
+
+        let name = self.encounter.combatants[index].name.clone();
+        self.record_history(&format!("Ready action for {}", name));
+        self.encounter.combatants[index].readied = Some(trigger.clone());
+
+        self.input_mode = InputMode::Normal;
+        let message = format!("{} readies an action: {}", name, trigger);
+        self.set_message(message.clone());
+        self.push_log(message);
+        Ok(())
+    }
+
+    /// Replaces a combatant's notes; blank input clears them.
+    pub fn complete_edit_notes(&mut self, index: usize, notes: String) -> Result<(), String> {
+        self.require_creature(index)?;
+        let notes = notes.trim().to_string();
+        let name = self.encounter.combatants[index].name.clone();
+        self.record_history(&format!("Edit notes for {}", name));
+        self.encounter.combatants[index].notes = notes.clone();
+
+        self.input_mode = InputMode::Normal;
+        if notes.is_empty() {
+            self.set_message(format!("Cleared notes for {}", name));
+        } else {
+            self.set_message(format!("Updated notes for {}", name));
+        }
+        Ok(())
+    }
+
+    /// Fires a readied action, which spends the creature's reaction.
+    pub fn complete_trigger_readied(&mut self, index: usize) -> Result<(), String> {
+        self.require_creature(index)?;
+        let combatant = &self.encounter.combatants[index];
+        let name = combatant.name.clone();
+        let Some(trigger) = combatant.readied.clone() else {
+            return Err(format!("{} has no readied action", name));
+        };
+        if combatant.economy.reaction {
+            return Err(format!("{} has already used its reaction this round", name));
+        }
+
+        self.record_history(&format!("Readied action by {}", name));
+        let combatant = &mut self.encounter.combatants[index];
+        combatant.readied = None;
+        combatant.economy.reaction = true;
+
+        self.input_mode = InputMode::Normal;
+        let message = format!("{} takes its readied action ({})", name, trigger);
+        self.set_message(message.clone());
+        self.push_log(message);
+        Ok(())
+    }
+
+    /// Deals damage after applying the target's resistances, immunities and vulnerabilities.
+    /// `source` is the creature credited with it in the combat summary.
+    pub fn complete_deal_damage(
+        &mut self,
+        index: usize,
+        raw_damage: i32,
+        damage_type: DamageType,
+        source: Option<usize>,
+    ) -> Result<(), String> {
+        self.require_creature(index)?;
+
+        self.record_history(&format!(
+            "Damage to {}",
+            self.encounter.combatants[index].name
+        ));
+        let outcome =
+            self.apply_damage_to(index, raw_damage, damage_type, SaveOutcome::Full, source);
+        match outcome.check {
+            Some(check) => {
+                self.set_message(check_prompt_message(&outcome.name, &check.1, check.0.dc));
+                self.input_mode = InputMode::ConcentrationCheck(check.0);
+            }
+            None => {
+                self.input_mode = InputMode::Normal;
+                self.set_message(outcome.message);
+            }
+        }
+        Ok(())
+    }
+
+    /// Rolls an attack and compares it to the target's AC. A bare number is the
+    /// attack bonus added to a d20; anything with dice is rolled as written.
+    /// Natural 1s always miss and natural 20s always hit as a critical.
+    pub fn complete_attack(
+        &mut self,
+        attacker: usize,
+        target: usize,
+        input: &str,
+    ) -> Result<(), String> {
+        self.require_creature(attacker)?;
+        self.require_creature(target)?;
+        let input = input.trim();
+        let roll = if input.contains(['d', 'D']) {
+            self.dice.roll_str(input)?
+        } else {
+            let bonus = if input.is_empty() {
+                0
+            } else {
+                input
+                    .trim_start_matches('+')
+                    .parse::<i32>()
+                    .map_err(|_| format!("Invalid attack bonus: {}", input))?
+            };
+            self.dice.roll(&DiceExpr::d20(bonus))
+        };
+
+        let attacker_name = self.encounter.combatants[attacker].name.clone();
+        let target_name = self.encounter.combatants[target].name.clone();
+        let ac = self.encounter.combatants[target].armor_class;
+        let natural = roll.natural_d20();
+        let critical = natural == Some(20);
+        let hit = critical || (natural != Some(1) && roll.total >= ac);
+        let result = if critical {
+            "critical hit!"
+        } else if natural == Some(1) {
+            "natural 1, miss"
+        } else if hit {
+            "hit"
+        } else {
+            "miss"
+        };
+
+        self.record_history(&format!("Attack by {}", attacker_name));
+        let message = format!(
+            "{} attacks {}: {} vs AC {} - {}",
+            attacker_name, target_name, roll.total, ac, result
+        );
+        self.log_roll("Attack", &roll);
+        self.push_log(message.clone());
+
+        if hit {
+            self.input_mode = InputMode::Attacking(AttackState {
+                step: 3,
+                attacker,
+                target,
+                input: String::new(),
+                critical,
+            });
+            let dice = if critical { " (dice are doubled)" } else { "" };
+            self.set_message(format!("{} | Enter damage{}", message, dice));
+        } else {
+            self.input_mode = InputMode::Normal;
+            self.set_message(message);
+        }
+        Ok(())
+    }
+
+    /// Applies one damage roll to several creatures, each with its own save outcome.
+    /// Concentration checks are queued and prompted one after another.
+    pub fn complete_area_damage(
+        &mut self,
+        targets: &[(usize, SaveOutcome)],
+        raw_damage: i32,
+        damage_type: DamageType,
+    ) -> Result<(), String> {
+        if targets.is_empty() {
+            return Err("Select at least one target".to_string());
+        }
+        for (index, _) in targets {
+            self.require_creature(*index)?;
+        }
+
+        self.record_history(&format!("Area damage to {} targets", targets.len()));
+        let typed = if damage_type == DamageType::Untyped {
+            String::new()
+        } else {
+            format!(" {}", damage_type.as_str().to_lowercase())
+        };
+        self.push_log(format!(
+            "Area damage: {}{} to {} target(s)",
+            raw_damage,
+            typed,
+            targets.len()
+        ));
+
+        let source = self.acting_creature();
+        let mut checks = Vec::new();
+        for (index, save) in targets {
+            let outcome = self.apply_damage_to(*index, raw_damage, damage_type, *save, source);
+            if let Some((state, _)) = outcome.check {
+                checks.push(state);
+            }
+        }
+
+        self.input_mode = InputMode::Normal;
+        self.pending_prompts
+            .extend(checks.into_iter().map(InputMode::ConcentrationCheck));
+        let pending = self.pending_prompts.len();
+        self.finish_prompt();
+        let mut message = format!("Area damage{} hit {} target(s)", typed, targets.len());
+        if pending > 0 {
+            message.push_str(&format!(" | {} concentration check(s) to roll", pending));
+        }
+        self.set_message(message);
+        Ok(())
+    }
 
+    /// Shared damage pipeline: save, defenses, temp HP, death saves and concentration.
+    /// Logs the result; the caller decides how to prompt any concentration check.
+    fn apply_damage_to(
+        &mut self,
+        index: usize,
+        raw_damage: i32,
+        damage_type: DamageType,
+        save: SaveOutcome,
+        source: Option<usize>,
+    ) -> DamageOutcome {
         let combatant = &mut self.encounter.combatants[index];
+        let damage = combatant
+            .defenses
+            .apply(save.apply(raw_damage), damage_type);
         let was_unconscious = combatant.is_unconscious();
         let had_concentration = combatant.concentration.clone();
         combatant.take_damage(damage);
+        if damage > 0 && !combatant.damage_taken_since_turn.contains(&damage_type) {
+            combatant.damage_taken_since_turn.push(damage_type);
+        }
         let name = combatant.name.clone();
         let hp = combatant.hp_current;
         let mut extra_message: Option<String> = None;
@@ -71,62 +718,99 @@ impl App {
                 }
             }
         }
+
+        let base = damage_log_message(&name, raw_damage, damage, damage_type, save, hp);
+        let mut check = None;
         if combatant.is_unconscious() {
             combatant.clear_concentration();
-        } else if let Some(info) = had_concentration {
-            let dc = std::cmp::max(10, damage / 2);
-            self.input_mode = InputMode::ConcentrationCheck(ConcentrationCheckState {
+        } else if let Some(info) = had_concentration.filter(|_| damage > 0) {
+            // No damage taken (0 entered, or negated by a save or immunity) means no save
+            let state = ConcentrationCheckState {
                 combatant_index: index,
-                dc,
+                dc: std::cmp::max(10, damage / 2),
                 input: String::new(),
-            });
-            self.set_message(format!(
-                "{} took damage while concentrating on {}. Roll CON save (DC {}).",
-                name, info.spell_name, dc
-            ));
-            return Ok(());
+            };
+            check = Some((state, info.spell_name));
         }
 
-        self.input_mode = InputMode::Normal;
-        let base = format!("{} took {} damage (HP: {})", name, damage, hp);
-        if let Some(extra) = extra_message {
-            self.set_message(format!("{} | {}", base, extra));
-        } else {
-            self.set_message(base.clone());
+        let source = source
+            .and_then(|i| self.encounter.combatants.get(i))
+            .map(|c| c.name.clone());
+        self.encounter
+            .record_damage(source.as_deref(), &name, damage);
+        self.push_log(base.clone());
+        let message = match extra_message {
+            Some(extra) => format!("{} | {}", base, extra),
+            None => base,
+        };
+        DamageOutcome {
+            name,
+            message,
+            check,
+        }
+    }
+
+    /// Adds or removes a resistance, immunity or vulnerability on a creature.
+    pub fn toggle_defense(
+        &mut self,
+        index: usize,
+        kind: DefenseKind,
+        damage_type: DamageType,
+    ) -> Result<(), String> {
+        self.require_creature(index)?;
+        if damage_type == DamageType::Untyped {
+            return Err("Untyped damage can't be resisted".to_string());
         }
-        self.push_log(base);
+
+        let name = self.encounter.combatants[index].name.clone();
+        self.record_history(&format!("Change {} defenses", name));
+        let added = self.encounter.combatants[index]
+            .defenses
+            .toggle(kind, damage_type);
+        self.set_message(format!(
+            "{} {} {} {}",
+            name,
+            if added { "gains" } else { "loses" },
+            damage_type.as_str(),
+            kind.as_str()
+        ));
         Ok(())
     }
 
     pub fn complete_heal(&mut self, index: usize, amount: i32) -> Result<(), String> {
-        if index >= self.encounter.combatants.len() {
-            return Err("Invalid combatant index".to_string());
-        }
+        self.require_creature(index)?;
+
+        self.record_history(&format!("Heal {}", self.encounter.combatants[index].name));
+        let msg = self.apply_healing_to(index, amount);
+        self.input_mode = InputMode::Normal;
+        self.set_message(msg.clone());
+        self.push_log(msg);
+        Ok(())
+    }
 
+    /// Heals and clears death saves once above 0 HP; returns the log line.
+    fn apply_healing_to(&mut self, index: usize, amount: i32) -> String {
         let combatant = &mut self.encounter.combatants[index];
         combatant.heal(amount);
-        let name = combatant.name.clone();
-        let hp = combatant.hp_current;
-
         if combatant.hp_current > 0 {
             combatant.clear_death_saves();
             // healing to positive HP keeps concentration as-is
         }
-
-        self.input_mode = InputMode::Normal;
-        let msg = format!("{} healed {} HP (HP: {})", name, amount, hp);
-        self.set_message(msg.clone());
-        self.push_log(msg);
-        Ok(())
+        format!(
+            "{} healed {} HP (HP: {})",
+            combatant.name, amount, combatant.hp_current
+        )
     }
 
     pub fn complete_grant_temp_hp(&mut self, index: usize, amount: i32) -> Result<(), String> {
-        if index >= self.encounter.combatants.len() {
-            return Err("Invalid combatant index".to_string());
-        }
+        self.require_creature(index)?;
         if amount < 0 {
             return Err("Temp HP must be non-negative".to_string());
         }
+        self.record_history(&format!(
+            "Temp HP for {}",
+            self.encounter.combatants[index].name
+        ));
         let combatant = &mut self.encounter.combatants[index];
         let name = combatant.name.clone();
         combatant.grant_temp_hp(amount);
@@ -137,47 +821,196 @@ impl App {
         Ok(())
     }
 
+    pub fn complete_set_exhaustion(&mut self, index: usize, level: u8) -> Result<(), String> {
+        self.require_creature(index)?;
+        if level > MAX_EXHAUSTION {
+            return Err(format!("Exhaustion level must be 0-{}", MAX_EXHAUSTION));
+        }
+        self.record_history(&format!(
+            "Exhaustion for {}",
+            self.encounter.combatants[index].name
+        ));
+        let combatant = &mut self.encounter.combatants[index];
+        combatant.exhaustion = level;
+        let name = combatant.name.clone();
+        self.input_mode = InputMode::Normal;
+        let msg = match level {
+            0 => format!("{} is no longer exhausted", name),
+            MAX_EXHAUSTION => format!("{} dies of exhaustion", name),
+            _ => format!("{} is at exhaustion level {}", name, level),
+        };
+        self.set_message(msg.clone());
+        self.push_log(msg);
+        Ok(())
+    }
+
+    /// Adds a condition, optionally with a save the bearer repeats at the end of each turn.
     pub fn complete_add_status(
         &mut self,
         combatant_index: usize,
         condition: ConditionType,
         duration: i32,
+        save: Option<RepeatSave>,
     ) -> Result<(), String> {
-        if combatant_index >= self.encounter.combatants.len() {
-            return Err("Invalid combatant index".to_string());
-        }
+        self.require_creature(combatant_index)?;
 
-        let effect = StatusEffect::new(condition, duration, None);
+        self.record_history(&format!(
+            "{} on {}",
+            condition.as_str(),
+            self.encounter.combatants[combatant_index].name
+        ));
+        let effect = StatusEffect::new(condition, duration, None).with_save(save);
         let combatant = &mut self.encounter.combatants[combatant_index];
         combatant.add_status_effect(effect);
         let name = combatant.name.clone();
+        let save_ends = save
+            .map(|save| format!(" ({} save ends)", save.label()))
+            .unwrap_or_default();
 
         self.input_mode = InputMode::Normal;
         self.set_message(format!(
-            "Added {} to {} for {} rounds",
+            "Added {} to {} for {} rounds{}",
             condition.as_str(),
             name,
-            duration
+            duration,
+            save_ends
         ));
         self.push_log(format!(
-            "{} gains {} for {}",
+            "{} gains {} for {}{}",
             name,
             condition.as_str(),
             if duration >= 0 {
                 format!("{} rounds", duration)
             } else {
                 "indefinite".to_string()
-            }
+            },
+            save_ends
         ));
         Ok(())
     }
 
+    /// Applies a PC's entered end-of-turn save. It's part of ending the turn, so
+    /// it shares the "Next turn" undo step rather than recording its own.
+    pub fn complete_repeat_save(
+        &mut self,
+        state: RepeatSaveState,
+        roll_total: i32,
+    ) -> Result<(), String> {
+        self.require_creature(state.combatant_index)?;
+        let name = self.encounter.combatants[state.combatant_index]
+            .name
+            .clone();
+        let message = self
+            .resolve_repeat_save(
+                state.combatant_index,
+                state.condition,
+                state.save,
+                roll_total,
+            )
+            .unwrap_or_else(|| {
+                format!(
+                    "{} no longer has {} with that save",
+                    name,
+                    state.condition.as_str()
+                )
+            });
+        self.set_message(message);
+        self.input_mode = InputMode::Normal;
+        self.finish_prompt();
+        Ok(())
+    }
+
+    /// End-of-turn saves against the combatant's conditions: NPCs roll with their
+    /// stat block's save bonus, PCs are prompted one after another.
+    fn roll_repeat_saves(&mut self, index: usize) -> Vec<String> {
+        let Some(combatant) = self.encounter.combatants.get(index) else {
+            return Vec::new();
+        };
+        if combatant.is_dead() {
+            return Vec::new();
+        }
+        let name = combatant.name.clone();
+        let is_player = combatant.is_player;
+        let stat_block = combatant.stat_block.clone();
+        let saves: Vec<(ConditionType, RepeatSave)> = combatant
+            .status_effects
+            .iter()
+            .filter_map(|e| Some((e.condition, e.save?)))
+            .collect();
+
+        let mut messages = Vec::new();
+        for (condition, save) in saves {
+            if is_player {
+                self.pending_prompts
+                    .push_back(InputMode::RepeatSave(RepeatSaveState {
+                        combatant_index: index,
+                        condition,
+                        save,
+                        input: String::new(),
+                    }));
+                messages.push(format!(
+                    "{}: roll a {} save to end {}",
+                    name,
+                    save.label(),
+                    condition.as_str()
+                ));
+                continue;
+            }
+
+            let modifier = stat_block
+                .as_ref()
+                .map_or(0, |block| block.save_modifier(save.ability));
+            let roll = self.dice.roll(&DiceExpr::d20(modifier));
+            self.log_roll(&format!("{} save", save.ability.as_str()), &roll);
+            messages.extend(self.resolve_repeat_save(index, condition, save, roll.total));
+        }
+        messages
+    }
+
+    /// Compares a save against the condition's DC, removing it on a success.
+    /// Returns the logged line, or `None` if the condition is gone or was
+    /// re-applied with a different save since the roll was asked for.
+    fn resolve_repeat_save(
+        &mut self,
+        index: usize,
+        condition: ConditionType,
+        save: RepeatSave,
+        total: i32,
+    ) -> Option<String> {
+        let combatant = &mut self.encounter.combatants[index];
+        let position = combatant
+            .status_effects
+            .iter()
+            .position(|e| e.condition == condition && e.save == Some(save))?;
+        let success = total >= save.dc;
+        if success {
+            combatant.status_effects.remove(position);
+        }
+        let message = format!(
+            "{} {} a {} save against {} ({} vs DC {}){}",
+            combatant.name,
+            if success { "succeeds on" } else { "fails" },
+            save.ability.as_str(),
+            condition.as_str(),
+            total,
+            save.dc,
+            if success {
+                format!("; {} ends", condition.as_str())
+            } else {
+                String::new()
+            }
+        );
+        self.push_log(message.clone());
+        Some(message)
+    }
+
     pub fn complete_remove(&mut self, index: usize) -> Result<(), String> {
         if index >= self.encounter.combatants.len() {
             return Err("Invalid combatant index".to_string());
         }
 
         let name = self.encounter.combatants[index].name.clone();
+        self.record_history(&format!("Remove {}", name));
         self.encounter.remove_combatant(index);
 
         self.input_mode = InputMode::Normal;
@@ -203,6 +1036,8 @@ impl App {
         }
 
         let name = combatant.name.clone();
+        self.record_history(&format!("Death save for {}", name));
+        let combatant = &mut self.encounter.combatants[index];
         let outcome = combatant.apply_death_save_roll(roll);
         self.input_mode = InputMode::Normal;
 
@@ -257,9 +1092,7 @@ impl App {
         &mut self,
         state: AddConcentrationState,
     ) -> Result<(), String> {
-        if state.combatant_index >= self.encounter.combatants.len() {
-            return Err("Invalid combatant index".to_string());
-        }
+        self.require_creature(state.combatant_index)?;
 
         let con_mod = state
             .con_mod
@@ -270,6 +1103,10 @@ impl App {
             return Err("Spell name cannot be empty".to_string());
         }
 
+        self.record_history(&format!(
+            "Concentration for {}",
+            self.encounter.combatants[state.combatant_index].name
+        ));
         let info = ConcentrationInfo::new(state.spell_name.clone(), con_mod);
         let combatant = &mut self.encounter.combatants[state.combatant_index];
         let name = combatant.name.clone();
@@ -295,13 +1132,18 @@ impl App {
             return Err("Invalid combatant index".to_string());
         }
 
-        let combatant = &mut self.encounter.combatants[state.combatant_index];
-        let Some(info) = combatant.concentration.clone() else {
+        let Some(info) = self.encounter.combatants[state.combatant_index]
+            .concentration
+            .clone()
+        else {
             return Err("Combatant is not concentrating".to_string());
         };
 
-        let name = combatant.name.clone();
-        self.input_mode = InputMode::Normal;
+        let name = self.encounter.combatants[state.combatant_index]
+            .name
+            .clone();
+        self.record_history(&format!("Concentration check for {}", name));
+        let combatant = &mut self.encounter.combatants[state.combatant_index];
 
         if roll_total >= state.dc {
             self.set_message(format!(
@@ -316,6 +1158,8 @@ impl App {
             ));
         }
 
+        self.input_mode = InputMode::Normal;
+        self.finish_prompt();
         Ok(())
     }
 
@@ -324,9 +1168,10 @@ impl App {
             return Err("Invalid combatant index".to_string());
         }
 
-        let combatant = &mut self.encounter.combatants[index];
-        let name = combatant.name.clone();
-        if combatant.concentration.is_some() {
+        let name = self.encounter.combatants[index].name.clone();
+        if self.encounter.combatants[index].concentration.is_some() {
+            self.record_history(&format!("Clear concentration for {}", name));
+            let combatant = &mut self.encounter.combatants[index];
             combatant.clear_concentration();
             self.set_message(format!("{} stops concentrating.", name));
         } else {
@@ -345,8 +1190,17 @@ impl App {
             return Err("Invalid combatant index".to_string());
         }
 
+        let name = self.encounter.combatants[combatant_index].name.clone();
+        let status_count = self.encounter.combatants[combatant_index]
+            .status_effects
+            .len();
+        if status_index.is_some_and(|idx| idx >= status_count) {
+            return Err("Invalid status selection".to_string());
+        }
+        if status_count > 0 {
+            self.record_history(&format!("Clear status on {}", name));
+        }
         let combatant = &mut self.encounter.combatants[combatant_index];
-        let name = combatant.name.clone();
         match status_index {
             Some(idx) => {
                 if idx < combatant.status_effects.len() {
@@ -374,35 +1228,126 @@ impl App {
     }
 
     pub fn add_combatant_from_template(&mut self, template_index: usize) -> Result<(), String> {
-        if template_index >= self.templates.len() {
+        let Some(tpl) = self.templates.get(template_index).cloned() else {
             return Err("Invalid template selection".to_string());
-        }
-        let tpl = self.templates[template_index].clone();
-        let state = AddCombatantState {
-            name: tpl.name.clone(),
-            hp: tpl.hp_max.to_string(),
-            ac: tpl.armor_class.to_string(),
-            is_player: if tpl.is_player {
-                "y".to_string()
-            } else {
-                "n".to_string()
-            },
-            step: 1, // next prompt will be initiative
-            ..Default::default()
         };
+        let state = template_state(&tpl, self.roll_hit_points);
+        if !tpl.is_player {
+            // NPCs roll d20 + modifier straight away
+            return self.complete_add_combatant(state);
+        }
         self.input_mode = InputMode::AddingCombatant(state);
-        self.set_message(format!("Set initiative for template: {}", tpl.name));
+        self.set_message(format!(
+            "Set initiative for template: {} (blank rolls d20{:+})",
+            tpl.name, tpl.initiative_modifier
+        ));
         Ok(())
     }
 
-    pub fn save_template_from_combatant(&mut self, combatant_index: usize) -> Result<(), String> {
-        if combatant_index >= self.encounter.combatants.len() {
-            return Err("Invalid combatant index".to_string());
+    pub fn complete_add_copies(&mut self, state: CopiesState) -> Result<(), String> {
+        let Some(tpl) = self.templates.get(state.template_index).cloned() else {
+            return Err("Invalid template selection".to_string());
+        };
+        let copies = state
+            .count
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or("Number of copies must be a positive number")?;
+        self.add_template_copies(&tpl, copies, state.group_initiative)
+    }
+
+    /// Adds `copies` of a bestiary monster as one undo step.
+    pub fn add_bestiary_copies(
+        &mut self,
+        index: usize,
+        copies: u32,
+        group_initiative: bool,
+    ) -> Result<(), String> {
+        let Some(tpl) = self.bestiary.get(index).cloned() else {
+            return Err("Invalid bestiary selection".to_string());
+        };
+        self.add_template_copies(&tpl, copies, group_initiative)
+    }
+
+    /// Several copies are numbered after any existing "Goblin N" and each rolls its
+    /// own initiative, unless `group_initiative` has them share the first roll.
+    fn add_template_copies(
+        &mut self,
+        tpl: &CombatantTemplate,
+        copies: u32,
+        group_initiative: bool,
+    ) -> Result<(), String> {
+        let names = if copies > 1 {
+            self.encounter.numbered_names(&tpl.name, copies)
+        } else {
+            vec![tpl.name.clone()]
+        };
+
+        let mut built: Vec<BuiltCombatant> = Vec::new();
+        for name in names {
+            let mut state = AddCombatantState {
+                name,
+                ..template_state(tpl, self.roll_hit_points)
+            };
+            if let Some(first) = built.first().filter(|_| group_initiative) {
+                state.initiative = first.combatant.initiative.to_string();
+            }
+            built.push(self.build_combatant(&state)?);
+        }
+
+        let label = if copies > 1 {
+            format!("Add {} x{}", tpl.name, copies)
+        } else {
+            format!("Add {}", tpl.name)
+        };
+        self.record_history(&label);
+        for combatant in built {
+            self.insert_combatant(combatant);
         }
+        self.input_mode = InputMode::Normal;
+        self.set_message(match (copies > 1, group_initiative) {
+            (true, true) => format!("Added {} copies of {} on one initiative", copies, tpl.name),
+            (true, false) => format!("Added {} copies of {}", copies, tpl.name),
+            _ => format!("Added combatant: {}", tpl.name),
+        });
+        Ok(())
+    }
+
+    pub fn save_template_from_combatant(&mut self, combatant_index: usize) -> Result<(), String> {
+        self.require_creature(combatant_index)?;
         let c = &self.encounter.combatants[combatant_index];
-        let tpl =
-            CombatantTemplate::from_stats(c.name.clone(), c.hp_max, c.armor_class, c.is_player);
+        let mut tpl = CombatantTemplate::from_stats(
+            c.name.clone(),
+            base_hit_points(c),
+            c.armor_class,
+            c.is_player,
+            c.initiative_modifier,
+        );
+        tpl.legendary_actions = c.legendary_actions.map_or(0, |p| p.max);
+        tpl.legendary_resistances = c.legendary_resistances.map_or(0, |p| p.max);
+        tpl.defenses = c.defenses.clone();
+        tpl.stat_block = c.stat_block.clone();
+        tpl.hit_dice = c.hit_dice.clone();
+        tpl.resources = resources::fresh(&c.resources);
+
+        self.store_template(tpl);
+        self.input_mode = InputMode::Normal;
+        Ok(())
+    }
+
+    /// Copies a bestiary monster into `templates.json`; the browser stays open.
+    pub fn save_bestiary_template(&mut self, index: usize) -> Result<(), String> {
+        let Some(tpl) = self.bestiary.get(index).cloned() else {
+            return Err("Invalid bestiary selection".to_string());
+        };
+        self.store_template(tpl);
+        Ok(())
+    }
 
+    /// Adds or replaces (by name) a template and writes `templates.json`.
+    fn store_template(&mut self, tpl: CombatantTemplate) {
         if let Some(existing) = self
             .templates
             .iter_mut()
@@ -413,7 +1358,7 @@ impl App {
             self.templates.push(tpl.clone());
         }
 
-        if let Err(err) = save_templates(&self.templates) {
+        if let Err(err) = save_templates(&self.data_dir, &self.templates) {
             self.set_message(format!(
                 "Saved template in memory but failed to write file: {}",
                 err
@@ -421,11 +1366,247 @@ impl App {
         } else {
             self.set_message(format!("Saved template: {}", tpl.name));
         }
-        self.input_mode = InputMode::Normal;
-        Ok(())
+    }
+
+    /// Snapshots encounter and log so the next mutation can be undone.
+    fn record_history(&mut self, label: &str) {
+        self.history.record(label, &self.encounter, &self.log);
+    }
+
+    pub fn undo(&mut self) {
+        match self.history.undo(&self.encounter, &self.log) {
+            Some(snapshot) => {
+                self.encounter = snapshot.encounter;
+                self.log = snapshot.log;
+                self.set_message(format!("Undid: {}", snapshot.label));
+            }
+            None => self.set_message("Nothing to undo".to_string()),
+        }
+    }
+
+    pub fn redo(&mut self) {
+        match self.history.redo(&self.encounter, &self.log) {
+            Some(snapshot) => {
+                self.encounter = snapshot.encounter;
+                self.log = snapshot.log;
+                self.set_message(format!("Redid: {}", snapshot.label));
+            }
+            None => self.set_message("Nothing to redo".to_string()),
+        }
+    }
+
+    pub fn advance_turn(&mut self) {
+        if self.encounter.combatants.is_empty() {
+            return;
+        }
+        self.record_history("Next turn");
+        let ending = self.encounter.current_turn_index;
+        let mut ended = self.apply_ongoing_effects(ending, TurnBoundary::End);
+        ended.extend(self.roll_repeat_saves(ending));
+        self.encounter.next_turn();
+        self.clear_message();
+        self.begin_current_turn();
+
+        if !ended.is_empty() {
+            let mut message = ended.join(" | ");
+            if let Some(started) = self.message.take() {
+                message = format!("{} | {}", message, started);
+            }
+            self.set_message(message);
+        }
+        self.finish_prompt();
+    }
+
+    /// Rolls the combatant's ongoing effects for one end of its turn through the
+    /// normal damage and healing paths. Concentration checks are queued.
+    /// Returns a message per effect.
+    fn apply_ongoing_effects(&mut self, index: usize, timing: TurnBoundary) -> Vec<String> {
+        let Some(combatant) = self.encounter.combatants.get(index) else {
+            return Vec::new();
+        };
+        let name = combatant.name.clone();
+        let effects: Vec<OngoingEffect> = combatant
+            .ongoing_effects
+            .iter()
+            .filter(|e| e.timing == timing)
+            .cloned()
+            .collect();
+
+        let mut messages = Vec::new();
+        for effect in effects {
+            let combatant = &self.encounter.combatants[index];
+            if combatant.is_dead() {
+                break;
+            }
+            if let Some(blocked) = effect
+                .suppressed_by
+                .iter()
+                .find(|t| combatant.damage_taken_since_turn.contains(t))
+            {
+                let message = format!(
+                    "{}'s {} doesn't work this turn ({} damage)",
+                    name,
+                    effect.name,
+                    blocked.as_str().to_lowercase()
+                );
+                self.push_log(message.clone());
+                messages.push(message);
+                continue;
+            }
+
+            let roll = match self.dice.roll_str(&effect.dice) {
+                Ok(roll) => roll,
+                Err(e) => {
+                    messages.push(format!("{}: {}", effect.name, e));
+                    continue;
+                }
+            };
+            self.log_roll(&effect.name, &roll);
+            self.push_log(format!("{}'s {}: {}", name, effect.name, roll.total));
+            if effect.healing {
+                let line = self.apply_healing_to(index, roll.total.max(0));
+                self.push_log(line.clone());
+                messages.push(format!("{}: {}", effect.name, line));
+            } else {
+                let outcome = self.apply_damage_to(
+                    index,
+                    roll.total,
+                    effect.damage_type,
+                    SaveOutcome::Full,
+                    None,
+                );
+                messages.push(format!("{}: {}", effect.name, outcome.message));
+                if let Some((check, spell_name)) = outcome.check {
+                    messages.push(check_prompt_message(&name, &spell_name, check.dc));
+                    self.pending_prompts
+                        .push_back(InputMode::ConcentrationCheck(check));
+                }
+            }
+        }
+        messages
+    }
+
+    /// Start-of-turn upkeep for whoever's turn it now is.
+    fn begin_current_turn(&mut self) {
+        let index = self.encounter.current_turn_index;
+        let Some(combatant) = self.encounter.combatants.get_mut(index) else {
+            return;
+        };
+
+        // An unused readied action lapses when the creature's next turn starts
+        if let Some(trigger) = combatant.readied.take() {
+            let message = format!("{}'s readied action lapses ({})", combatant.name, trigger);
+            self.push_log(message);
+        }
+
+        // Legendary actions refresh at the start of the creature's own turn
+        let combatant = &mut self.encounter.combatants[index];
+        if let Some(pool) = combatant.legendary_actions.as_mut() {
+            if pool.remaining < pool.max {
+                pool.refresh();
+                let message = format!(
+                    "{} regains legendary actions ({}/{})",
+                    combatant.name, pool.remaining, pool.max
+                );
+                self.push_log(message);
+            }
+        }
+
+        // Spent recharge abilities roll a d6 to come back
+        let combatant = &self.encounter.combatants[index];
+        let name = combatant.name.clone();
+        let spent: Vec<usize> = (0..combatant.resources.len())
+            .filter(|&i| {
+                let r = &combatant.resources[i];
+                r.used > 0 && r.recharge().is_some()
+            })
+            .collect();
+        let mut recharges = Vec::new();
+        for i in spent {
+            let roll = self.dice.roll_die(6);
+            let resource = &mut self.encounter.combatants[index].resources[i];
+            let min = resource.recharge().unwrap_or(6);
+            let outcome = if roll >= i32::from(min) {
+                resource.used = 0;
+                "recharges"
+            } else {
+                "does not recharge"
+            };
+            recharges.push(format!(
+                "{} {} (rolled {})",
+                resource.label(),
+                outcome,
+                roll
+            ));
+        }
+        if !recharges.is_empty() {
+            for line in &recharges {
+                self.push_log(format!("{}'s {}", name, line));
+            }
+            self.set_message(format!("{}: {}", name, recharges.join("; ")));
+        }
+
+        // Start-of-turn damage and healing; regeneration-style effects only look
+        // at damage taken since this turn began
+        let started = self.apply_ongoing_effects(index, TurnBoundary::Start);
+        self.encounter.combatants[index]
+            .damage_taken_since_turn
+            .clear();
+        if !started.is_empty() {
+            let message = match self.message.take() {
+                Some(recharge) => format!("{} | {}", recharge, started.join(" | ")),
+                None => started.join(" | "),
+            };
+            self.set_message(message);
+        }
+
+        // Lair actions, hazards and events announce their reminder
+        let entry = &self.encounter.combatants[index];
+        if !entry.is_creature() {
+            let text = match entry.reminder.as_deref() {
+                Some(reminder) if !reminder.trim().is_empty() => {
+                    format!("{}: {}", entry.name, reminder)
+                }
+                _ => format!("{} ({})", entry.name, entry.kind.as_str()),
+            };
+            let round = self.encounter.round_number;
+            self.set_message(text.clone());
+            self.push_log(format!("Round {} - {}", round, text));
+        }
+    }
+
+    /// Parses and rolls a dice expression (plain numbers are accepted as-is).
+    pub fn roll_dice(&mut self, input: &str) -> Result<DiceRoll, String> {
+        self.dice.roll_str(input)
+    }
+
+    /// Writes the breakdown of an actual roll to the combat log.
+    pub fn log_roll(&mut self, purpose: &str, roll: &DiceRoll) {
+        if !roll.is_fixed() {
+            self.push_log(format!("{} roll {}", purpose, roll.breakdown()));
+        }
+    }
+
+    /// Holds a prompt's roll until the action it feeds starts logging; dropped
+    /// if the action fails. Fixed numbers are never logged.
+    pub fn queue_roll_log(&mut self, purpose: &str, roll: &DiceRoll) {
+        self.pending_roll =
+            (!roll.is_fixed()).then(|| format!("{} roll {}", purpose, roll.breakdown()));
+    }
+
+    /// Logs the queued roll if the action it fed logged nothing itself.
+    pub fn log_pending_roll(&mut self) {
+        if let Some(line) = self.pending_roll.take() {
+            self.push_log(line);
+        }
     }
 
     fn push_log(&mut self, message: String) {
+        // The action's first line means it went through, so its roll goes in first
+        if let Some(roll) = self.pending_roll.take() {
+            self.log
+                .push(LogEntry::new(self.encounter.round_number, roll));
+        }
         let entry = LogEntry::new(self.encounter.round_number, message);
         self.log.push(entry);
         if self.log.len() > 200 {
@@ -449,9 +1630,19 @@ impl App {
             );
         }
 
-        match save_encounter(&self.encounter, &self.log, &filename) {
+        match save_encounter(
+            &self.data_dir,
+            &self.encounter,
+            &self.log,
+            &self.history,
+            &filename,
+        ) {
             Ok(()) => {
-                self.set_message(format!("Successfully saved encounter: {}", filename));
+                let roster_note = self.write_back_party_roster();
+                self.set_message(with_note(
+                    format!("Successfully saved encounter: {}", filename),
+                    roster_note,
+                ));
                 self.input_mode = InputMode::Normal;
                 Ok(())
             }
@@ -465,10 +1656,11 @@ impl App {
     }
 
     pub fn complete_load_encounter(&mut self, filename: String) -> Result<(), String> {
-        match load_encounter(&filename) {
+        match load_encounter(&self.data_dir, &filename) {
             Ok(saved) => {
                 self.encounter = saved.encounter;
                 self.log = saved.log;
+                self.history = saved.history;
                 self.set_message(format!("Successfully loaded encounter: {}", filename));
                 self.input_mode = InputMode::Normal;
                 Ok(())
@@ -483,7 +1675,7 @@ impl App {
     }
 
     pub fn list_saved_encounters(&self) -> Vec<String> {
-        list_encounter_files().unwrap_or_else(|_| Vec::new())
+        list_encounter_files(&self.data_dir).unwrap_or_else(|_| Vec::new())
     }
 
     pub fn complete_save_library(&mut self, mut state: SaveLibraryState) -> Result<(), String> {
@@ -514,7 +1706,7 @@ impl App {
         state.difficulty = state.difficulty.trim().to_string();
 
         // Check if already exists
-        if library_template_exists(&state.name) {
+        if library_template_exists(&self.data_dir, &state.name) {
             let name = state.name.clone();
             self.input_mode = InputMode::ConfirmingLibraryOverwrite(state);
             self.set_message(format!(
@@ -529,17 +1721,12 @@ impl App {
 
     fn save_library_template_internal(&mut self, state: SaveLibraryState) -> Result<(), String> {
         // Convert current combatants to library combatants (fresh state)
-        let library_combatants: Vec<LibraryCombatant> = self
-            .encounter
-            .combatants
-            .iter()
-            .map(|c| LibraryCombatant {
-                name: c.name.clone(),
-                hp_max: c.hp_max,
-                armor_class: c.armor_class,
-                is_player: c.is_player,
-            })
-            .collect();
+        let library_combatants = library_entries(&self.encounter.combatants);
+        // A blank difficulty is filled in from the party's XP thresholds
+        let difficulty = match self.encounter_difficulty().rating {
+            Some(rating) if state.difficulty.is_empty() => rating.as_str().to_string(),
+            _ => state.difficulty.clone(),
+        };
 
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -549,12 +1736,12 @@ impl App {
         let template = EncounterTemplate {
             name: state.name.clone(),
             description: state.description.clone(),
-            difficulty: state.difficulty.clone(),
+            difficulty,
             combatants: library_combatants,
             created_at: timestamp,
         };
 
-        match save_library_template(&template, &state.name) {
+        match save_library_template(&self.data_dir, &template, &state.name) {
             Ok(()) => {
                 self.set_message(format!("Successfully saved to library: {}", state.name));
                 self.input_mode = InputMode::Normal;
@@ -569,60 +1756,371 @@ impl App {
         }
     }
 
-    pub fn confirm_overwrite_library(&mut self, state: SaveLibraryState) -> Result<(), String> {
-        self.save_library_template_internal(state)
+    /// Saves party levels typed in the difficulty view to `party.json`.
+    pub fn complete_set_party(&mut self, input: &str) -> Result<(), String> {
+        let levels = difficulty::parse_levels(input)?;
+        let party = Party {
+            levels,
+            ..self.party.clone()
+        };
+        save_party(&self.data_dir, &party)?;
+        self.party = party;
+        self.input_mode = InputMode::Normal;
+        self.set_message(match self.encounter_difficulty().rating {
+            Some(rating) => format!(
+                "Party of {} saved; encounter is {}",
+                self.party.levels.len(),
+                rating.as_str()
+            ),
+            None => "Party cleared".to_string(),
+        });
+        Ok(())
     }
 
-    pub fn cancel_library_overwrite(&mut self) {
-        self.input_mode = InputMode::Normal;
-        self.set_message("Save cancelled".to_string());
+    /// Adds a roster PC to the encounter, prompting for initiative like a PC template.
+    pub fn add_roster_member(&mut self, index: usize) -> Result<(), String> {
+        let Some(member) = self.party.members.get(index).cloned() else {
+            return Err("Invalid roster selection".to_string());
+        };
+        if self
+            .encounter
+            .combatants
+            .iter()
+            .chain(&self.encounter.delayed)
+            .any(|c| c.name == member.name)
+        {
+            return Err(format!("{} is already in the encounter", member.name));
+        }
+
+        self.set_message(format!(
+            "Set initiative for {} (blank rolls d20{:+})",
+            member.name, member.initiative_modifier
+        ));
+        self.input_mode = InputMode::AddingCombatant(AddCombatantState {
+            step: 2, // next prompt will be initiative
+            name: member.name.clone(),
+            initiative_modifier: member.initiative_modifier.to_string(),
+            hp: member.hp_max.to_string(),
+            ac: member.armor_class.to_string(),
+            is_player: "y".to_string(),
+            defenses: member.defenses.clone(),
+            roster_member: Some(Box::new(member)),
+            ..Default::default()
+        });
+        Ok(())
     }
 
-    pub fn select_library_template(&mut self, filename: String) -> Result<(), String> {
-        // Check if encounter is empty
-        if self.encounter.combatants.is_empty() {
-            // Load directly without confirmation
-            self.start_library_initiative_input(filename)
-        } else {
-            // Need confirmation
-            self.input_mode = InputMode::ConfirmingLibraryLoad(filename);
-            self.set_message("This will clear the current encounter. Continue? (y/n)".to_string());
-            Ok(())
+    /// Puts every PC in the encounter on the roster, updating those already on it.
+    pub fn record_party_roster(&mut self) -> Result<(), String> {
+        let mut party = self.party.clone();
+        let recorded = party.record(
+            self.encounter
+                .combatants
+                .iter()
+                .chain(&self.encounter.delayed),
+            true,
+        );
+        if recorded == 0 {
+            return Err("No PCs in the encounter to add to the roster".to_string());
         }
+        save_party(&self.data_dir, &party)?;
+        self.party = party;
+        self.set_message(format!("Saved {} PCs to the party roster", recorded));
+        Ok(())
     }
 
-    fn start_library_initiative_input(&mut self, filename: String) -> Result<(), String> {
-        match load_library_template(&filename) {
-            Ok(template) => {
-                // Initialize state with all combatants
-                let combatants_with_init: Vec<(LibraryCombatant, String)> = template
-                    .combatants
-                    .iter()
-                    .map(|c| (c.clone(), String::new()))
-                    .collect();
-
-                self.input_mode = InputMode::SettingLibraryInitiatives(LoadLibraryState {
-                    template,
-                    combatants_with_init,
-                    current_index: 0,
-                });
-                Ok(())
-            }
-            Err(err) => {
-                log::error!("Failed to load library template '{}': {}", filename, err);
-                self.set_message(format!("Failed to load library template: {}", err));
-                self.input_mode = InputMode::Normal;
-                Err(err)
-            }
+    /// Sets a roster member's hit dice, e.g. "5d10+10" for five d10s at +2 CON.
+    pub fn complete_set_hit_dice(&mut self, index: usize, input: &str) -> Result<(), String> {
+        let formula = input.trim();
+        if rest::HitDicePool::parse(formula).is_none() {
+            return Err(format!(
+                "Invalid hit dice '{}' (use e.g. 5d10 or 5d10+10, with CON times the dice count)",
+                formula
+            ));
         }
+        let mut party = self.party.clone();
+        let Some(member) = party.members.get_mut(index) else {
+            return Err("Invalid roster selection".to_string());
+        };
+        member.hit_dice = Some(formula.to_string());
+        let message = format!("{} now has hit dice {}", member.name, formula);
+        save_party(&self.data_dir, &party)?;
+        self.party = party;
+        self.input_mode = InputMode::PartyRoster(index);
+        self.set_message(message);
+        Ok(())
     }
 
-    pub fn confirm_load_library(&mut self, filename: String) -> Result<(), String> {
-        self.start_library_initiative_input(filename)
+    pub fn remove_roster_member(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.party.members.len() {
+            return Err("Invalid roster selection".to_string());
+        }
+        let mut party = self.party.clone();
+        let member = party.members.remove(index);
+        save_party(&self.data_dir, &party)?;
+        self.party = party;
+        self.set_message(format!("Removed {} from the party roster", member.name));
+        Ok(())
     }
 
-    pub fn cancel_library_load(&mut self) {
-        self.input_mode = InputMode::Normal;
+    /// Finishes a rest for the PCs chosen in the rest dialog and logs each change.
+    pub fn complete_rest(&mut self, state: RestState) -> Result<(), String> {
+        let label = if state.long {
+            "Long rest"
+        } else {
+            "Short rest"
+        };
+        let mut lines = Vec::new();
+        let mut rest = |combatant: &mut Combatant, hit_dice: u32, dice: &mut DiceRoller| {
+            let line = if state.long {
+                Some(rest::long_rest(combatant))
+            } else {
+                rest::short_rest(combatant, hit_dice, dice)
+            };
+            lines.extend(line);
+        };
+
+        if state.on_roster {
+            if self.party.members.is_empty() {
+                return Err("No PCs on the party roster".to_string());
+            }
+            let mut party = self.party.clone();
+            for (i, member) in party.members.iter_mut().enumerate() {
+                let mut combatant = member.to_combatant();
+                let hit_dice = state.hit_dice.get(i).copied().unwrap_or(0);
+                rest(&mut combatant, hit_dice, &mut self.dice);
+                *member = PartyMember::from_combatant(&combatant);
+            }
+            save_party(&self.data_dir, &party)?;
+            self.party = party;
+        } else {
+            if self.rest_targets(false).is_empty() {
+                return Err("No PCs in the encounter".to_string());
+            }
+            self.record_history(label);
+            let pcs = self
+                .encounter
+                .combatants
+                .iter_mut()
+                .chain(&mut self.encounter.delayed)
+                .filter(|c| c.is_player && c.is_creature());
+            for (i, combatant) in pcs.enumerate() {
+                let hit_dice = state.hit_dice.get(i).copied().unwrap_or(0);
+                rest(combatant, hit_dice, &mut self.dice);
+            }
+        }
+
+        self.input_mode = InputMode::Normal;
+        self.set_message(format!(
+            "{} finished for {}",
+            label,
+            if state.on_roster {
+                "the party roster"
+            } else {
+                "the encounter's PCs"
+            }
+        ));
+        for line in lines {
+            self.push_log(line);
+        }
+        Ok(())
+    }
+
+    /// Writes roster PCs' HP, hit dice, exhaustion and lasting effects back to
+    /// `party.json`. Returns a note for the status message if anything changed.
+    fn write_back_party_roster(&mut self) -> Option<String> {
+        let mut party = self.party.clone();
+        let recorded = party.record(
+            self.encounter
+                .combatants
+                .iter()
+                .chain(&self.encounter.delayed),
+            false,
+        );
+        if recorded == 0 {
+            return None;
+        }
+        match save_party(&self.data_dir, &party) {
+            Ok(()) => {
+                self.party = party;
+                Some(format!("party roster updated ({})", recorded))
+            }
+            Err(err) => Some(format!("party roster not saved: {}", err)),
+        }
+    }
+
+    /// Ends the fight: shows the summary, saves it under `sessions/` and
+    /// clears the encounter for the next one.
+    pub fn end_combat(&mut self) -> Result<(), String> {
+        if !self.encounter.combatants.iter().any(|c| c.is_creature()) {
+            return Err("No combat to end".to_string());
+        }
+
+        if self.encounter.id == 0 {
+            // Saved before encounters had ids; pin one so a redo after undo overwrites
+            self.encounter.id = new_encounter_id();
+        }
+        self.record_history("End combat");
+
+        // Both writes are idempotent: the session is keyed on the encounter id and the
+        // roster just takes the PCs' current state, so ending again after an undo is safe
+        let record = session::summarize(&self.encounter);
+        let saved = save_session(&self.data_dir, &record);
+        let roster_note = self.write_back_party_roster();
+
+        self.encounter = CombatEncounter::new();
+        self.log.clear();
+        self.pinned = None;
+        self.push_log(format!("Combat ended after {} rounds", record.rounds));
+
+        let message = match saved {
+            Ok(filename) => format!("Session saved: {}", filename),
+            Err(err) => format!("Failed to save session: {}", err),
+        };
+        self.set_message(with_note(message, roster_note));
+        self.input_mode = InputMode::CombatSummary(Box::new(record));
+        Ok(())
+    }
+
+    pub fn confirm_overwrite_library(&mut self, state: SaveLibraryState) -> Result<(), String> {
+        self.save_library_template_internal(state)
+    }
+
+    pub fn cancel_library_overwrite(&mut self) {
+        self.input_mode = InputMode::Normal;
+        self.set_message("Save cancelled".to_string());
+    }
+
+    pub fn select_library_template(&mut self, filename: String) -> Result<(), String> {
+        // Check if encounter is empty
+        if self.encounter.combatants.is_empty() {
+            // Load directly without confirmation
+            self.start_library_initiative_input(filename)
+        } else {
+            // Need confirmation
+            self.input_mode = InputMode::ConfirmingLibraryLoad(filename);
+            self.set_message("This will clear the current encounter. Continue? (y/n)".to_string());
+            Ok(())
+        }
+    }
+
+    fn start_library_initiative_input(&mut self, filename: String) -> Result<(), String> {
+        match load_library_template(&self.data_dir, &filename) {
+            Ok(template) => {
+                // NPCs roll d20 + modifier now; PCs are left blank for manual entry
+                let mut roll_log = Vec::new();
+                let mut combatants_with_init: Vec<(LibraryCombatant, String)> = Vec::new();
+                for c in &template.combatants {
+                    if c.count > 1 && c.kind.is_creature() {
+                        let existing = combatants_with_init.iter().map(|(e, _)| e.name.as_str());
+                        let names = next_copy_names(existing, &c.name, c.count);
+                        let group_roll = (c.group_initiative && !c.is_player)
+                            .then(|| self.dice.roll(&DiceExpr::d20(c.initiative_modifier)));
+                        if let Some(roll) = &group_roll {
+                            roll_log.push(format!(
+                                "Group initiative roll for {} x{} {}",
+                                c.name,
+                                c.count,
+                                roll.breakdown()
+                            ));
+                        }
+                        for name in names {
+                            let initiative = if c.is_player {
+                                String::new()
+                            } else if let Some(roll) = &group_roll {
+                                roll.total.to_string()
+                            } else {
+                                let roll = self.dice.roll(&DiceExpr::d20(c.initiative_modifier));
+                                roll_log.push(format!(
+                                    "Initiative roll for {} {}",
+                                    name,
+                                    roll.breakdown()
+                                ));
+                                roll.total.to_string()
+                            };
+                            let mut copy = LibraryCombatant {
+                                name,
+                                count: 1,
+                                group_initiative: false,
+                                ..c.clone()
+                            };
+                            self.roll_library_hit_points(&mut copy, &mut roll_log);
+                            combatants_with_init.push((copy, initiative));
+                        }
+                    } else if !c.kind.is_creature() {
+                        let initiative = c.fixed_initiative.unwrap_or(20);
+                        combatants_with_init.push((c.clone(), initiative.to_string()));
+                    } else if c.is_player {
+                        combatants_with_init.push((c.clone(), String::new()));
+                    } else {
+                        let roll = self.dice.roll(&DiceExpr::d20(c.initiative_modifier));
+                        roll_log.push(format!(
+                            "Initiative roll for {} {}",
+                            c.name,
+                            roll.breakdown()
+                        ));
+                        let mut npc = c.clone();
+                        self.roll_library_hit_points(&mut npc, &mut roll_log);
+                        combatants_with_init.push((npc, roll.total.to_string()));
+                    }
+                }
+
+                let state = LoadLibraryState {
+                    template,
+                    combatants_with_init,
+                    current_index: 0,
+                    roll_log,
+                };
+                match next_manual_initiative(&state, 0) {
+                    Some(index) => {
+                        self.input_mode = InputMode::SettingLibraryInitiatives(LoadLibraryState {
+                            current_index: index,
+                            ..state
+                        });
+                        Ok(())
+                    }
+                    None => self.finalize_library_load(state),
+                }
+            }
+            Err(err) => {
+                log::error!("Failed to load library template '{}': {}", filename, err);
+                self.set_message(format!("Failed to load library template: {}", err));
+                self.input_mode = InputMode::Normal;
+                Err(err)
+            }
+        }
+    }
+
+    /// Replaces an NPC's average HP with a hit dice roll when rolled HP is chosen.
+    fn roll_library_hit_points(
+        &mut self,
+        entry: &mut LibraryCombatant,
+        roll_log: &mut Vec<String>,
+    ) {
+        if !self.roll_hit_points || entry.is_player {
+            return;
+        }
+        let Some(roll) = entry
+            .hit_dice
+            .as_deref()
+            .and_then(|formula| self.dice.roll_str(formula).ok())
+        else {
+            return;
+        };
+        entry.hp_max = roll.total.max(1);
+        roll_log.push(format!(
+            "Hit points roll for {} {}",
+            entry.name,
+            roll.breakdown()
+        ));
+    }
+
+    pub fn confirm_load_library(&mut self, filename: String) -> Result<(), String> {
+        self.start_library_initiative_input(filename)
+    }
+
+    pub fn cancel_library_load(&mut self) {
+        self.input_mode = InputMode::Normal;
         self.set_message("Load cancelled".to_string());
     }
 
@@ -632,19 +2130,25 @@ impl App {
             _ => return Err("Not in library initiative setting mode".to_string()),
         };
 
-        // Parse initiative
-        let init_value = initiative.parse::<i32>().map_err(|_| {
+        // Parse or roll initiative
+        let roll = self.dice.roll_str(&initiative).inspect_err(|_| {
             self.set_message("Invalid initiative value".to_string());
-            "Invalid initiative value".to_string()
         })?;
 
         // Update the current combatant's initiative
         let mut new_state = state.clone();
-        new_state.combatants_with_init[state.current_index].1 = init_value.to_string();
+        new_state.combatants_with_init[state.current_index].1 = roll.total.to_string();
+        if !roll.is_fixed() {
+            new_state.roll_log.push(format!(
+                "Initiative roll for {} {}",
+                state.combatants_with_init[state.current_index].0.name,
+                roll.breakdown()
+            ));
+        }
 
-        // Move to next combatant or finalize
-        if state.current_index + 1 < state.combatants_with_init.len() {
-            new_state.current_index += 1;
+        // Move to next player character or finalize
+        if let Some(next) = next_manual_initiative(&new_state, state.current_index + 1) {
+            new_state.current_index = next;
             self.input_mode = InputMode::SettingLibraryInitiatives(new_state);
             Ok(())
         } else {
@@ -654,23 +2158,41 @@ impl App {
     }
 
     fn finalize_library_load(&mut self, state: LoadLibraryState) -> Result<(), String> {
+        self.record_history(&format!("Load library '{}'", state.template.name));
+
         // Clear current encounter
-        self.encounter.combatants.clear();
-        self.encounter.current_turn_index = 0;
-        self.encounter.round_number = 1;
+        self.encounter = CombatEncounter::new();
         self.log.clear();
 
         // Create fresh combatants with entered initiatives
         for (lib_combatant, init_str) in state.combatants_with_init {
             let initiative = init_str.parse::<i32>().unwrap_or(10); // Fallback to 10
-            let combatant = Combatant::new(
-                lib_combatant.name,
-                initiative,
-                lib_combatant.hp_max,
-                lib_combatant.armor_class,
-                lib_combatant.is_player,
-            );
-            self.encounter.add_combatant(combatant);
+            let mut combatant = if lib_combatant.kind.is_creature() {
+                Combatant::new(
+                    lib_combatant.name,
+                    initiative,
+                    lib_combatant.hp_max,
+                    lib_combatant.armor_class,
+                    lib_combatant.is_player,
+                )
+            } else {
+                Combatant::new_turn_entry(
+                    lib_combatant.name,
+                    initiative,
+                    lib_combatant.kind,
+                    lib_combatant.reminder,
+                )
+            };
+            combatant.initiative_modifier = lib_combatant.initiative_modifier;
+            combatant.legendary_actions = LegendaryPool::from_max(lib_combatant.legendary_actions);
+            combatant.legendary_resistances =
+                LegendaryPool::from_max(lib_combatant.legendary_resistances);
+            combatant.defenses = lib_combatant.defenses;
+            combatant.stat_block = lib_combatant.stat_block;
+            combatant.notes = lib_combatant.notes;
+            combatant.hit_dice = lib_combatant.hit_dice;
+            combatant.resources = lib_combatant.resources;
+            self.add_to_encounter(combatant);
         }
 
         self.set_message(format!(
@@ -681,67 +2203,1124 @@ impl App {
             "Loaded encounter '{}' from library",
             state.template.name
         ));
+        for entry in state.roll_log {
+            self.push_log(entry);
+        }
         self.input_mode = InputMode::Normal;
         Ok(())
     }
 
     pub fn list_library_templates(&self) -> Vec<String> {
-        list_library_files().unwrap_or_else(|_| Vec::new())
+        list_library_files(&self.data_dir).unwrap_or_else(|_| Vec::new())
+    }
+
+    /// Rerolls d20 + initiative modifier for every NPC and re-sorts the order.
+    pub fn roll_npc_initiative(&mut self) {
+        if !self
+            .encounter
+            .combatants
+            .iter()
+            .any(|c| !c.is_player && c.is_creature())
+        {
+            self.set_message("No NPCs to roll initiative for".to_string());
+            return;
+        }
+
+        self.record_history("Roll NPC initiative");
+        let mut rolls = Vec::new();
+        for combatant in self.encounter.combatants.iter_mut() {
+            // Lair actions and other turn entries keep their fixed initiative count
+            if combatant.is_player || !combatant.is_creature() {
+                continue;
+            }
+            let roll = self
+                .dice
+                .roll(&DiceExpr::d20(combatant.initiative_modifier));
+            combatant.initiative = roll.total;
+            combatant.held_place = None;
+            rolls.push(format!(
+                "Initiative roll for {} {}",
+                combatant.name,
+                roll.breakdown()
+            ));
+        }
+        self.encounter.resort_keeping_turn();
+
+        let count = rolls.len();
+        for entry in rolls {
+            self.push_log(entry);
+        }
+        self.set_message(format!("Rolled initiative for {} NPC(s)", count));
     }
 }
+
+/// Result of running one target through the damage pipeline
+struct DamageOutcome {
+    name: String,
+    message: String,
+    check: Option<(ConcentrationCheckState, String)>, // check and spell name
+}
+
+fn check_prompt_message(name: &str, spell_name: &str, dc: i32) -> String {
+    format!(
+        "{} took damage while concentrating on {}. Roll CON save (DC {}).",
+        name, spell_name, dc
+    )
+}
+
+/// e.g. "Imp took 5 fire damage (10 raw) (HP: 5)"; untyped, unmodified damage keeps the short form
+fn damage_log_message(
+    name: &str,
+    raw: i32,
+    applied: i32,
+    damage_type: DamageType,
+    save: SaveOutcome,
+    hp: i32,
+) -> String {
+    let typed = if damage_type == DamageType::Untyped {
+        String::new()
+    } else {
+        format!("{} ", damage_type.as_str().to_lowercase())
+    };
+    let detail = match (raw == applied, save) {
+        (true, SaveOutcome::Full) => String::new(),
+        (_, SaveOutcome::Full) => format!(" ({} raw)", raw),
+        (_, save) => format!(" ({} raw, {})", raw, save.as_str()),
+    };
+    format!(
+        "{} took {} {}damage{} (HP: {})",
+        name, applied, typed, detail, hp
+    )
+}
+
+/// A combatant parsed from the add prompts, with the rolls that produced it
+struct BuiltCombatant {
+    combatant: Combatant,
+    initiative_roll: DiceRoll,
+    hp_roll: DiceRoll,
+}
+
+/// Add-combatant prompts prefilled from a template, waiting on initiative.
+/// NPCs with hit dice put the formula in the HP prompt when `roll_hp` is set.
+fn template_state(tpl: &CombatantTemplate, roll_hp: bool) -> AddCombatantState {
+    let hp = match &tpl.hit_dice {
+        Some(formula) if roll_hp && !tpl.is_player => formula.clone(),
+        _ => tpl.hp_max.to_string(),
+    };
+    AddCombatantState {
+        name: tpl.name.clone(),
+        initiative_modifier: tpl.initiative_modifier.to_string(),
+        hp,
+        ac: tpl.armor_class.to_string(),
+        is_player: if tpl.is_player {
+            "y".to_string()
+        } else {
+            "n".to_string()
+        },
+        legendary_actions: tpl.legendary_actions,
+        legendary_resistances: tpl.legendary_resistances,
+        defenses: tpl.defenses.clone(),
+        stat_block: tpl.stat_block.clone().map(Box::new),
+        hit_dice: tpl.hit_dice.clone(),
+        resources: tpl.resources.clone(),
+        step: 2, // next prompt will be initiative
+        ..Default::default()
+    }
+}
+
+fn with_note(message: String, note: Option<String>) -> String {
+    match note {
+        Some(note) => format!("{}; {}", message, note),
+        None => message,
+    }
+}
+
+/// Parses an initiative modifier such as `+2`, `-1` or `3`. Blank means +0.
+fn parse_initiative_modifier(input: &str) -> Result<i32, String> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Ok(0);
+    }
+    trimmed
+        .strip_prefix('+')
+        .unwrap_or(trimmed)
+        .parse::<i32>()
+        .map_err(|_| "Invalid initiative modifier".to_string())
+}
+
+/// HP a combatant is saved with: the hit dice average when its HP may have been rolled.
+fn base_hit_points(c: &Combatant) -> i32 {
+    c.hit_dice
+        .as_deref()
+        .and_then(|formula| DiceExpr::parse(formula).ok())
+        .and_then(|expr| expr.average())
+        .unwrap_or(c.hp_max)
+}
+
+/// Converts combatants to fresh library entries, folding identical numbered copies
+/// ("Goblin 1", "Goblin 2") back into one entry with a count.
+fn library_entries(combatants: &[Combatant]) -> Vec<LibraryCombatant> {
+    struct Group {
+        entry: LibraryCombatant,
+        first_name: String,
+        numbered: bool,
+        initiatives: Vec<i32>,
+    }
+
+    let mut groups: Vec<Group> = Vec::new();
+    for c in combatants {
+        let entry = LibraryCombatant {
+            name: c.name.clone(),
+            hp_max: base_hit_points(c),
+            armor_class: c.armor_class,
+            is_player: c.is_player,
+            initiative_modifier: c.initiative_modifier,
+            legendary_actions: c.legendary_actions.map_or(0, |p| p.max),
+            legendary_resistances: c.legendary_resistances.map_or(0, |p| p.max),
+            kind: c.kind,
+            reminder: c.reminder.clone(),
+            fixed_initiative: (!c.is_creature()).then_some(c.initiative),
+            defenses: c.defenses.clone(),
+            stat_block: c.stat_block.clone(),
+            notes: c.notes.clone(),
+            hit_dice: c.hit_dice.clone(),
+            resources: resources::fresh(&c.resources),
+            count: 1,
+            group_initiative: false,
+        };
+        let base = split_copy_number(&c.name)
+            .filter(|_| c.is_creature())
+            .map(|(base, _)| base);
+        let Some(base) = base else {
+            groups.push(Group {
+                entry,
+                first_name: c.name.clone(),
+                numbered: false,
+                initiatives: vec![c.initiative],
+            });
+            continue;
+        };
+
+        let entry = LibraryCombatant {
+            name: base.to_string(),
+            ..entry
+        };
+        let same_stats = |group: &Group| {
+            group.numbered
+                && LibraryCombatant {
+                    count: 1,
+                    ..group.entry.clone()
+                } == entry
+        };
+        match groups.iter_mut().find(|group| same_stats(group)) {
+            Some(group) => {
+                group.entry.count += 1;
+                group.initiatives.push(c.initiative);
+            }
+            None => groups.push(Group {
+                entry,
+                first_name: c.name.clone(),
+                numbered: true,
+                initiatives: vec![c.initiative],
+            }),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|mut group| {
+            if group.entry.count == 1 {
+                // A lone "Goblin 2" keeps its name
+                group.entry.name = group.first_name;
+            } else {
+                // Copies sharing one count were most likely added as a group
+                group.entry.group_initiative =
+                    group.initiatives.windows(2).all(|pair| pair[0] == pair[1]);
+            }
+            group.entry
+        })
+        .collect()
+}
+
+/// Index of the next player character (at or after `from`) still needing an initiative.
+fn next_manual_initiative(state: &LoadLibraryState, from: usize) -> Option<usize> {
+    (from..state.combatants_with_init.len()).find(|&i| {
+        let c = &state.combatants_with_init[i].0;
+        c.is_player && c.kind.is_creature()
+    })
+}
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::combat::CombatEncounter;
+    use crate::dice::DiceRoller;
+    use crate::models::{Ability, DamageDefenses, StatBlock};
     use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// App whose party, templates and saves live in a scratch folder of its own
+    fn test_app() -> App {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "dnd-combat-tracker-test-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        App::with_data_dir(dir)
+    }
+
+    fn add_basic_combatant(app: &mut App, name: &str) {
+        let state = AddCombatantState {
+            step: 5,
+            name: name.to_string(),
+            initiative_modifier: String::new(),
+            initiative: "10".to_string(),
+            hp: "20".to_string(),
+            ac: "15".to_string(),
+            is_player: "n".to_string(),
+            ..Default::default()
+        };
+        app.complete_add_combatant(state).unwrap();
+    }
+
+    /// Sets tiebreakers so combatants tied on initiative take turns in the order
+    /// named, as swapping ties would, so tests can index them. Meant for setup,
+    /// so the turn stays at the same position in the order.
+    fn order_ties(app: &mut App, names: &[&str]) {
+        for (rank, name) in names.iter().enumerate() {
+            let combatant = app
+                .encounter
+                .combatants
+                .iter_mut()
+                .find(|c| c.name == *name)
+                .unwrap();
+            combatant.tiebreaker = 100 - rank as i32;
+        }
+        let turn = app.encounter.current_turn_index;
+        app.encounter.resort_keeping_turn();
+        app.encounter.current_turn_index = turn;
+    }
+
+    #[test]
+    fn granting_temp_hp_updates_combatant_and_logs() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Orc");
+        app.complete_grant_temp_hp(0, 7).unwrap();
+        assert_eq!(app.encounter.combatants[0].temp_hp, 7);
+        assert!(app.message.as_ref().unwrap().contains("gains 7 temp HP"));
+        assert!(app.log.last().unwrap().message.contains("gains 7 temp HP"));
+    }
+
+    #[test]
+    fn log_is_capped_at_200_entries() {
+        let mut app = test_app();
+        for i in 0..205 {
+            app.push_log(format!("entry {}", i));
+        }
+        assert_eq!(app.log.len(), 200);
+        assert_eq!(app.log.first().unwrap().message, "entry 5");
+    }
+
+    #[test]
+    fn damage_triggers_concentration_check() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Mage");
+        app.encounter.combatants[0]
+            .set_concentration(ConcentrationInfo::new("Haste".to_string(), 3));
+        app.complete_deal_damage(0, 12, DamageType::Untyped, None)
+            .unwrap();
+        match &app.input_mode {
+            InputMode::ConcentrationCheck(state) => {
+                assert_eq!(state.dc, 10); // max(10, damage/2)
+                assert!(app.message.as_ref().unwrap().contains("Roll CON save"));
+            }
+            _ => panic!("Expected ConcentrationCheck mode"),
+        }
+        assert!(app.encounter.combatants[0].concentration.is_some());
+
+        // Zero damage doesn't call for a save
+        app.cancel_input();
+        app.complete_deal_damage(0, 0, DamageType::Untyped, None)
+            .unwrap();
+        assert_eq!(app.input_mode, InputMode::Normal);
+    }
+
+    #[test]
+    fn typed_damage_applies_defenses_and_logs_raw_amount() {
+        let mut app = test_app();
+        let mut tpl = CombatantTemplate::from_stats("Imp".to_string(), 30, 13, false, 3);
+        tpl.defenses
+            .toggle(DefenseKind::Resistance, DamageType::Cold);
+        tpl.defenses.toggle(DefenseKind::Immunity, DamageType::Fire);
+        tpl.defenses
+            .toggle(DefenseKind::Vulnerability, DamageType::Radiant);
+        app.templates = vec![tpl];
+        app.add_combatant_from_template(0).unwrap();
+
+        app.complete_deal_damage(0, 9, DamageType::Cold, None)
+            .unwrap();
+        assert_eq!(app.encounter.combatants[0].hp_current, 26);
+        assert_eq!(
+            app.log.last().unwrap().message,
+            "Imp took 4 cold damage (9 raw) (HP: 26)"
+        );
+
+        app.complete_deal_damage(0, 5, DamageType::Radiant, None)
+            .unwrap();
+        assert_eq!(app.encounter.combatants[0].hp_current, 16);
+
+        // Immune damage is fully negated and doesn't force a concentration save
+        app.encounter.combatants[0]
+            .set_concentration(ConcentrationInfo::new("Invisibility".to_string(), 2));
+        app.complete_deal_damage(0, 20, DamageType::Fire, None)
+            .unwrap();
+        assert_eq!(app.encounter.combatants[0].hp_current, 16);
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert_eq!(
+            app.log.last().unwrap().message,
+            "Imp took 0 fire damage (20 raw) (HP: 16)"
+        );
+
+        app.toggle_defense(0, DefenseKind::Immunity, DamageType::Fire)
+            .unwrap();
+        assert!(app.encounter.combatants[0].defenses.immunities.is_empty());
+        assert!(
+            app.toggle_defense(0, DefenseKind::Resistance, DamageType::Untyped)
+                .is_err()
+        );
+        let json = serde_json::to_string(&app.encounter.combatants[0]).unwrap();
+        assert!(json.contains("\"resistances\":[\"Cold\"]"));
+    }
+
+    #[test]
+    fn attacks_compare_against_ac_and_handle_natural_rolls() {
+        let seed_for = |natural: i32| {
+            (0..)
+                .find(|&seed| DiceRoller::seeded(seed).roll_die(20) == natural)
+                .unwrap()
+        };
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Fighter");
+        add_basic_combatant(&mut app, "Orc");
+        order_ties(&mut app, &["Fighter", "Orc"]);
+        app.encounter.combatants[1].armor_class = 40;
+
+        // A natural 20 hits any AC as a critical and moves on to damage
+        app.dice = DiceRoller::seeded(seed_for(20));
+        app.complete_attack(0, 1, "+5").unwrap();
+        match &app.input_mode {
+            InputMode::Attacking(state) => {
+                assert_eq!(state.step, 3);
+                assert!(state.critical);
+            }
+            _ => panic!("Expected damage step after a hit"),
+        }
+        assert!(
+            app.log
+                .iter()
+                .any(|e| e.message == "Fighter attacks Orc: 25 vs AC 40 - critical hit!")
+        );
+
+        // A natural 1 misses even when the total beats the AC
+        app.encounter.combatants[1].armor_class = 5;
+        app.dice = DiceRoller::seeded(seed_for(1));
+        app.complete_attack(0, 1, "10").unwrap();
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(
+            app.log
+                .iter()
+                .any(|e| e.message == "Fighter attacks Orc: 11 vs AC 5 - natural 1, miss")
+        );
+
+        assert!(app.complete_attack(0, 1, "abc").is_err());
+    }
+
+    #[test]
+    fn area_damage_applies_saves_and_queues_concentration_checks() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Cleric");
+        add_basic_combatant(&mut app, "Rogue");
+        add_basic_combatant(&mut app, "Druid");
+        order_ties(&mut app, &["Cleric", "Rogue", "Druid"]);
+        for i in [0, 2] {
+            app.encounter.combatants[i]
+                .set_concentration(ConcentrationInfo::new("Bless".to_string(), 2));
+        }
+
+        let targets = [
+            (0, SaveOutcome::Full),
+            (1, SaveOutcome::None),
+            (2, SaveOutcome::Half),
+        ];
+        app.complete_area_damage(&targets, 8, DamageType::Fire)
+            .unwrap();
+        let hp: Vec<_> = app
+            .encounter
+            .combatants
+            .iter()
+            .map(|c| c.hp_current)
+            .collect();
+        assert_eq!(hp, vec![12, 20, 16]);
+        assert_eq!(
+            app.log.last().unwrap().message,
+            "Druid took 4 fire damage (8 raw, half on save) (HP: 16)"
+        );
+
+        // One check per concentrating target, prompted one after another
+        let InputMode::ConcentrationCheck(first) = app.input_mode.clone() else {
+            panic!("Expected ConcentrationCheck mode");
+        };
+        assert_eq!(first.combatant_index, 0);
+        assert_eq!(app.pending_prompts.len(), 1);
+        app.complete_concentration_check(first, 15).unwrap();
+        let InputMode::ConcentrationCheck(second) = app.input_mode.clone() else {
+            panic!("Expected the queued ConcentrationCheck");
+        };
+        assert_eq!(second.combatant_index, 2);
+        app.complete_concentration_check(second, 3).unwrap();
+        assert_eq!(app.input_mode, InputMode::Normal);
+        assert!(app.encounter.combatants[2].concentration.is_none());
+
+        // The whole area effect is a single undo step
+        app.undo();
+        app.undo();
+        app.undo();
+        assert!(app.encounter.combatants.iter().all(|c| c.hp_current == 20));
+        assert!(app.complete_area_damage(&[], 8, DamageType::Fire).is_err());
+    }
+
+    #[test]
+    fn undo_reverts_damage_and_its_log_entry() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Orc");
+        let log_len = app.log.len();
+        app.complete_deal_damage(0, 70, DamageType::Untyped, None)
+            .unwrap();
+        assert_eq!(app.encounter.combatants[0].hp_current, 0);
+        assert_eq!(app.log.len(), log_len + 1);
+
+        app.undo();
+        assert_eq!(app.encounter.combatants[0].hp_current, 20);
+        assert_eq!(app.log.len(), log_len);
+
+        app.redo();
+        assert_eq!(app.encounter.combatants[0].hp_current, 0);
+        assert_eq!(app.log.len(), log_len + 1);
+    }
+
+    #[test]
+    fn undo_restores_removed_combatant_and_turn() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Orc");
+        add_basic_combatant(&mut app, "Goblin");
+        order_ties(&mut app, &["Orc", "Goblin"]);
+        app.advance_turn();
+        app.complete_remove(0).unwrap();
+        assert_eq!(app.encounter.combatants.len(), 1);
+
+        app.undo();
+        assert_eq!(app.encounter.combatants.len(), 2);
+        assert_eq!(app.encounter.current_turn_index, 1);
+        app.undo();
+        assert_eq!(app.encounter.current_turn_index, 0);
+    }
+
+    #[test]
+    fn new_action_clears_redo() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Orc");
+        app.complete_heal(0, 1).unwrap();
+        app.undo();
+        app.complete_grant_temp_hp(0, 3).unwrap();
+        app.redo();
+        assert_eq!(app.message.as_deref(), Some("Nothing to redo"));
+        assert_eq!(app.encounter.combatants[0].temp_hp, 3);
+    }
+
+    #[test]
+    fn dice_initiative_is_rolled_and_logged() {
+        let mut app = test_app();
+        app.dice = DiceRoller::seeded(11);
+        let state = AddCombatantState {
+            step: 5,
+            name: "Rogue".to_string(),
+            initiative_modifier: String::new(),
+            initiative: "1d20+4".to_string(),
+            hp: "20".to_string(),
+            ac: "15".to_string(),
+            is_player: "y".to_string(),
+            ..Default::default()
+        };
+        app.complete_add_combatant(state).unwrap();
+        let init = app.encounter.combatants[0].initiative;
+        assert!((5..=24).contains(&init));
+        // The roll is logged ahead of the line it produced
+        let roll = &app.log[0].message;
+        assert!(roll.starts_with("Initiative roll 1d20+4: ["));
+        assert!(roll.ends_with(&format!("= {}", init)));
+        assert!(app.log[1].message.starts_with("Added Rogue"));
+    }
+
+    #[test]
+    fn blank_initiative_rolls_d20_plus_modifier() {
+        let mut app = test_app();
+        app.dice = DiceRoller::seeded(3);
+        let state = AddCombatantState {
+            step: 5,
+            name: "Scout".to_string(),
+            initiative_modifier: "+3".to_string(),
+            initiative: String::new(),
+            hp: "16".to_string(),
+            ac: "13".to_string(),
+            is_player: "n".to_string(),
+            ..Default::default()
+        };
+        app.complete_add_combatant(state).unwrap();
+        let scout = &app.encounter.combatants[0];
+        assert_eq!(scout.initiative_modifier, 3);
+        assert!((4..=23).contains(&scout.initiative));
+        assert!(app.log[0].message.starts_with("Initiative roll 1d20+3: ["));
+        assert_eq!(parse_initiative_modifier("-2"), Ok(-2));
+        assert!(parse_initiative_modifier("x").is_err());
+    }
+
+    #[test]
+    fn npc_template_add_rolls_without_prompt() {
+        let mut app = test_app();
+        app.templates = vec![
+            CombatantTemplate::from_stats("Goblin".to_string(), 7, 15, false, 2),
+            CombatantTemplate::from_stats("Cleric".to_string(), 30, 18, true, 0),
+        ];
+        app.add_combatant_from_template(0).unwrap();
+        assert!(matches!(app.input_mode, InputMode::Normal));
+        assert_eq!(app.encounter.combatants[0].initiative_modifier, 2);
+
+        app.add_combatant_from_template(1).unwrap();
+        match &app.input_mode {
+            InputMode::AddingCombatant(state) => assert_eq!(state.step, 2),
+            other => panic!("expected initiative prompt, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn roll_npc_initiative_keeps_pcs_and_current_turn() {
+        let mut app = test_app();
+        app.encounter
+            .add_combatant(Combatant::new("Fighter".to_string(), 30, 40, 18, true));
+        app.encounter
+            .add_combatant(Combatant::new("Orc".to_string(), 25, 15, 13, false));
+        app.encounter
+            .add_combatant(Combatant::new("Wolf".to_string(), 20, 11, 13, false));
+        app.encounter.current_turn_index = 0;
+
+        app.roll_npc_initiative();
+        let fighter = app
+            .encounter
+            .combatants
+            .iter()
+            .position(|c| c.name == "Fighter")
+            .unwrap();
+        assert_eq!(app.encounter.combatants[fighter].initiative, 30);
+        assert_eq!(app.encounter.current_turn_index, fighter);
+        for c in app.encounter.combatants.iter().filter(|c| !c.is_player) {
+            assert!((1..=20).contains(&c.initiative));
+        }
+        assert_eq!(app.history.undo_stack.len(), 1);
+        assert_eq!(
+            app.log
+                .iter()
+                .filter(|e| e.message.starts_with("Initiative roll for"))
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn resolving_a_tie_swaps_and_logs() {
+        let mut app = test_app();
+        app.encounter
+            .add_combatant(Combatant::new("Orc".to_string(), 10, 15, 13, false));
+        app.encounter
+            .add_combatant(Combatant::new("Wolf".to_string(), 10, 11, 13, false));
+        assert!(app.complete_resolve_tie(1).is_ok());
+        assert_eq!(app.encounter.combatants[0].name, "Wolf");
+        assert!(!app.encounter.is_unresolved_tie(0));
+        assert!(
+            app.log
+                .last()
+                .unwrap()
+                .message
+                .contains("Wolf acts before Orc")
+        );
+
+        app.undo();
+        assert_eq!(app.encounter.combatants[0].name, "Orc");
+        assert!(app.complete_resolve_tie(5).is_err());
+    }
+
+    #[test]
+    fn legendary_actions_spend_off_turn_and_refresh_on_own_turn() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Fighter");
+        add_basic_combatant(&mut app, "Dragon");
+        order_ties(&mut app, &["Fighter", "Dragon"]);
+        app.complete_set_legendary(SetLegendaryState {
+            combatant_index: 1,
+            step: 1,
+            actions: "3".to_string(),
+            resistances: "2".to_string(),
+        })
+        .unwrap();
+
+        // Fighter's turn: the dragon may act
+        app.complete_spend_legendary_action(1, 2).unwrap();
+        assert_eq!(
+            app.encounter.combatants[1].legendary_actions,
+            Some(LegendaryPool {
+                max: 3,
+                remaining: 1
+            })
+        );
+        assert!(app.complete_spend_legendary_action(1, 2).is_err());
+        app.complete_use_legendary_resistance(1).unwrap();
+        assert_eq!(
+            app.encounter.combatants[1]
+                .legendary_resistances
+                .unwrap()
+                .remaining,
+            1
+        );
+
+        // Dragon's turn: pool refreshes and can't be spent
+        app.advance_turn();
+        assert_eq!(
+            app.encounter.combatants[1]
+                .legendary_actions
+                .unwrap()
+                .remaining,
+            3
+        );
+        assert!(
+            app.log
+                .last()
+                .unwrap()
+                .message
+                .contains("regains legendary")
+        );
+        assert!(app.complete_spend_legendary_action(1, 1).is_err());
+        assert!(app.complete_spend_legendary_action(0, 1).is_err());
+    }
+
+    #[test]
+    fn legendary_pools_carry_through_templates() {
+        let mut app = test_app();
+        let mut tpl = CombatantTemplate::from_stats("Lich".to_string(), 135, 17, false, 3);
+        tpl.legendary_actions = 3;
+        tpl.legendary_resistances = 3;
+        app.templates = vec![tpl];
+        app.add_combatant_from_template(0).unwrap();
+        let lich = &app.encounter.combatants[0];
+        assert_eq!(lich.legendary_actions, Some(LegendaryPool::new(3)));
+        assert_eq!(lich.legendary_resistances, Some(LegendaryPool::new(3)));
+
+        let json = serde_json::to_string(&app.templates[0]).unwrap();
+        let legacy: CombatantTemplate = serde_json::from_str(
+            r#"{"name":"Orc","hp_max":15,"armor_class":13,"is_player":false}"#,
+        )
+        .unwrap();
+        assert!(json.contains("\"legendary_actions\":3"));
+        assert_eq!(legacy.legendary_actions, 0);
+    }
+
+    #[test]
+    fn stat_blocks_are_copied_from_templates_onto_combatants() {
+        let mut app = test_app();
+        let mut tpl = CombatantTemplate::from_stats("Goblin".to_string(), 7, 15, false, 2);
+        tpl.stat_block = Some(StatBlock {
+            challenge_rating: "1/4".to_string(),
+            xp: 50,
+            speed: "30 ft.".to_string(),
+            ..Default::default()
+        });
+        app.templates = vec![tpl];
+        app.add_combatant_from_template(0).unwrap();
+        let goblin = app.encounter.combatants[0].stat_block.as_ref().unwrap();
+        assert_eq!(goblin.challenge_rating, "1/4");
+        assert_eq!(goblin.xp, 50);
+
+        // Templates saved before stat blocks existed still load, and stay lean
+        let legacy: CombatantTemplate = serde_json::from_str(
+            r#"{"name":"Orc","hp_max":15,"armor_class":13,"is_player":false}"#,
+        )
+        .unwrap();
+        assert!(legacy.stat_block.is_none());
+        assert!(
+            !serde_json::to_string(&legacy)
+                .unwrap()
+                .contains("stat_block")
+        );
+    }
+
+    #[test]
+    fn template_copies_continue_numbering_and_share_group_initiative() {
+        let mut app = test_app();
+        app.templates = vec![CombatantTemplate::from_stats(
+            "Goblin".to_string(),
+            7,
+            15,
+            false,
+            2,
+        )];
+        add_basic_combatant(&mut app, "Goblin 3");
+        app.start_adding_copies(0);
+        let InputMode::AddingCopies(mut state) = app.input_mode.clone() else {
+            panic!("expected the copies prompt");
+        };
+        state.group_initiative = true;
+        app.complete_add_copies(state).unwrap();
+
+        let goblins: Vec<_> = app
+            .encounter
+            .combatants
+            .iter()
+            .filter(|c| c.name == "Goblin 4" || c.name == "Goblin 5")
+            .collect();
+        assert_eq!(goblins.len(), 2);
+        assert_eq!(goblins[0].initiative, goblins[1].initiative);
+        // Only the shared roll is logged
+        let rolls = app
+            .log
+            .iter()
+            .filter(|e| e.message.starts_with("Initiative roll"));
+        assert_eq!(rolls.count(), 1);
+
+        app.undo();
+        assert_eq!(app.encounter.combatants.len(), 1);
+        let invalid = CopiesState {
+            template_index: 0,
+            count: "0".to_string(),
+            group_initiative: false,
+        };
+        assert!(app.complete_add_copies(invalid).is_err());
+    }
+
+    #[test]
+    fn npc_hit_points_use_the_average_or_a_logged_hit_dice_roll() {
+        let mut app = test_app();
+        app.dice = DiceRoller::seeded(7);
+        let mut tpl = CombatantTemplate::from_stats("Goblin".to_string(), 7, 15, false, 2);
+        tpl.hit_dice = Some("2d6".to_string());
+        app.templates = vec![tpl];
+
+        app.add_combatant_from_template(0).unwrap();
+        assert_eq!(app.encounter.combatants[0].hp_max, 7);
+        assert!(
+            !app.log
+                .iter()
+                .any(|e| e.message.starts_with("Hit points roll"))
+        );
+
+        app.toggle_hit_point_mode();
+        app.complete_add_copies(CopiesState {
+            template_index: 0,
+            count: "4".to_string(),
+            group_initiative: false,
+        })
+        .unwrap();
+        let rolls: Vec<_> = app
+            .log
+            .iter()
+            .filter(|e| e.message.starts_with("Hit points roll 2d6"))
+            .collect();
+        assert_eq!(rolls.len(), 4);
+        for c in app
+            .encounter
+            .combatants
+            .iter()
+            .filter(|c| c.name != "Goblin")
+        {
+            assert!((2..=12).contains(&c.hp_max));
+            assert_eq!(c.hp_current, c.hp_max);
+            assert_eq!(c.hit_dice.as_deref(), Some("2d6"));
+            // Saved back to the library and templates at the average
+            assert_eq!(library_entries(std::slice::from_ref(c))[0].hp_max, 7);
+        }
+    }
+
+    #[test]
+    fn bestiary_monsters_are_added_as_numbered_copies() {
+        let mut app = test_app();
+        app.bestiary = crate::app::bestiary::parse_monsters(
+            r#"[{"name": "Goblin", "hit_points": 7, "armor_class": 15, "dexterity": 14,
+                 "challenge_rating": "1/4", "damage_vulnerabilities": "radiant"}]"#,
+        )
+        .unwrap()
+        .monsters;
+
+        app.add_bestiary_copies(0, 1, false).unwrap();
+        let goblin = &app.encounter.combatants[0];
+        assert_eq!(goblin.name, "Goblin");
+        assert_eq!((goblin.hp_max, goblin.armor_class), (7, 15));
+        assert_eq!(goblin.initiative_modifier, 2);
+        assert!(!goblin.is_player);
+        assert!(
+            goblin
+                .defenses
+                .has(DefenseKind::Vulnerability, DamageType::Radiant)
+        );
+        assert_eq!(goblin.stat_block.as_ref().unwrap().xp, 50);
+        assert!(app.add_bestiary_copies(1, 1, false).is_err());
+
+        // Copies are numbered after any existing ones and undo as one step
+        app.encounter.combatants[0].name = "Goblin 2".to_string();
+        app.add_bestiary_copies(0, 3, false).unwrap();
+        let mut names: Vec<_> = app.encounter.combatants.iter().map(|c| &c.name).collect();
+        names.sort();
+        assert_eq!(names, ["Goblin 2", "Goblin 3", "Goblin 4", "Goblin 5"]);
+        app.undo();
+        assert_eq!(app.encounter.combatants.len(), 1);
+    }
+
+    #[test]
+    fn lair_entry_announces_reminder_and_rejects_damage() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Dragon");
+        app.complete_add_turn_entry(AddTurnEntryState {
+            step: 3,
+            kind_index: 0,
+            name: String::new(),
+            initiative: String::new(),
+            reminder: "Magma erupts from a point within 120 ft".to_string(),
+        })
+        .unwrap();
+
+        let lair = &app.encounter.combatants[0];
+        assert_eq!(lair.kind, CombatantKind::Lair);
+        assert_eq!(lair.name, "Lair Action");
+        assert_eq!(lair.initiative, 20);
+        assert!(
+            app.complete_deal_damage(0, 5, DamageType::Untyped, None)
+                .is_err()
+        );
+        assert!(app.complete_heal(0, 5).is_err());
+
+        app.input_mode = InputMode::DealingDamage(SelectionState::default());
+        assert_eq!(app.selectable_indices(), vec![1]);
+        assert_eq!(app.effective_selection(0), Some(1));
+        app.input_mode = InputMode::Removing(SelectionState::default());
+        assert_eq!(app.selectable_indices(), vec![0, 1]);
+        app.input_mode = InputMode::Normal;
+
+        app.encounter.current_turn_index = 1;
+        app.advance_turn();
+        assert_eq!(app.encounter.current_turn_index, 0);
+        assert!(
+            app.message
+                .as_ref()
+                .unwrap()
+                .contains("Lair Action: Magma erupts")
+        );
+        assert!(
+            app.log
+                .last()
+                .unwrap()
+                .message
+                .starts_with("Round 2 - Lair")
+        );
+    }
+
+    #[test]
+    fn reactions_are_logged_and_limited_to_one_per_round() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Fighter");
+        add_basic_combatant(&mut app, "Orc");
+        order_ties(&mut app, &["Fighter", "Orc"]);
+
+        app.toggle_economy(EconomySlot::Action);
+        assert!(app.encounter.combatants[0].economy.action);
+        assert!(app.log.last().unwrap().message.starts_with("Added"));
+
+        app.complete_use_reaction(1).unwrap();
+        assert_eq!(app.log.last().unwrap().message, "Orc uses its reaction");
+        assert!(app.complete_use_reaction(1).is_err());
+
+        app.toggle_economy(EconomySlot::Reaction);
+        assert_eq!(app.log.last().unwrap().message, "Fighter uses its reaction");
+
+        app.advance_turn();
+        assert!(!app.encounter.combatants[0].economy.action);
+        assert!(app.encounter.combatants[0].economy.reaction);
+        assert!(!app.encounter.combatants[1].economy.reaction);
+    }
+
+    #[test]
+    fn delayed_creature_reenters_after_chosen_creature() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Fighter");
+        add_basic_combatant(&mut app, "Orc");
+        add_basic_combatant(&mut app, "Wizard");
+        order_ties(&mut app, &["Fighter", "Orc", "Wizard"]);
+
+        app.delay_current_turn();
+        assert_eq!(app.encounter.delayed.len(), 1);
+        assert_eq!(
+            app.encounter.combatants[app.encounter.current_turn_index].name,
+            "Orc"
+        );
+        assert_eq!(app.log.last().unwrap().message, "Fighter delays its turn");
+
+        // Fighter steps in after the Orc's turn and acts next
+        app.complete_resume_delayed(0, Some(0)).unwrap();
+        assert_eq!(app.encounter.combatants[1].name, "Fighter");
+        assert_eq!(
+            app.encounter.combatants[app.encounter.current_turn_index].name,
+            "Orc"
+        );
+        app.advance_turn();
+        assert_eq!(
+            app.encounter.combatants[app.encounter.current_turn_index].name,
+            "Fighter"
+        );
+
+        app.undo();
+        app.undo();
+        assert_eq!(app.encounter.delayed.len(), 1);
+        assert_eq!(app.encounter.combatants.len(), 2);
+    }
+
+    #[test]
+    fn readied_action_spends_reaction_or_lapses_on_next_turn() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Fighter");
+        add_basic_combatant(&mut app, "Orc");
+        order_ties(&mut app, &["Fighter", "Orc"]);
+
+        assert!(app.complete_ready_action("  ".to_string()).is_err());
+        app.complete_ready_action("Orc moves adjacent".to_string())
+            .unwrap();
+        assert_eq!(
+            app.encounter.combatants[0].readied.as_deref(),
+            Some("Orc moves adjacent")
+        );
+
+        app.advance_turn();
+        app.complete_trigger_readied(0).unwrap();
+        assert!(app.encounter.combatants[0].readied.is_none());
+        assert!(app.encounter.combatants[0].economy.reaction);
+        assert!(app.complete_trigger_readied(0).is_err());
+
+        // Unused readied actions are dropped when the creature's turn comes back
+        app.complete_ready_action("Spell is cast".to_string())
+            .unwrap();
+        assert_eq!(
+            app.encounter.combatants[1].readied.as_deref(),
+            Some("Spell is cast")
+        );
+        app.advance_turn();
+        app.advance_turn();
+        assert!(app.encounter.combatants[1].readied.is_none());
+        assert_eq!(
+            app.log.last().unwrap().message,
+            "Orc's readied action lapses (Spell is cast)"
+        );
+    }
+
+    #[test]
+    fn details_follow_the_turn_unless_pinned() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Fighter");
+        add_basic_combatant(&mut app, "Orc");
+        add_basic_combatant(&mut app, "Wizard");
+        order_ties(&mut app, &["Fighter", "Orc", "Wizard"]);
+
+        assert_eq!(app.details_index(), Some(0));
+        app.advance_turn();
+        assert_eq!(app.details_index(), Some(1));
+
+        app.pin_combatant(2);
+        assert!(app.show_details);
+        app.advance_turn();
+        assert_eq!(app.details_index(), Some(2));
+
+        // The pin follows the name when the order shifts, and falls back once removed
+        app.complete_remove(0).unwrap();
+        assert_eq!(app.details_index(), Some(1));
+        app.complete_remove(1).unwrap();
+        assert_eq!(app.details_index(), Some(app.encounter.current_turn_index));
+
+        app.start_pinning();
+        assert!(matches!(app.input_mode, InputMode::Normal));
+        assert!(app.pinned.is_none());
+    }
+
+    #[test]
+    fn notes_are_edited_and_cleared_with_undo() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Orc");
 
-    fn add_basic_combatant(app: &mut App, name: &str) {
-        let state = AddCombatantState {
-            step: 4,
-            name: name.to_string(),
-            initiative: "10".to_string(),
-            hp: "20".to_string(),
-            ac: "15".to_string(),
-            is_player: "n".to_string(),
+        app.choose_notes_target(0);
+        app.complete_edit_notes(0, "  Carries the cellar key ".to_string())
+            .unwrap();
+        assert_eq!(app.encounter.combatants[0].notes, "Carries the cellar key");
+
+        app.choose_notes_target(0);
+        let InputMode::EditingNotes(state) = &app.input_mode else {
+            panic!("expected notes editor");
         };
-        app.complete_add_combatant(state).unwrap();
+        assert_eq!(state.input, "Carries the cellar key");
+
+        app.complete_edit_notes(0, String::new()).unwrap();
+        assert!(app.encounter.combatants[0].notes.is_empty());
+        app.undo();
+        assert_eq!(app.encounter.combatants[0].notes, "Carries the cellar key");
     }
 
     #[test]
-    fn granting_temp_hp_updates_combatant_and_logs() {
-        let mut app = App::new();
-        add_basic_combatant(&mut app, "Orc");
-        app.complete_grant_temp_hp(0, 7).unwrap();
-        assert_eq!(app.encounter.combatants[0].temp_hp, 7);
-        assert!(app.message.as_ref().unwrap().contains("gains 7 temp HP"));
-        assert!(app.log.last().unwrap().message.contains("gains 7 temp HP"));
+    fn fixed_rolls_are_not_logged() {
+        let mut app = test_app();
+        let roll = app.roll_dice("12").unwrap();
+        app.log_roll("Damage", &roll);
+        assert!(app.log.is_empty());
+        assert!(app.roll_dice("2d").is_err());
     }
 
     #[test]
-    fn log_is_capped_at_200_entries() {
-        let mut app = App::new();
-        for i in 0..205 {
-            app.push_log(format!("entry {}", i));
-        }
-        assert_eq!(app.log.len(), 200);
-        assert_eq!(app.log.first().unwrap().message, "entry 5");
+    fn prompt_rolls_are_logged_before_their_outcome() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Orc");
+        app.log.clear();
+        let roll = app.roll_dice("2d6+1").unwrap();
+        app.queue_roll_log("Damage", &roll);
+        app.complete_deal_damage(0, roll.total, DamageType::Untyped, None)
+            .unwrap();
+        assert!(app.log[0].message.starts_with("Damage roll 2d6+1: ["));
+        assert!(app.log[1].message.starts_with("Orc took"));
+
+        // Undo takes the roll back out with the damage
+        app.undo();
+        assert!(app.log.is_empty());
     }
 
     #[test]
-    fn damage_triggers_concentration_check() {
-        let mut app = App::new();
-        add_basic_combatant(&mut app, "Mage");
-        app.encounter.combatants[0]
-            .set_concentration(ConcentrationInfo::new("Haste".to_string(), 3));
-        app.complete_deal_damage(0, 12).unwrap();
-        match &app.input_mode {
-            InputMode::ConcentrationCheck(state) => {
-                assert_eq!(state.dc, 10); // max(10, damage/2)
-                assert!(app.message.as_ref().unwrap().contains("Roll CON save"));
-            }
-            _ => panic!("Expected ConcentrationCheck mode"),
-        }
-        assert!(app.encounter.combatants[0].concentration.is_some());
+    fn prompt_rolls_are_logged_once_and_only_for_actions_that_happen() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Orc");
+        app.log.clear();
+
+        // A failed action logs nothing, leaving the roll for the caller to drop
+        let roll = app.roll_dice("1d8").unwrap();
+        app.queue_roll_log("Healing", &roll);
+        assert!(app.complete_heal(5, roll.total).is_err());
+        assert!(app.log.is_empty());
+        app.pending_roll = None;
+
+        // Flushing after a successful action doesn't log the roll again
+        let roll = app.roll_dice("1d8").unwrap();
+        app.queue_roll_log("Damage", &roll);
+        app.complete_deal_damage(0, roll.total, DamageType::Untyped, None)
+            .unwrap();
+        app.log_pending_roll();
+        assert_eq!(app.log.len(), 2);
     }
 
     // Save/Load Encounters Tests
@@ -759,6 +3338,7 @@ mod tests {
             log: log.clone(),
             saved_at: 1234567890,
             version: env!("CARGO_PKG_VERSION").to_string(),
+            history: Default::default(),
         };
 
         // Serialize to JSON
@@ -776,7 +3356,7 @@ mod tests {
 
     #[test]
     fn start_saving_encounter_enters_correct_mode() {
-        let mut app = App::new();
+        let mut app = test_app();
         add_basic_combatant(&mut app, "Orc");
 
         app.start_saving_encounter();
@@ -791,7 +3371,7 @@ mod tests {
 
     #[test]
     fn start_loading_encounter_enters_correct_mode() {
-        let mut app = App::new();
+        let mut app = test_app();
 
         app.start_loading_encounter();
 
@@ -806,7 +3386,7 @@ mod tests {
 
     #[test]
     fn complete_save_encounter_validates_empty_filename() {
-        let mut app = App::new();
+        let mut app = test_app();
         add_basic_combatant(&mut app, "Goblin");
 
         let result = app.complete_save_encounter("".to_string());
@@ -817,7 +3397,7 @@ mod tests {
 
     #[test]
     fn complete_save_encounter_validates_invalid_characters() {
-        let mut app = App::new();
+        let mut app = test_app();
         add_basic_combatant(&mut app, "Goblin");
 
         let result = app.complete_save_encounter("invalid/filename".to_string());
@@ -831,7 +3411,7 @@ mod tests {
         use std::fs;
         use std::time::{SystemTime, UNIX_EPOCH};
 
-        let mut app = App::new();
+        let mut app = test_app();
         add_basic_combatant(&mut app, "Dragon");
         app.encounter.combatants[0].hp_current = 50;
         app.encounter.round_number = 3;
@@ -852,7 +3432,7 @@ mod tests {
         assert!(save_result.is_ok(), "Save failed: {:?}", save_result);
 
         // Verify file exists
-        let file_path = format!("{}/{}.json", encounters_dir(), test_filename);
+        let file_path = format!("{}/{}.json", encounters_dir(&app.data_dir), test_filename);
         assert!(Path::new(&file_path).exists());
 
         // Modify app state
@@ -872,13 +3452,147 @@ mod tests {
         assert_eq!(app.log.len(), 1);
         assert_eq!(app.log[0].message, "Dragon breathes fire");
 
+        // Undo history survives the round trip
+        assert!(!app.history.undo_stack.is_empty());
+        app.undo();
+        assert!(app.encounter.combatants.is_empty());
+
         // Cleanup
         let _ = fs::remove_file(&file_path);
     }
 
+    #[test]
+    fn undo_after_loading_a_save_in_a_fresh_app() {
+        use std::fs;
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Ogre");
+        app.complete_deal_damage(0, 7, DamageType::Untyped, None)
+            .unwrap();
+        assert_eq!(app.encounter.combatants[0].hp_current, 13);
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let test_filename = format!("test_undo_{}", timestamp);
+        app.complete_save_encounter(test_filename.clone()).unwrap();
+
+        let mut restored = App::with_data_dir(app.data_dir.clone());
+        restored
+            .complete_load_encounter(test_filename.clone())
+            .unwrap();
+        assert_eq!(restored.encounter.combatants[0].hp_current, 13);
+
+        restored.undo();
+        assert_eq!(restored.encounter.combatants[0].hp_current, 20);
+        assert!(!restored.log.iter().any(|e| e.message.contains("took")));
+
+        let _ = fs::remove_file(format!(
+            "{}/{}.json",
+            encounters_dir(&app.data_dir),
+            test_filename
+        ));
+    }
+
+    #[test]
+    fn damage_totals_outlast_the_trimmed_log() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Fighter");
+        add_basic_combatant(&mut app, "Ogre");
+        order_ties(&mut app, &["Fighter", "Ogre"]);
+        app.encounter.combatants[1].hp_max = 1000;
+        app.encounter.combatants[1].hp_current = 1000;
+        for _ in 0..250 {
+            app.complete_deal_damage(1, 2, DamageType::Bludgeoning, Some(0))
+                .unwrap();
+        }
+        assert_eq!(app.log.len(), 200);
+
+        // Undoing a hit takes it back out of the totals
+        app.undo();
+        let record = session::summarize(&app.encounter);
+        assert_eq!(record.damage[0].name, "Fighter");
+        assert_eq!((record.damage[0].dealt, record.damage[1].taken), (498, 498));
+    }
+
+    #[test]
+    fn end_combat_awards_xp_and_saves_a_session() {
+        use std::fs;
+
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Fighter");
+        add_basic_combatant(&mut app, "Goblin");
+        order_ties(&mut app, &["Fighter", "Goblin"]);
+        app.encounter.combatants[0].is_player = true;
+        app.encounter.combatants[1].stat_block = Some(StatBlock {
+            xp: 50,
+            ..StatBlock::default()
+        });
+
+        // Damage is credited to the named attacker, even off its turn
+        app.complete_deal_damage(0, 3, DamageType::Piercing, Some(1))
+            .unwrap();
+        app.complete_deal_damage(1, 20, DamageType::Slashing, app.acting_creature())
+            .unwrap();
+        let tally = |app: &App, name: &str| {
+            let t = app
+                .encounter
+                .damage
+                .iter()
+                .find(|t| t.name == name)
+                .unwrap();
+            (t.dealt, t.taken)
+        };
+        assert_eq!(tally(&app, "Goblin"), (3, 20));
+        assert_eq!(tally(&app, "Fighter"), (20, 3));
+
+        // A monster killed and cleared off the board earlier still pays out
+        add_basic_combatant(&mut app, "Orc");
+        order_ties(&mut app, &["Fighter", "Goblin", "Orc"]);
+        app.encounter.combatants[2].stat_block = Some(StatBlock {
+            xp: 100,
+            ..StatBlock::default()
+        });
+        app.complete_deal_damage(2, 20, DamageType::Slashing, Some(0))
+            .unwrap();
+        app.complete_remove(2).unwrap();
+
+        app.end_combat().unwrap();
+        let InputMode::CombatSummary(summary) = app.input_mode.clone() else {
+            panic!("Expected the combat summary, got {:?}", app.input_mode);
+        };
+        assert_eq!(summary.total_xp, 150);
+        assert_eq!(summary.survivors, vec!["Fighter"]);
+        assert_eq!(summary.xp_per_survivor, 150);
+        assert_eq!(summary.damage[0].dealt, 40);
+        assert!(app.encounter.combatants.is_empty());
+
+        let file_path = format!(
+            "{}/session_{}.json",
+            sessions_dir(&app.data_dir),
+            summary.encounter_id
+        );
+        assert!(Path::new(&file_path).exists());
+
+        // Undo brings the fight back, and ending it again rewrites the same record
+        app.undo();
+        assert_eq!(app.encounter.combatants.len(), 2);
+        assert_eq!(app.encounter.defeated.len(), 1);
+        app.end_combat().unwrap();
+        let InputMode::CombatSummary(again) = app.input_mode.clone() else {
+            panic!("Expected the combat summary, got {:?}", app.input_mode);
+        };
+        assert_eq!(again.encounter_id, summary.encounter_id);
+        let _ = fs::remove_file(&file_path);
+        app.undo();
+        assert!(test_app().end_combat().is_err());
+    }
+
     #[test]
     fn complete_load_encounter_fails_for_missing_file() {
-        let mut app = App::new();
+        let mut app = test_app();
 
         let result = app.complete_load_encounter("nonexistent_file_12345".to_string());
 
@@ -896,7 +3610,7 @@ mod tests {
             .unwrap()
             .as_secs();
 
-        let mut app = App::new();
+        let mut app = test_app();
         add_basic_combatant(&mut app, "Test");
 
         // Create multiple test encounters
@@ -927,23 +3641,24 @@ mod tests {
 
         // Cleanup
         for filename in &filenames {
-            let path = format!("{}/{}.json", encounters_dir(), filename);
+            let path = format!("{}/{}.json", encounters_dir(&app.data_dir), filename);
             let _ = fs::remove_file(&path);
         }
     }
 
-    // Note: Testing the case where the directory doesn't exist is difficult
-    // in a multi-threaded test environment as other tests may create it.
-    // The `list_encounter_files()` function returns an empty vec if the
-    // directory doesn't exist, which is tested implicitly when the directory
-    // is first created by other tests.
+    #[test]
+    fn list_encounter_files_is_empty_without_a_directory() {
+        let app = test_app();
+        assert!(!Path::new(&encounters_dir(&app.data_dir)).exists());
+        assert!(app.list_saved_encounters().is_empty());
+    }
 
     #[test]
     fn save_encounter_includes_all_combatant_state() {
         use std::fs;
         use std::time::{SystemTime, UNIX_EPOCH};
 
-        let mut app = App::new();
+        let mut app = test_app();
         add_basic_combatant(&mut app, "Wizard");
 
         // Add complex state
@@ -966,7 +3681,7 @@ mod tests {
         // Save and load
         app.complete_save_encounter(test_filename.clone()).unwrap();
 
-        let mut new_app = App::new();
+        let mut new_app = App::with_data_dir(app.data_dir.clone());
         new_app
             .complete_load_encounter(test_filename.clone())
             .unwrap();
@@ -988,7 +3703,7 @@ mod tests {
         );
 
         // Cleanup
-        let path = format!("{}/{}.json", encounters_dir(), test_filename);
+        let path = format!("{}/{}.json", encounters_dir(&app.data_dir), test_filename);
         let _ = fs::remove_file(&path);
     }
 
@@ -997,7 +3712,7 @@ mod tests {
         use std::fs;
         use std::time::{SystemTime, UNIX_EPOCH};
 
-        let mut app = App::new();
+        let mut app = test_app();
         add_basic_combatant(&mut app, "Orc");
 
         let timestamp = SystemTime::now()
@@ -1012,7 +3727,7 @@ mod tests {
         assert!(app.message.as_ref().unwrap().contains("saved"));
 
         // Cleanup
-        let path = format!("{}/{}.json", encounters_dir(), test_filename);
+        let path = format!("{}/{}.json", encounters_dir(&app.data_dir), test_filename);
         let _ = fs::remove_file(&path);
     }
 
@@ -1021,7 +3736,7 @@ mod tests {
         use std::fs;
         use std::time::{SystemTime, UNIX_EPOCH};
 
-        let mut app = App::new();
+        let mut app = test_app();
         add_basic_combatant(&mut app, "Goblin");
 
         let timestamp = SystemTime::now()
@@ -1037,7 +3752,7 @@ mod tests {
         assert!(app.message.as_ref().unwrap().contains("loaded"));
 
         // Cleanup
-        let path = format!("{}/{}.json", encounters_dir(), test_filename);
+        let path = format!("{}/{}.json", encounters_dir(&app.data_dir), test_filename);
         let _ = fs::remove_file(&path);
     }
 
@@ -1053,12 +3768,38 @@ mod tests {
                 hp_max: 15,
                 armor_class: 13,
                 is_player: false,
+                initiative_modifier: 0,
+                legendary_actions: 0,
+                legendary_resistances: 0,
+                kind: CombatantKind::Creature,
+                reminder: None,
+                fixed_initiative: None,
+                defenses: DamageDefenses::default(),
+                stat_block: None,
+                notes: String::new(),
+                hit_dice: None,
+                resources: Vec::new(),
+                count: 1,
+                group_initiative: false,
             },
             LibraryCombatant {
                 name: "Goblin".to_string(),
                 hp_max: 7,
                 armor_class: 15,
                 is_player: false,
+                initiative_modifier: 2,
+                legendary_actions: 1,
+                legendary_resistances: 0,
+                kind: CombatantKind::Creature,
+                reminder: None,
+                fixed_initiative: None,
+                defenses: DamageDefenses::default(),
+                stat_block: None,
+                notes: String::new(),
+                hit_dice: Some("2d6".to_string()),
+                resources: Vec::new(),
+                count: 1,
+                group_initiative: false,
             },
         ];
 
@@ -1089,7 +3830,7 @@ mod tests {
 
     #[test]
     fn start_saving_library_validates_non_empty_encounter() {
-        let mut app = App::new();
+        let mut app = test_app();
         app.start_saving_library();
 
         // Should set error message and remain in Normal mode
@@ -1109,7 +3850,7 @@ mod tests {
             difficulty: "Easy".to_string(),
         };
 
-        let mut app = App::new();
+        let mut app = test_app();
         let result = app.complete_save_library(state);
 
         assert!(result.is_err());
@@ -1125,7 +3866,7 @@ mod tests {
             difficulty: "Easy".to_string(),
         };
 
-        let mut app = App::new();
+        let mut app = test_app();
         let result = app.complete_save_library(state);
 
         assert!(result.is_err());
@@ -1141,7 +3882,7 @@ mod tests {
             difficulty: "Easy".to_string(),
         };
 
-        let mut app = App::new();
+        let mut app = test_app();
         let result = app.complete_save_library(state);
 
         assert!(result.is_err());
@@ -1153,7 +3894,7 @@ mod tests {
         use std::fs;
         use std::time::{SystemTime, UNIX_EPOCH};
 
-        let mut app = App::new();
+        let mut app = test_app();
 
         // Add combatants with various states
         add_basic_combatant(&mut app, "Dragon");
@@ -1161,6 +3902,7 @@ mod tests {
         app.encounter.combatants[0].temp_hp = 10;
 
         add_basic_combatant(&mut app, "Kobold");
+        order_ties(&mut app, &["Dragon", "Kobold"]);
         app.encounter.combatants[1].hp_current = 7;
 
         let timestamp = SystemTime::now()
@@ -1181,11 +3923,11 @@ mod tests {
         assert!(save_result.is_ok(), "Save failed: {:?}", save_result);
 
         // Verify file exists
-        let file_path = format!("{}/{}.json", library_dir(), test_name);
+        let file_path = format!("{}/{}.json", library_dir(&app.data_dir), test_name);
         assert!(Path::new(&file_path).exists());
 
         // Load the template
-        let loaded_template = load_library_template(&test_name);
+        let loaded_template = load_library_template(&app.data_dir, &test_name);
         assert!(loaded_template.is_ok());
 
         let template = loaded_template.unwrap();
@@ -1209,12 +3951,489 @@ mod tests {
         let _ = fs::remove_file(&file_path);
     }
 
+    #[test]
+    fn numbered_copies_fold_into_one_library_entry_and_expand_on_load() {
+        use std::fs;
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let mut app = test_app();
+        app.templates = vec![CombatantTemplate::from_stats(
+            "Goblin".to_string(),
+            7,
+            15,
+            false,
+            2,
+        )];
+        add_basic_combatant(&mut app, "Hero");
+        app.complete_add_copies(CopiesState {
+            template_index: 0,
+            count: "3".to_string(),
+            group_initiative: true,
+        })
+        .unwrap();
+        // A lone numbered creature keeps its own name
+        add_basic_combatant(&mut app, "Orc 2");
+        order_ties(&mut app, &["Hero", "Orc 2"]);
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let test_name = format!("test_copies_{}", timestamp);
+        app.complete_save_library(SaveLibraryState {
+            step: 2,
+            name: test_name.clone(),
+            description: "Goblin ambush".to_string(),
+            difficulty: String::new(),
+        })
+        .unwrap();
+
+        let template = load_library_template(&app.data_dir, &test_name).unwrap();
+        let names: Vec<_> = template
+            .combatants
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(names.iter().filter(|n| **n == "Goblin").count(), 1);
+        assert!(names.contains(&"Orc 2"));
+        let goblins = template.combatants.iter().find(|c| c.name == "Goblin");
+        assert_eq!(goblins.unwrap().count, 3);
+        assert!(goblins.unwrap().group_initiative);
+
+        app.confirm_load_library(test_name.clone()).unwrap();
+        let goblins: Vec<_> = app
+            .encounter
+            .combatants
+            .iter()
+            .filter(|c| c.name.starts_with("Goblin"))
+            .collect();
+        let mut goblin_names: Vec<_> = goblins.iter().map(|c| c.name.as_str()).collect();
+        goblin_names.sort();
+        assert_eq!(goblin_names, vec!["Goblin 1", "Goblin 2", "Goblin 3"]);
+        assert!(
+            goblins
+                .iter()
+                .all(|c| c.initiative == goblins[0].initiative)
+        );
+
+        let path = format!("{}/{}.json", library_dir(&app.data_dir), test_name);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn roster_pc_saved_as_template_keeps_its_hp() {
+        let mut app = test_app();
+        app.party.members = vec![PartyMember {
+            name: "Paladin".to_string(),
+            hp_max: 44,
+            hp_current: 44,
+            armor_class: 18,
+            hit_dice: Some("5d10+10".to_string()),
+            ..PartyMember::default()
+        }];
+        app.add_roster_member(0).unwrap();
+        let InputMode::AddingCombatant(mut state) = app.input_mode.clone() else {
+            panic!("Expected the initiative prompt, got {:?}", app.input_mode);
+        };
+        state.initiative = "12".to_string();
+        app.complete_add_combatant(state).unwrap();
+
+        let paladin = &app.encounter.combatants[0];
+        assert_eq!(paladin.hit_dice_pool.as_deref(), Some("5d10+10"));
+        assert_eq!(paladin.hit_dice, None);
+        assert_eq!(library_entries(&app.encounter.combatants)[0].hp_max, 44);
+
+        app.save_template_from_combatant(0).unwrap();
+        let tpl = app.templates.iter().find(|t| t.name == "Paladin").unwrap();
+        assert_eq!(tpl.hp_max, 44);
+        assert_eq!(tpl.hit_dice, None);
+    }
+
+    #[test]
+    fn roster_pcs_keep_lasting_state_between_encounters() {
+        let mut app = test_app();
+        app.party.members = vec![PartyMember {
+            name: "Fighter".to_string(),
+            hp_max: 30,
+            hp_current: 12,
+            armor_class: 18,
+            hit_dice: Some("3d10".to_string()),
+            hit_dice_spent: 1,
+            exhaustion: 2,
+            lasting_effects: vec![StatusEffect::new(ConditionType::Poisoned, 0, None)],
+            ..PartyMember::default()
+        }];
+
+        app.add_roster_member(0).unwrap();
+        let InputMode::AddingCombatant(mut state) = app.input_mode.clone() else {
+            panic!("Expected the initiative prompt, got {:?}", app.input_mode);
+        };
+        state.initiative = "15".to_string();
+        app.complete_add_combatant(state).unwrap();
+
+        let fighter = &app.encounter.combatants[0];
+        assert!(fighter.is_player);
+        assert_eq!((fighter.hp_current, fighter.hp_max), (12, 30));
+        assert_eq!((fighter.hit_dice_spent, fighter.exhaustion), (1, 2));
+        assert_eq!(fighter.status_effects.len(), 1);
+        assert!(app.add_roster_member(0).is_err());
+
+        // Timed conditions end with the fight; untracked PCs stay off the roster
+        app.complete_deal_damage(0, 5, DamageType::Untyped, None)
+            .unwrap();
+        app.complete_set_exhaustion(0, 3).unwrap();
+        app.complete_add_status(0, ConditionType::Prone, 2, None)
+            .unwrap();
+        add_basic_combatant(&mut app, "Wizard");
+        app.encounter.combatants[1].is_player = true;
+        let recorded = app.party.record(&app.encounter.combatants, false);
+
+        assert_eq!(recorded, 1);
+        assert_eq!(app.party.members.len(), 1);
+        let member = &app.party.members[0];
+        assert_eq!((member.hp_current, member.exhaustion), (7, 3));
+        assert_eq!(
+            member.lasting_effects,
+            vec![StatusEffect::new(ConditionType::Poisoned, 0, None)]
+        );
+        assert!(app.complete_set_exhaustion(0, 7).is_err());
+    }
+
+    #[test]
+    fn rests_recover_encounter_pcs_and_can_be_undone() {
+        let mut app = test_app();
+        app.dice = DiceRoller::seeded(3);
+        add_basic_combatant(&mut app, "Cleric");
+        add_basic_combatant(&mut app, "Goblin");
+        order_ties(&mut app, &["Cleric", "Goblin"]);
+        let cleric = &mut app.encounter.combatants[0];
+        cleric.is_player = true;
+        cleric.hit_dice_pool = Some("2d8+2".to_string());
+        cleric.hp_current = 4;
+        cleric.temp_hp = 3;
+        cleric.exhaustion = 1;
+        app.encounter.combatants[1].hp_current = 5;
+        assert_eq!(app.rest_targets(false).len(), 1);
+
+        app.complete_rest(RestState {
+            hit_dice: vec![1],
+            ..RestState::default()
+        })
+        .unwrap();
+        let cleric = &app.encounter.combatants[0];
+        assert_eq!(cleric.hit_dice_spent, 1);
+        assert!(cleric.hp_current >= 4 + 3);
+        let last = &app.log.last().unwrap().message;
+        assert!(
+            last.starts_with("Cleric spends 1 hit die (d8+1: "),
+            "{}",
+            last
+        );
+
+        app.complete_rest(RestState {
+            long: true,
+            ..RestState::default()
+        })
+        .unwrap();
+        let cleric = &app.encounter.combatants[0];
+        assert_eq!((cleric.hp_current, cleric.temp_hp), (20, 0));
+        assert_eq!((cleric.exhaustion, cleric.hit_dice_spent), (0, 0));
+        assert_eq!(app.encounter.combatants[1].hp_current, 5); // NPCs don't rest
+
+        app.undo();
+        assert_eq!(app.encounter.combatants[0].hit_dice_spent, 1);
+    }
+
+    #[test]
+    fn resources_are_spent_restored_and_recovered_on_rests() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Cleric");
+        app.encounter.combatants[0].is_player = true;
+        assert!(app.adjust_resource(0, 0, true).is_err());
+
+        app.complete_set_resources(0, "slots 2 1; Channel Divinity 1/sr")
+            .unwrap();
+        app.adjust_resource(0, 0, true).unwrap();
+        app.adjust_resource(0, 2, true).unwrap();
+        assert_eq!(
+            app.log.last().unwrap().message,
+            "Cleric uses Channel Divinity (0/1 left)"
+        );
+        assert!(app.adjust_resource(0, 2, true).is_err());
+        assert!(app.adjust_resource(0, 1, false).is_err());
+
+        // Edits keep what was already spent
+        app.complete_set_resources(0, "slots 3 1; Channel Divinity 2/sr")
+            .unwrap();
+        let summary = resources::summary(&app.encounter.combatants[0].resources);
+        assert_eq!(summary, "L1 2/3 L2 1/1 CD 1/2");
+
+        app.undo();
+        app.undo();
+        assert_eq!(app.encounter.combatants[0].resources[2].used, 0);
+
+        app.adjust_resource(0, 0, true).unwrap();
+        app.adjust_resource(0, 2, true).unwrap();
+        app.complete_rest(RestState::default()).unwrap();
+        let cleric = &app.encounter.combatants[0];
+        assert_eq!((cleric.resources[0].used, cleric.resources[2].used), (2, 0));
+        assert_eq!(
+            app.log.last().unwrap().message,
+            "Cleric regains Channel Divinity"
+        );
+
+        app.complete_rest(RestState {
+            long: true,
+            ..RestState::default()
+        })
+        .unwrap();
+        assert_eq!(app.encounter.combatants[0].resources[0].used, 0);
+    }
+
+    #[test]
+    fn recharge_abilities_roll_at_the_start_of_their_turn() {
+        let seed_for = |roll: i32| {
+            (0..)
+                .find(|&seed| DiceRoller::seeded(seed).roll_die(6) == roll)
+                .unwrap()
+        };
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Dragon");
+        add_basic_combatant(&mut app, "Fighter");
+        order_ties(&mut app, &["Dragon", "Fighter"]);
+        app.complete_set_resources(0, "Fire Breath 1/r5").unwrap();
+        app.adjust_resource(0, 0, true).unwrap();
+
+        app.dice = DiceRoller::seeded(seed_for(4));
+        app.advance_turn();
+        app.advance_turn();
+        assert_eq!(app.encounter.combatants[0].resources[0].used, 1);
+        assert_eq!(
+            app.log.last().unwrap().message,
+            "Dragon's Fire Breath does not recharge (rolled 4)"
+        );
+
+        app.dice = DiceRoller::seeded(seed_for(5));
+        app.advance_turn();
+        app.advance_turn();
+        assert_eq!(app.encounter.combatants[0].resources[0].used, 0);
+        assert_eq!(
+            app.message.as_deref(),
+            Some("Dragon: Fire Breath recharges (rolled 5)")
+        );
+
+        // Nothing to roll while the ability is unspent
+        let logged = app.log.len();
+        app.advance_turn();
+        app.advance_turn();
+        assert_eq!(app.log.len(), logged);
+    }
+
+    #[test]
+    fn ongoing_effects_go_through_the_damage_pipeline() {
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Cleric");
+        add_basic_combatant(&mut app, "Troll");
+        order_ties(&mut app, &["Cleric", "Troll"]);
+        let cleric = &mut app.encounter.combatants[0];
+        cleric.is_player = true;
+        cleric.hp_current = 1;
+        app.complete_add_ongoing_effect(0, "Heat Metal: 2 fire end")
+            .unwrap();
+        app.complete_add_ongoing_effect(1, "Burning: 3 fire start 1")
+            .unwrap();
+        app.complete_add_ongoing_effect(1, "Regeneration: 10 heal unless fire")
+            .unwrap();
+        assert!(
+            app.complete_add_ongoing_effect(1, "Burning: 1q6 fire")
+                .is_err()
+        );
+        let troll = &mut app.encounter.combatants[1];
+        troll.hp_current = 10;
+        troll
+            .concentration
+            .replace(ConcentrationInfo::new("Bless".to_string(), 0));
+        app.input_mode = InputMode::Normal;
+
+        // The cleric's Heat Metal drops it at the end of its turn; the troll's
+        // burning hits at the start of its turn and stops its regeneration
+        app.advance_turn();
+        let cleric = &app.encounter.combatants[0];
+        assert_eq!(cleric.hp_current, 0);
+        assert!(cleric.death_saves.is_some());
+        assert_eq!(app.encounter.combatants[1].hp_current, 7);
+        assert!(matches!(
+            app.input_mode,
+            InputMode::ConcentrationCheck(ConcentrationCheckState { dc: 10, .. })
+        ));
+        let message = app.message.clone().unwrap();
+        assert!(message.starts_with("Heat Metal: Cleric took 2 fire damage"));
+        assert!(message.contains("Regeneration doesn't work this turn (fire damage)"));
+        // Nobody is credited with damage from an ongoing effect
+        assert!(app.encounter.damage.iter().all(|t| t.dealt == 0));
+
+        // Burning has run out, so regeneration works next time around
+        app.cancel_input();
+        app.advance_turn();
+        app.advance_turn();
+        let cleric = &app.encounter.combatants[0];
+        assert_eq!(cleric.death_saves.as_ref().unwrap().failures, 1);
+        let troll = &app.encounter.combatants[1];
+        assert_eq!(troll.hp_current, 17);
+        assert_eq!(troll.ongoing_effects.len(), 1);
+        assert_eq!(
+            app.log.last().unwrap().message,
+            "Troll healed 10 HP (HP: 17)"
+        );
+
+        app.remove_ongoing_effect(1, 0).unwrap();
+        assert!(app.encounter.combatants[1].ongoing_effects.is_empty());
+        app.undo();
+        assert_eq!(app.encounter.combatants[1].ongoing_effects.len(), 1);
+    }
+
+    #[test]
+    fn repeat_saves_end_conditions_at_the_end_of_the_turn() {
+        let seed_for = |natural: i32| {
+            (0..)
+                .find(|&seed| DiceRoller::seeded(seed).roll_die(20) == natural)
+                .unwrap()
+        };
+        let mut app = test_app();
+        add_basic_combatant(&mut app, "Goblin");
+        add_basic_combatant(&mut app, "Cleric");
+        order_ties(&mut app, &["Goblin", "Cleric"]);
+        let mut block = StatBlock::default();
+        block.saving_throws.insert(Ability::Wis, 2);
+        app.encounter.combatants[0].stat_block = Some(block);
+        app.encounter.combatants[1].is_player = true;
+        let wis = |dc| {
+            Some(RepeatSave {
+                ability: Ability::Wis,
+                dc,
+            })
+        };
+        app.complete_add_status(0, ConditionType::Paralyzed, 0, wis(14))
+            .unwrap();
+        app.complete_add_status(1, ConditionType::Frightened, 0, wis(13))
+            .unwrap();
+
+        // NPCs roll with their save bonus
+        app.dice = DiceRoller::seeded(seed_for(1));
+        app.advance_turn();
+        assert_eq!(app.encounter.combatants[0].status_effects.len(), 1);
+        assert_eq!(
+            app.message.as_deref(),
+            Some("Goblin fails a WIS save against Paralyzed (3 vs DC 14)")
+        );
+
+        // PCs are prompted for theirs
+        app.advance_turn();
+        let InputMode::RepeatSave(state) = app.input_mode.clone() else {
+            panic!("expected a save prompt, got {:?}", app.input_mode);
+        };
+        assert_eq!(state.condition, ConditionType::Frightened);
+        app.complete_repeat_save(state.clone(), 15).unwrap();
+        assert!(app.encounter.combatants[1].status_effects.is_empty());
+        assert_eq!(
+            app.log.last().unwrap().message,
+            "Cleric succeeds on a WIS save against Frightened (15 vs DC 13); Frightened ends"
+        );
+        assert_eq!(app.input_mode, InputMode::Normal);
+
+        // The save is part of the "Next turn" undo step
+        app.undo();
+        assert_eq!(app.encounter.current_turn_index, 1);
+        assert_eq!(app.encounter.combatants[1].status_effects.len(), 1);
+        app.redo();
+        assert!(app.encounter.combatants[1].status_effects.is_empty());
+
+        // A stale prompt doesn't end the condition once it carries a different save
+        let cha = Some(RepeatSave {
+            ability: Ability::Cha,
+            dc: 15,
+        });
+        app.complete_add_status(1, ConditionType::Frightened, 0, cha)
+            .unwrap();
+        app.complete_repeat_save(state, 20).unwrap();
+        assert_eq!(app.encounter.combatants[1].status_effects[0].save, cha);
+        assert_eq!(
+            app.message.as_deref(),
+            Some("Cleric no longer has Frightened with that save")
+        );
+        app.undo();
+
+        app.dice = DiceRoller::seeded(seed_for(20));
+        app.advance_turn();
+        assert!(app.encounter.combatants[0].status_effects.is_empty());
+        app.undo();
+        assert_eq!(app.encounter.combatants[0].status_effects.len(), 1);
+    }
+
+    #[test]
+    fn library_save_fills_in_computed_difficulty() {
+        use std::fs;
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let mut app = test_app();
+        app.party = Party {
+            levels: vec![3, 3, 3, 3],
+            ..Party::default()
+        };
+        let mut hobgoblin =
+            CombatantTemplate::from_stats("Hobgoblin".to_string(), 11, 18, false, 1);
+        hobgoblin.stat_block = Some(StatBlock {
+            challenge_rating: "1/2".to_string(),
+            xp: 100,
+            ..Default::default()
+        });
+        let mut bugbear = CombatantTemplate::from_stats("Bugbear".to_string(), 27, 16, false, 2);
+        bugbear.stat_block = Some(StatBlock {
+            challenge_rating: "1".to_string(),
+            xp: 200,
+            ..Default::default()
+        });
+        app.templates = vec![hobgoblin, bugbear];
+        app.complete_add_copies(CopiesState {
+            template_index: 0,
+            count: "3".to_string(),
+            group_initiative: false,
+        })
+        .unwrap();
+        app.add_combatant_from_template(1).unwrap();
+        add_basic_combatant(&mut app, "Scout"); // No CR, left out of the totals
+
+        let report = app.encounter_difficulty();
+        assert_eq!((report.adjusted_xp, report.unrated), (1000, 1));
+        assert_eq!(report.rating, Some(crate::models::Difficulty::Hard));
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let test_name = format!("test_difficulty_{}", timestamp);
+        app.complete_save_library(SaveLibraryState {
+            step: 2,
+            name: test_name.clone(),
+            description: "Hobgoblin patrol".to_string(),
+            difficulty: String::new(),
+        })
+        .unwrap();
+
+        let template = load_library_template(&app.data_dir, &test_name).unwrap();
+        assert_eq!(template.difficulty, "Hard");
+        assert_eq!(app.library_difficulty(&template), report);
+
+        let path = format!("{}/{}.json", library_dir(&app.data_dir), test_name);
+        let _ = fs::remove_file(&path);
+    }
+
     #[test]
     fn library_combatant_strips_runtime_state() {
         use std::fs;
         use std::time::{SystemTime, UNIX_EPOCH};
 
-        let mut app = App::new();
+        let mut app = test_app();
 
         // Add combatant with various runtime state
         add_basic_combatant(&mut app, "Orc");
@@ -1248,7 +4467,7 @@ mod tests {
         app.complete_save_library(state).unwrap();
 
         // Load and verify state was stripped
-        let template = load_library_template(&test_name).unwrap();
+        let template = load_library_template(&app.data_dir, &test_name).unwrap();
         let lib_combatant = &template.combatants[0];
 
         // Only basic stats should be present
@@ -1261,7 +4480,7 @@ mod tests {
         // concentration, status_effects, or initiative fields at all
 
         // Cleanup
-        let path = format!("{}/{}.json", library_dir(), test_name);
+        let path = format!("{}/{}.json", library_dir(&app.data_dir), test_name);
         let _ = fs::remove_file(&path);
     }
 
@@ -1275,12 +4494,12 @@ mod tests {
             .unwrap()
             .as_secs();
         let test_name = format!("test_exists_{}", timestamp);
+        let mut app = test_app();
 
         // Should not exist initially
-        assert!(!library_template_exists(&test_name));
+        assert!(!library_template_exists(&app.data_dir, &test_name));
 
         // Create template
-        let mut app = App::new();
         add_basic_combatant(&mut app, "Goblin");
 
         let state = SaveLibraryState {
@@ -1293,14 +4512,14 @@ mod tests {
         app.complete_save_library(state).unwrap();
 
         // Should exist now
-        assert!(library_template_exists(&test_name));
+        assert!(library_template_exists(&app.data_dir, &test_name));
 
         // Cleanup
-        let path = format!("{}/{}.json", library_dir(), test_name);
+        let path = format!("{}/{}.json", library_dir(&app.data_dir), test_name);
         let _ = fs::remove_file(&path);
 
         // Should not exist after cleanup
-        assert!(!library_template_exists(&test_name));
+        assert!(!library_template_exists(&app.data_dir, &test_name));
     }
 
     #[test]
@@ -1320,7 +4539,7 @@ mod tests {
         ];
 
         // Create templates
-        let mut app = App::new();
+        let mut app = test_app();
         add_basic_combatant(&mut app, "Test");
 
         for name in &names {
@@ -1334,7 +4553,7 @@ mod tests {
         }
 
         // Get list
-        let list = list_library_files().unwrap();
+        let list = list_library_files(&app.data_dir).unwrap();
 
         // Verify all test files are present
         for name in &names {
@@ -1352,7 +4571,7 @@ mod tests {
 
         // Cleanup
         for name in &names {
-            let path = format!("{}/{}.json", library_dir(), name);
+            let path = format!("{}/{}.json", library_dir(&app.data_dir), name);
             let _ = fs::remove_file(&path);
         }
     }
@@ -1362,7 +4581,7 @@ mod tests {
         use std::fs;
         use std::time::{SystemTime, UNIX_EPOCH};
 
-        let mut app = App::new();
+        let mut app = test_app();
         add_basic_combatant(&mut app, "Troll");
 
         let timestamp = SystemTime::now()
@@ -1403,7 +4622,7 @@ mod tests {
         assert!(app.message.as_ref().unwrap().contains("Overwrite?"));
 
         // Cleanup
-        let path = format!("{}/{}.json", library_dir(), test_name);
+        let path = format!("{}/{}.json", library_dir(&app.data_dir), test_name);
         let _ = fs::remove_file(&path);
     }
 
@@ -1412,11 +4631,12 @@ mod tests {
         use std::fs;
         use std::time::{SystemTime, UNIX_EPOCH};
 
-        let mut app = App::new();
+        let mut app = test_app();
 
         // Create and save a template
         add_basic_combatant(&mut app, "Orc");
         add_basic_combatant(&mut app, "Goblin");
+        order_ties(&mut app, &["Orc", "Goblin"]);
 
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -1434,7 +4654,7 @@ mod tests {
         app.complete_save_library(state).unwrap();
 
         // Load template
-        let template = load_library_template(&test_name).unwrap();
+        let template = load_library_template(&app.data_dir, &test_name).unwrap();
 
         // Create LoadLibraryState with initiatives
         let combatants_with_init: Vec<(LibraryCombatant, String)> = template
@@ -1448,6 +4668,7 @@ mod tests {
             template: template.clone(),
             combatants_with_init,
             current_index: 0,
+            roll_log: Vec::new(),
         };
 
         // Clear encounter and finalize load
@@ -1473,7 +4694,84 @@ mod tests {
         assert_eq!(orc.initiative, 15);
 
         // Cleanup
-        let path = format!("{}/{}.json", library_dir(), test_name);
+        let path = format!("{}/{}.json", library_dir(&app.data_dir), test_name);
         let _ = fs::remove_file(&path);
     }
+
+    #[test]
+    fn library_load_prompts_only_for_player_characters() {
+        use std::fs;
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let mut app = test_app();
+        let mut goblin = Combatant::new("Goblin".to_string(), 10, 7, 15, false);
+        goblin.initiative_modifier = 2;
+        goblin.legendary_resistances = Some(LegendaryPool {
+            max: 2,
+            remaining: 0,
+        });
+        app.encounter.add_combatant(goblin);
+        app.encounter.add_combatant(Combatant::new_turn_entry(
+            "Collapsing Ceiling".to_string(),
+            15,
+            CombatantKind::Hazard,
+            Some("DC 13 Dex save or 2d10".to_string()),
+        ));
+        app.encounter
+            .add_combatant(Combatant::new("Wizard".to_string(), 12, 20, 12, true));
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let test_name = format!("test_npc_roll_{}", timestamp);
+        app.complete_save_library(SaveLibraryState {
+            step: 2,
+            name: test_name.clone(),
+            description: "NPC roll test".to_string(),
+            difficulty: String::new(),
+        })
+        .unwrap();
+
+        app.encounter.combatants.clear();
+        app.select_library_template(test_name.clone()).unwrap();
+        match &app.input_mode {
+            InputMode::SettingLibraryInitiatives(state) => {
+                assert_eq!(
+                    state.combatants_with_init[state.current_index].0.name,
+                    "Wizard"
+                );
+                assert_eq!(state.roll_log.len(), 1);
+            }
+            other => panic!("expected initiative prompt, got {:?}", other),
+        }
+
+        app.complete_library_initiative("14".to_string()).unwrap();
+        assert!(matches!(app.input_mode, InputMode::Normal));
+        let goblin = app
+            .encounter
+            .combatants
+            .iter()
+            .find(|c| c.name == "Goblin")
+            .unwrap();
+        assert_eq!(goblin.initiative_modifier, 2);
+        assert!((3..=22).contains(&goblin.initiative));
+        assert_eq!(goblin.legendary_resistances, Some(LegendaryPool::new(2)));
+        let hazard = app
+            .encounter
+            .combatants
+            .iter()
+            .find(|c| c.name == "Collapsing Ceiling")
+            .unwrap();
+        assert_eq!(hazard.kind, CombatantKind::Hazard);
+        assert_eq!(hazard.initiative, 15);
+        assert_eq!(hazard.reminder.as_deref(), Some("DC 13 Dex save or 2d10"));
+        assert!(
+            app.log
+                .iter()
+                .any(|e| e.message.starts_with("Initiative roll for Goblin 1d20+2"))
+        );
+
+        let _ = fs::remove_file(format!("{}/{}.json", library_dir(&app.data_dir), test_name));
+    }
 }
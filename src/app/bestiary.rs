@@ -0,0 +1,690 @@
+//! Imports SRD 5.1 monsters from local JSON dumps in the Open5e or 5e-database schema.
+
+use crate::dice::DiceExpr;
+use crate::models::stat_block::{
+    AbilityScores, StatBlockEntry, challenge_rating_value, xp_for_challenge_rating,
+};
+use crate::models::{
+    Ability, CombatantTemplate, DamageDefenses, DamageType, DefenseKind, StatBlock, resources,
+};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+
+pub fn bestiary_dir(data_dir: &Path) -> String {
+    super::persistence::data_path(data_dir, "bestiary")
+}
+
+/// Monsters read from one or more bestiary files
+#[derive(Debug, Default)]
+pub struct BestiaryImport {
+    pub monsters: Vec<CombatantTemplate>,
+    /// Entries (or whole files) that couldn't be read as monsters
+    pub skipped: usize,
+}
+
+/// Reads every `.json` file in the bestiary directory, sorted by monster name.
+pub fn load_bestiary(data_dir: &Path) -> Result<BestiaryImport, String> {
+    let dir = bestiary_dir(data_dir);
+    if !Path::new(&dir).exists() {
+        return Err(format!(
+            "No {}/ directory found; add Open5e or 5e-database monster JSON files there",
+            dir
+        ));
+    }
+
+    let entries = fs::read_dir(dir).map_err(|e| {
+        log::error!("Failed to read bestiary directory: {}", e);
+        format!("Could not read bestiary directory: {}", e)
+    })?;
+
+    let mut paths: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    let mut import = BestiaryImport::default();
+    for path in paths {
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| parse_monsters(&content));
+        match parsed {
+            Ok(file) => {
+                import.monsters.extend(file.monsters);
+                import.skipped += file.skipped;
+            }
+            Err(e) => {
+                log::error!("Skipping bestiary file {}: {}", path.display(), e);
+                import.skipped += 1;
+            }
+        }
+    }
+
+    import
+        .monsters
+        .sort_by_key(|monster| monster.name.to_lowercase());
+    Ok(import)
+}
+
+/// Accepts a list of monsters, a single monster, or an Open5e API page (`results`).
+pub fn parse_monsters(content: &str) -> Result<BestiaryImport, String> {
+    let value: Value = serde_json::from_str(content).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let items = match value {
+        Value::Array(items) => items,
+        Value::Object(mut page) => match page.remove("results") {
+            Some(Value::Array(items)) => items,
+            None if page.contains_key("name") => vec![Value::Object(page)],
+            _ => return Err("Expected a list of monsters".to_string()),
+        },
+        _ => return Err("Expected a list of monsters".to_string()),
+    };
+
+    let mut import = BestiaryImport::default();
+    for item in items {
+        match parse_monster(item) {
+            Some(monster) => import.monsters.push(monster),
+            None => import.skipped += 1,
+        }
+    }
+    Ok(import)
+}
+
+/// Maps the fields both schemas share onto a template. Name, hit points and
+/// armor class are required; anything not understood is kept in `StatBlock::extra`.
+pub fn parse_monster(value: Value) -> Option<CombatantTemplate> {
+    let Value::Object(mut fields) = value else {
+        return None;
+    };
+
+    let name = take_with(&mut fields, "name", |v| {
+        v.as_str()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+    })?;
+    let hp_max = take_with(&mut fields, "hit_points", |v| v.as_i64())? as i32;
+    let armor_class = take_with(&mut fields, "armor_class", armor_class)?;
+
+    let mut score =
+        |key: &str| take_with(&mut fields, key, |v| v.as_i64()).map_or(10, |n| n as i32);
+    let ability_scores = AbilityScores {
+        strength: score("strength"),
+        dexterity: score("dexterity"),
+        constitution: score("constitution"),
+        intelligence: score("intelligence"),
+        wisdom: score("wisdom"),
+        charisma: score("charisma"),
+    };
+
+    let mut block = StatBlock {
+        ability_scores,
+        ..StatBlock::default()
+    };
+    take_saving_throws(&mut fields, &mut block);
+
+    let text = |v: &Value| v.as_str().map(|s| s.trim().to_string());
+    block.size = take_with(&mut fields, "size", text).unwrap_or_default();
+    block.creature_type = take_with(&mut fields, "type", text)
+        .unwrap_or_default()
+        .to_lowercase();
+    block.tags = take_with(&mut fields, "subtype", labels).unwrap_or_default();
+    for tag in take_with(&mut fields, "tags", labels).unwrap_or_default() {
+        if !block.tags.contains(&tag) {
+            block.tags.push(tag);
+        }
+    }
+
+    if let Some(cr) = take_with(&mut fields, "challenge_rating", challenge_rating) {
+        // Open5e repeats the rating as a number
+        take_with(&mut fields, "cr", |v| v.as_f64());
+        block.challenge_rating = cr;
+    }
+    block.xp = take_with(&mut fields, "xp", |v| v.as_u64())
+        .map(|xp| xp as u32)
+        .or_else(|| xp_for_challenge_rating(&block.challenge_rating))
+        .unwrap_or(0);
+    block.speed = take_with(&mut fields, "speed", speed).unwrap_or_default();
+    block.senses = take_with(&mut fields, "senses", senses).unwrap_or_default();
+    block.traits = take_with(&mut fields, "special_abilities", entries).unwrap_or_default();
+    block.actions = take_with(&mut fields, "actions", entries).unwrap_or_default();
+    block.reactions = take_with(&mut fields, "reactions", entries).unwrap_or_default();
+
+    let mut defenses = DamageDefenses::default();
+    for (key, kind) in [
+        ("damage_resistances", DefenseKind::Resistance),
+        ("damage_immunities", DefenseKind::Immunity),
+        ("damage_vulnerabilities", DefenseKind::Vulnerability),
+    ] {
+        if let Some(types) = take_with(&mut fields, key, damage_types) {
+            for damage_type in types {
+                if !defenses.has(kind, damage_type) {
+                    defenses.toggle(kind, damage_type);
+                }
+            }
+        }
+    }
+
+    let mut template = CombatantTemplate::from_stats(
+        name,
+        hp_max,
+        armor_class,
+        false,
+        block.ability_scores.modifier(Ability::Dex),
+    );
+    template.legendary_actions = legendary_action_count(&fields);
+    template.legendary_resistances = legendary_resistance_count(&block.traits);
+    let entry_names: Vec<&str> = [&block.traits, &block.actions, &block.reactions]
+        .into_iter()
+        .flatten()
+        .map(|entry| entry.name.as_str())
+        .collect();
+    template.resources = resources::recharge_abilities(&entry_names);
+    template.defenses = defenses;
+    // 5e-database's `hit_points_roll` includes the CON bonus; its `hit_dice` doesn't
+    template.hit_dice = take_with(&mut fields, "hit_points_roll", hit_dice)
+        .or_else(|| take_with(&mut fields, "hit_dice", hit_dice));
+
+    // Nulls (e.g. Open5e's non-proficient saves) carry nothing worth keeping
+    fields.retain(|_, v| !v.is_null());
+    block.extra = fields.into_iter().collect();
+    template.stat_block = Some(block);
+    Some(template)
+}
+
+/// Removes `key` only when `parse` understands its value, so unknown shapes are kept.
+fn take_with<T>(
+    fields: &mut Map<String, Value>,
+    key: &str,
+    parse: impl FnOnce(&Value) -> Option<T>,
+) -> Option<T> {
+    let parsed = parse(fields.get(key)?)?;
+    fields.remove(key);
+    Some(parsed)
+}
+
+/// A dice formula such as "2d6" or "17d10+85"
+fn hit_dice(value: &Value) -> Option<String> {
+    let formula = value.as_str()?.trim();
+    let expr = DiceExpr::parse(formula).ok()?;
+    (!expr.is_constant()).then(|| formula.to_string())
+}
+
+/// A number (Open5e) or a list of `{ "value": n }` armor entries (5e-database)
+fn armor_class(value: &Value) -> Option<i32> {
+    let value = match value {
+        Value::Array(list) => list.first()?,
+        other => other,
+    };
+    value
+        .as_i64()
+        .or_else(|| value.get("value")?.as_i64())
+        .map(|ac| ac as i32)
+}
+
+fn ability_from_abbreviation(abbreviation: &str) -> Option<Ability> {
+    Ability::all()
+        .into_iter()
+        .find(|a| a.as_str().eq_ignore_ascii_case(abbreviation))
+}
+
+/// Open5e uses `dexterity_save` fields; 5e-database lists `saving-throw-dex` proficiencies.
+fn take_saving_throws(fields: &mut Map<String, Value>, block: &mut StatBlock) {
+    for (key, ability) in [
+        ("strength_save", Ability::Str),
+        ("dexterity_save", Ability::Dex),
+        ("constitution_save", Ability::Con),
+        ("intelligence_save", Ability::Int),
+        ("wisdom_save", Ability::Wis),
+        ("charisma_save", Ability::Cha),
+    ] {
+        if let Some(bonus) = take_with(fields, key, |v| v.as_i64()) {
+            block.saving_throws.insert(ability, bonus as i32);
+        }
+    }
+
+    let Some(Value::Array(proficiencies)) = fields.get_mut("proficiencies") else {
+        return;
+    };
+    proficiencies.retain(|proficiency| {
+        let save = proficiency
+            .get("proficiency")
+            .and_then(|p| p.get("index"))
+            .and_then(Value::as_str)
+            .and_then(|index| index.strip_prefix("saving-throw-"))
+            .and_then(ability_from_abbreviation)
+            .zip(proficiency.get("value").and_then(Value::as_i64));
+        match save {
+            Some((ability, bonus)) => {
+                block.saving_throws.insert(ability, bonus as i32);
+                false
+            }
+            None => true, // Skills and other proficiencies stay in `extra`
+        }
+    });
+    if proficiencies.is_empty() {
+        fields.remove("proficiencies");
+    }
+}
+
+/// "1/4" as printed, or 0.25 as 5e-database stores it
+fn challenge_rating(value: &Value) -> Option<String> {
+    if let Some(cr) = value.as_str() {
+        return Some(cr.trim().to_string());
+    }
+    let eighths = (value.as_f64()? * 8.0).round() as i64;
+    match eighths {
+        1 => Some("1/8".to_string()),
+        2 => Some("1/4".to_string()),
+        4 => Some("1/2".to_string()),
+        e if e % 8 == 0 => Some((e / 8).to_string()),
+        _ => None,
+    }
+}
+
+/// `{"walk": 30, "fly": 60}` or `{"walk": "30 ft."}` become "30 ft., fly 60 ft."
+fn speed(value: &Value) -> Option<String> {
+    let modes = match value {
+        Value::String(text) => return Some(text.clone()),
+        Value::Object(modes) => modes,
+        _ => return None,
+    };
+    let describe = |mode: &str, value: &Value| match value {
+        Value::Number(feet) => Some(format!("{} {} ft.", mode, feet)),
+        Value::String(text) => Some(format!("{} {}", mode, text)),
+        Value::Bool(true) => Some(format!("({})", mode)),
+        _ => None,
+    };
+    let walk = modes
+        .get("walk")
+        .and_then(|v| describe("", v))
+        .map(|text| text.trim().to_string());
+    let others = modes
+        .iter()
+        .filter(|(mode, _)| mode.as_str() != "walk")
+        .filter_map(|(mode, v)| describe(mode, v));
+    Some(
+        walk.into_iter()
+            .chain(others)
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
+
+/// A string (Open5e) or `{"darkvision": "60 ft.", "passive_perception": 9}` (5e-database)
+fn senses(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Object(senses) => Some(
+            senses
+                .iter()
+                .map(|(sense, v)| {
+                    let amount = v.as_str().map_or_else(|| v.to_string(), str::to_string);
+                    format!("{} {}", sense.replace('_', " "), amount)
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        _ => None,
+    }
+}
+
+/// Lowercase labels from "goblinoid, shapechanger" or `["goblinoid"]`
+fn labels(value: &Value) -> Option<Vec<String>> {
+    let raw: Vec<&str> = match value {
+        Value::String(text) => text.split(',').collect(),
+        Value::Array(list) => list.iter().map(Value::as_str).collect::<Option<_>>()?,
+        _ => return None,
+    };
+    Some(
+        raw.into_iter()
+            .map(|label| label.trim().to_lowercase())
+            .filter(|label| !label.is_empty())
+            .collect(),
+    )
+}
+
+/// Named entries with `desc` text; Open5e writes an empty string when there are none
+fn entries(value: &Value) -> Option<Vec<StatBlockEntry>> {
+    match value {
+        Value::String(text) if text.is_empty() => Some(Vec::new()),
+        Value::Array(list) => list
+            .iter()
+            .map(|entry| {
+                Some(StatBlockEntry {
+                    name: entry.get("name")?.as_str()?.to_string(),
+                    description: entry
+                        .get("desc")
+                        .or_else(|| entry.get("description"))
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                })
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Damage types from "fire; cold, poison" (Open5e) or `["fire", "cold"]` (5e-database).
+/// Conditional entries such as "bludgeoning ... from nonmagical attacks" can't be
+/// represented, so the whole field is left in `extra` when one is present.
+fn damage_types(value: &Value) -> Option<Vec<DamageType>> {
+    let segments: Vec<&str> = match value {
+        Value::String(text) => text.split(';').collect(),
+        Value::Array(list) => list.iter().map(Value::as_str).collect::<Option<_>>()?,
+        _ => return None,
+    };
+    let mut types = Vec::new();
+    for segment in segments {
+        for token in segment.split(',') {
+            let token = token.trim();
+            let token = token.strip_prefix("and ").unwrap_or(token);
+            if !token.is_empty() {
+                types.push(DamageType::parse(token)?);
+            }
+        }
+    }
+    Some(types)
+}
+
+fn leading_number(text: &str) -> Option<u32> {
+    text.trim_start()
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Open5e states "can take 3 legendary actions"; 5e-database only lists them, and
+/// nearly every SRD monster with a list takes three.
+fn legendary_action_count(fields: &Map<String, Value>) -> u32 {
+    let stated = fields
+        .get("legendary_desc")
+        .and_then(Value::as_str)
+        .and_then(|desc| {
+            let at = desc.find("take ")?;
+            leading_number(&desc[at + "take ".len()..])
+        });
+    let listed = fields
+        .get("legendary_actions")
+        .and_then(Value::as_array)
+        .is_some_and(|list| !list.is_empty());
+    stated.unwrap_or(if listed { 3 } else { 0 })
+}
+
+/// From a "Legendary Resistance (3/Day)" trait
+fn legendary_resistance_count(traits: &[StatBlockEntry]) -> u32 {
+    traits
+        .iter()
+        .find(|t| t.name.to_lowercase().starts_with("legendary resistance"))
+        .and_then(|t| leading_number(&t.name[t.name.find('(')? + 1..]))
+        .unwrap_or(0)
+}
+
+/// Parsed browser query: fuzzy name words plus `cr:`, `type:` and `tag:` filters,
+/// e.g. "drag cr:5-10 type:dragon tag:chromatic"
+#[derive(Debug, Default, PartialEq)]
+pub struct BestiaryQuery {
+    pub name: String,
+    pub cr_min: Option<f64>,
+    pub cr_max: Option<f64>,
+    pub creature_type: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl BestiaryQuery {
+    pub fn parse(input: &str) -> Self {
+        let mut query = BestiaryQuery::default();
+        let mut name_words = Vec::new();
+        for word in input.split_whitespace() {
+            let word = word.to_lowercase();
+            if let Some(range) = word.strip_prefix("cr:") {
+                // "5" is exact, "1/4-2" a range, "5-" or "-1" open-ended
+                let (min, max) = range.split_once('-').unwrap_or((range, range));
+                query.cr_min = challenge_rating_value(min);
+                query.cr_max = challenge_rating_value(max);
+            } else if let Some(creature_type) = word.strip_prefix("type:") {
+                query.creature_type = Some(creature_type.to_string());
+            } else if let Some(tag) = word.strip_prefix("tag:") {
+                query.tags.push(tag.to_string());
+            } else {
+                name_words.push(word);
+            }
+        }
+        query.name = name_words.join(" ");
+        query
+    }
+
+    fn has_filters(&self) -> bool {
+        self.cr_min.is_some()
+            || self.cr_max.is_some()
+            || self.creature_type.is_some()
+            || !self.tags.is_empty()
+    }
+
+    /// Type and tags match by prefix; size counts as a tag ("tag:large")
+    fn passes_filters(&self, monster: &CombatantTemplate) -> bool {
+        if !self.has_filters() {
+            return true;
+        }
+        let Some(block) = &monster.stat_block else {
+            return false;
+        };
+        let cr = challenge_rating_value(&block.challenge_rating);
+        let in_range = |bound: Option<f64>, ok: fn(f64, f64) -> bool| {
+            bound.is_none_or(|bound| cr.is_some_and(|cr| ok(cr, bound)))
+        };
+        let size = block.size.to_lowercase();
+        in_range(self.cr_min, |cr, min| cr >= min)
+            && in_range(self.cr_max, |cr, max| cr <= max)
+            && self
+                .creature_type
+                .as_ref()
+                .is_none_or(|t| block.creature_type.starts_with(t.as_str()))
+            && self.tags.iter().all(|tag| {
+                size.starts_with(tag.as_str())
+                    || block.tags.iter().any(|t| t.starts_with(tag.as_str()))
+            })
+    }
+}
+
+/// Scores `query` as an in-order subsequence of `candidate`, favouring consecutive
+/// letters and word starts; None when some letter is missing.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let query: Vec<char> = query
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+
+    let mut score = 0;
+    let mut matched = 0;
+    let mut previous: Option<usize> = None;
+    for (i, &c) in candidate.iter().enumerate() {
+        if matched == query.len() {
+            break;
+        }
+        if c != query[matched] {
+            continue;
+        }
+        score += 1;
+        if i > 0 && previous == Some(i - 1) {
+            score += 5;
+        }
+        if i == 0 || !candidate[i - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous = Some(i);
+        matched += 1;
+    }
+    // Shorter names win ties: "Goblin" before "Goblin Boss"
+    (matched == query.len()).then_some(score * 100 - candidate.len() as i32)
+}
+
+/// Indices of monsters matching `input`, best fuzzy match first
+pub fn search_bestiary(monsters: &[CombatantTemplate], input: &str) -> Vec<usize> {
+    let query = BestiaryQuery::parse(input);
+    let mut results: Vec<(i32, usize)> = monsters
+        .iter()
+        .enumerate()
+        .filter(|(_, monster)| query.passes_filters(monster))
+        .filter_map(|(i, monster)| Some((fuzzy_score(&query.name, &monster.name)?, i)))
+        .collect();
+    // Stable, so equal scores keep the bestiary's alphabetical order
+    results.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    results.into_iter().map(|(_, i)| i).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open5e_page_maps_core_fields_and_keeps_the_rest() {
+        let import = parse_monsters(
+            r#"{"count": 2, "results": [
+                {"slug": "young-red-dragon", "name": "Young Red Dragon", "type": "dragon",
+                 "armor_class": 18, "armor_desc": "natural armor", "hit_points": 178,
+                 "hit_dice": "17d10+85", "speed": {"walk": 40, "climb": 40, "fly": 80},
+                 "strength": 23, "dexterity": 10, "constitution": 21,
+                 "intelligence": 14, "wisdom": 11, "charisma": 19,
+                 "strength_save": null, "dexterity_save": 4, "wisdom_save": 4,
+                 "damage_resistances": "", "damage_immunities": "fire",
+                 "damage_vulnerabilities": "", "senses": "blindsight 30 ft., passive Perception 18",
+                 "challenge_rating": "10", "cr": 10.0,
+                 "actions": [{"name": "Bite", "desc": "+10 to hit, 2d10+6 piercing plus 1d6 fire."},
+                             {"name": "Fire Breath (Recharge 5-6)", "desc": "..."}],
+                 "reactions": "", "legendary_desc": "", "legendary_actions": [],
+                 "special_abilities": [{"name": "Legendary Resistance (2/Day)", "desc": "..."}]},
+                {"name": "Broken entry"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(import.skipped, 1);
+        let dragon = &import.monsters[0];
+        assert_eq!(
+            (
+                dragon.hp_max,
+                dragon.armor_class,
+                dragon.initiative_modifier
+            ),
+            (178, 18, 0)
+        );
+        assert_eq!(dragon.defenses.immunities, vec![DamageType::Fire]);
+        assert_eq!(dragon.legendary_actions, 0);
+        assert_eq!(dragon.legendary_resistances, 2);
+        assert_eq!(
+            dragon.resources[0].full_label(),
+            "Fire Breath (Recharge 5–6)"
+        );
+
+        let block = dragon.stat_block.as_ref().unwrap();
+        assert_eq!(block.challenge_rating, "10");
+        assert_eq!(block.xp, 5900);
+        assert_eq!(block.speed, "40 ft., climb 40 ft., fly 80 ft.");
+        assert_eq!(block.saving_throws.get(&Ability::Dex), Some(&4));
+        assert_eq!(block.actions[0].name, "Bite");
+        assert!(block.reactions.is_empty());
+        assert_eq!(dragon.hit_dice.as_deref(), Some("17d10+85"));
+        assert!(!block.extra.contains_key("hit_dice"));
+        assert_eq!(block.creature_type, "dragon");
+        assert_eq!(block.extra["armor_desc"], "natural armor");
+        assert!(!block.extra.contains_key("cr"));
+        assert!(!block.extra.contains_key("strength_save"));
+    }
+
+    #[test]
+    fn five_e_database_entries_map_lists_and_proficiencies() {
+        let import = parse_monsters(
+            r#"[{"index": "goblin", "name": "Goblin", "hit_points": 7,
+                 "hit_dice": "2d6", "hit_points_roll": "2d6",
+                 "armor_class": [{"type": "armor", "value": 15}],
+                 "speed": {"walk": "30 ft."}, "strength": 8, "dexterity": 14,
+                 "constitution": 10, "intelligence": 10, "wisdom": 8, "charisma": 8,
+                 "proficiencies": [
+                     {"value": 6, "proficiency": {"index": "skill-stealth"}},
+                     {"value": 4, "proficiency": {"index": "saving-throw-dex"}}],
+                 "damage_resistances": ["cold", "bludgeoning, piercing, and slashing from nonmagical attacks"],
+                 "damage_vulnerabilities": ["radiant"],
+                 "senses": {"darkvision": "60 ft.", "passive_perception": 9},
+                 "challenge_rating": 0.25, "xp": 50,
+                 "legendary_actions": [{"name": "Dash", "desc": "..."}]}]"#,
+        )
+        .unwrap();
+        let goblin = &import.monsters[0];
+        assert_eq!(
+            (
+                goblin.hp_max,
+                goblin.armor_class,
+                goblin.initiative_modifier
+            ),
+            (7, 15, 2)
+        );
+        assert_eq!(goblin.legendary_actions, 3);
+        assert_eq!(goblin.hit_dice.as_deref(), Some("2d6"));
+        assert_eq!(goblin.defenses.vulnerabilities, vec![DamageType::Radiant]);
+        // Conditional resistances aren't applied and stay available as text
+        assert!(goblin.defenses.resistances.is_empty());
+
+        let block = goblin.stat_block.as_ref().unwrap();
+        assert_eq!(block.challenge_rating, "1/4");
+        assert_eq!(block.xp, 50);
+        assert_eq!(block.speed, "30 ft.");
+        assert_eq!(block.senses, "darkvision 60 ft., passive perception 9");
+        assert_eq!(block.saving_throws.get(&Ability::Dex), Some(&4));
+        assert_eq!(block.extra["proficiencies"].as_array().unwrap().len(), 1);
+        assert!(block.extra.contains_key("damage_resistances"));
+        assert!(block.extra.contains_key("index"));
+
+        // Kept fields survive a trip through templates.json
+        let json = serde_json::to_string(goblin).unwrap();
+        let reloaded: CombatantTemplate = serde_json::from_str(&json).unwrap();
+        assert_eq!(reloaded.stat_block, goblin.stat_block);
+    }
+
+    #[test]
+    fn search_ranks_fuzzy_matches_and_applies_filters() {
+        let monsters = parse_monsters(
+            r#"[{"name": "Goblin", "hit_points": 7, "armor_class": 15, "size": "Small",
+                  "type": "humanoid", "subtype": "goblinoid", "challenge_rating": "1/4"},
+                 {"name": "Goblin Boss", "hit_points": 21, "armor_class": 17, "size": "Small",
+                  "type": "humanoid", "subtype": "goblinoid", "challenge_rating": "1"},
+                 {"name": "Young Green Dragon", "hit_points": 136, "armor_class": 18,
+                  "size": "Large", "type": "Dragon", "challenge_rating": "8"},
+                 {"name": "Ghoul", "hit_points": 22, "armor_class": 12, "type": "undead",
+                  "challenge_rating": "1"}]"#,
+        )
+        .unwrap()
+        .monsters;
+        let names = |input: &str| -> Vec<&str> {
+            search_bestiary(&monsters, input)
+                .into_iter()
+                .map(|i| monsters[i].name.as_str())
+                .collect()
+        };
+
+        assert_eq!(names("gob"), vec!["Goblin", "Goblin Boss"]);
+        assert_eq!(names("gbs"), vec!["Goblin Boss"]);
+        assert_eq!(names("ygd"), vec!["Young Green Dragon"]);
+        assert_eq!(names("g")[..2], ["Ghoul", "Goblin"]);
+        assert_eq!(names("cr:1"), vec!["Goblin Boss", "Ghoul"]);
+        assert_eq!(names("cr:1/4-1 type:hum"), vec!["Goblin", "Goblin Boss"]);
+        assert_eq!(names("cr:2-"), vec!["Young Green Dragon"]);
+        assert_eq!(names("tag:large"), vec!["Young Green Dragon"]);
+        assert_eq!(names("boss tag:goblinoid"), vec!["Goblin Boss"]);
+        assert!(names("tag:fiend").is_empty());
+    }
+
+    #[test]
+    fn unreadable_files_report_an_error() {
+        assert!(parse_monsters("not json").is_err());
+        assert!(parse_monsters(r#"{"count": 0}"#).is_err());
+        assert_eq!(parse_monsters("[]").unwrap().monsters.len(), 0);
+    }
+}
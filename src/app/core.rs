@@ -1,7 +1,13 @@
+use super::bestiary::{bestiary_dir, load_bestiary};
+use super::history::UndoHistory;
 use super::persistence::*;
 use super::state::*;
 use crate::combat::CombatEncounter;
-use crate::models::{CombatantTemplate, LogEntry};
+use crate::dice::DiceRoller;
+use crate::models::difficulty;
+use crate::models::{Combatant, CombatantTemplate, DifficultyReport, LogEntry};
+use std::collections::VecDeque;
+use std::path::PathBuf;
 
 pub struct App {
     pub encounter: CombatEncounter,
@@ -9,12 +15,35 @@ pub struct App {
     pub should_quit: bool,
     pub message: Option<String>,
     pub templates: Vec<CombatantTemplate>,
+    /// Monsters imported from `bestiary/`, reloaded each time the browser opens
+    pub bestiary: Vec<CombatantTemplate>,
     pub log: Vec<LogEntry>,
+    pub history: UndoHistory,
+    pub dice: DiceRoller,
+    /// Breakdown of a prompt's roll, logged ahead of the action it feeds
+    pub pending_roll: Option<String>,
+    /// Prompts waiting for their turn, e.g. concentration checks after area damage
+    pub pending_prompts: VecDeque<InputMode>,
+    /// Details panel shown in place of the log
+    pub show_details: bool,
+    /// Combatant the details panel stays on instead of following the turn
+    pub pinned: Option<String>,
+    /// NPCs with hit dice roll their HP instead of taking the average
+    pub roll_hit_points: bool,
+    /// Party levels from `party.json`, used for encounter difficulty
+    pub party: Party,
+    /// Folder holding `party.json`, `templates.json` and the save directories
+    pub data_dir: PathBuf,
 }
 
 impl App {
     pub fn new() -> Self {
-        let (templates, message) = match load_templates() {
+        Self::with_data_dir(PathBuf::new())
+    }
+
+    /// Reads and writes the party, templates and saves under `data_dir`
+    pub fn with_data_dir(data_dir: PathBuf) -> Self {
+        let (templates, message) = match load_templates(&data_dir) {
             Ok(t) => (t, None),
             Err(e) => {
                 log::error!("Template load error: {}", e);
@@ -22,13 +51,28 @@ impl App {
             }
         };
 
+        let party = load_party(&data_dir).unwrap_or_else(|e| {
+            log::error!("Party load error: {}", e);
+            Party::default()
+        });
+
         Self {
             encounter: CombatEncounter::new(),
             input_mode: InputMode::Normal,
             should_quit: false,
             message,
             templates,
+            bestiary: Vec::new(),
             log: Vec::new(),
+            history: UndoHistory::default(),
+            dice: dice_roller_from_env(),
+            pending_roll: None,
+            pending_prompts: VecDeque::new(),
+            show_details: false,
+            pinned: None,
+            roll_hit_points: false,
+            party,
+            data_dir,
         }
     }
 
@@ -49,8 +93,43 @@ impl App {
         self.clear_message();
     }
 
+    pub fn start_adding_turn_entry(&mut self) {
+        self.input_mode = InputMode::AddingTurnEntry(AddTurnEntryState::default());
+        self.clear_message();
+    }
+
+    fn has_creatures(&self) -> bool {
+        self.encounter.combatants.iter().any(|c| c.is_creature())
+    }
+
+    /// Indices the current selection prompt can target. Lair, hazard and event
+    /// entries are only offered where they make sense (removal, tie swaps).
+    pub fn selectable_indices(&self) -> Vec<usize> {
+        let include_entries = matches!(
+            self.input_mode,
+            InputMode::Removing(_) | InputMode::ResolvingTie(_) | InputMode::ResumingDelayed(..)
+        );
+        self.encounter
+            .combatants
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| include_entries || c.is_creature())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Resolves a stored selection to a selectable index, falling back to the first one.
+    pub fn effective_selection(&self, selected_index: usize) -> Option<usize> {
+        let selectable = self.selectable_indices();
+        if selectable.contains(&selected_index) {
+            Some(selected_index)
+        } else {
+            selectable.first().copied()
+        }
+    }
+
     pub fn start_dealing_damage(&mut self) {
-        if self.encounter.combatants.is_empty() {
+        if !self.has_creatures() {
             self.set_message("No combatants to damage!".to_string());
             return;
         }
@@ -59,7 +138,7 @@ impl App {
     }
 
     pub fn start_healing(&mut self) {
-        if self.encounter.combatants.is_empty() {
+        if !self.has_creatures() {
             self.set_message("No combatants to heal!".to_string());
             return;
         }
@@ -68,7 +147,7 @@ impl App {
     }
 
     pub fn start_adding_status(&mut self) {
-        if self.encounter.combatants.is_empty() {
+        if !self.has_creatures() {
             self.set_message("No combatants to add status to!".to_string());
             return;
         }
@@ -100,7 +179,7 @@ impl App {
     }
 
     pub fn start_concentration_target(&mut self) {
-        if self.encounter.combatants.is_empty() {
+        if !self.has_creatures() {
             self.set_message("No combatants to set concentration on!".to_string());
             return;
         }
@@ -124,7 +203,7 @@ impl App {
     }
 
     pub fn start_granting_temp_hp(&mut self) {
-        if self.encounter.combatants.is_empty() {
+        if !self.has_creatures() {
             self.set_message("No combatants to grant temp HP!".to_string());
             return;
         }
@@ -132,6 +211,61 @@ impl App {
         self.clear_message();
     }
 
+    pub fn start_setting_exhaustion(&mut self) {
+        if !self.has_creatures() {
+            self.set_message("No combatants to exhaust!".to_string());
+            return;
+        }
+        self.input_mode = InputMode::SettingExhaustion(SelectionState::default());
+        self.clear_message();
+    }
+
+    /// Opens a rest for the encounter's PCs, or the roster when there are none.
+    pub fn start_rest(&mut self, long: bool) {
+        let on_roster = self.rest_targets(false).is_empty();
+        self.start_rest_on(long, on_roster);
+    }
+
+    pub fn start_rest_on(&mut self, long: bool, on_roster: bool) {
+        let targets = self.rest_targets(on_roster);
+        if targets.is_empty() {
+            self.set_message("No PCs in the encounter or on the party roster to rest".to_string());
+            return;
+        }
+        self.input_mode = InputMode::Resting(RestState {
+            long,
+            on_roster,
+            selected_index: 0,
+            hit_dice: vec![0; targets.len()],
+        });
+        self.clear_message();
+    }
+
+    /// PCs a rest applies to: the roster members, or the PCs in the encounter
+    /// (delayed ones included)
+    pub fn rest_targets(&self, on_roster: bool) -> Vec<Combatant> {
+        if on_roster {
+            return self
+                .party
+                .members
+                .iter()
+                .map(PartyMember::to_combatant)
+                .collect();
+        }
+        self.encounter
+            .combatants
+            .iter()
+            .chain(&self.encounter.delayed)
+            .filter(|c| c.is_player && c.is_creature())
+            .cloned()
+            .collect()
+    }
+
+    pub fn start_viewing_roster(&mut self) {
+        self.input_mode = InputMode::PartyRoster(0);
+        self.clear_message();
+    }
+
     pub fn start_selecting_template(&mut self) {
         if self.templates.is_empty() {
             self.set_message("No templates available".to_string());
@@ -141,8 +275,54 @@ impl App {
         self.clear_message();
     }
 
+    pub fn start_browsing_bestiary(&mut self) {
+        let import = match load_bestiary(&self.data_dir) {
+            Ok(import) => import,
+            Err(e) => {
+                self.set_message(e);
+                self.input_mode = InputMode::Normal;
+                return;
+            }
+        };
+        self.bestiary = import.monsters;
+        if self.bestiary.is_empty() {
+            self.set_message(format!(
+                "No monsters found in {}/ ({} skipped)",
+                bestiary_dir(&self.data_dir),
+                import.skipped
+            ));
+            self.input_mode = InputMode::Normal;
+            return;
+        }
+        let mut message = format!("Loaded {} monsters", self.bestiary.len());
+        if import.skipped > 0 {
+            message.push_str(&format!(" ({} skipped, see error_log.txt)", import.skipped));
+        }
+        self.set_message(message);
+        self.input_mode = InputMode::BrowsingBestiary(BestiaryState::default());
+    }
+
+    /// Asks how many copies of an NPC template to add; PCs go through the normal prompt.
+    pub fn start_adding_copies(&mut self, template_index: usize) {
+        let Some(tpl) = self.templates.get(template_index) else {
+            self.set_message("No matching template".to_string());
+            self.input_mode = InputMode::Normal;
+            return;
+        };
+        if tpl.is_player {
+            self.set_message(format!("{} is a PC and is added once", tpl.name));
+            return;
+        }
+        self.set_message(format!("How many {} to add?", tpl.name));
+        self.input_mode = InputMode::AddingCopies(CopiesState {
+            template_index,
+            count: "2".to_string(),
+            group_initiative: false,
+        });
+    }
+
     pub fn start_saving_template(&mut self) {
-        if self.encounter.combatants.is_empty() {
+        if !self.has_creatures() {
             self.set_message("No combatants to save as template".to_string());
             return;
         }
@@ -150,6 +330,290 @@ impl App {
         self.clear_message();
     }
 
+    pub fn start_resolving_tie(&mut self) {
+        let first_tie =
+            (0..self.encounter.combatants.len()).find(|&i| self.encounter.is_unresolved_tie(i));
+        match first_tie {
+            Some(index) => {
+                self.input_mode = InputMode::ResolvingTie(SelectionState {
+                    selected_index: index,
+                    input: String::new(),
+                });
+                self.clear_message();
+            }
+            None => self.set_message("No unresolved initiative ties".to_string()),
+        }
+    }
+
+    pub fn start_using_reaction(&mut self) {
+        if !self.has_creatures() {
+            self.set_message("No combatants to use a reaction!".to_string());
+            return;
+        }
+        self.input_mode = InputMode::UsingReaction(SelectionState::default());
+        self.clear_message();
+    }
+
+    pub fn start_setting_legendary(&mut self) {
+        if !self.has_creatures() {
+            self.set_message("No combatants to set legendary pools on!".to_string());
+            return;
+        }
+        self.input_mode = InputMode::SelectingLegendaryTarget(SelectionState::default());
+        self.clear_message();
+    }
+
+    pub fn start_spending_legendary_action(&mut self) {
+        match self.first_index_where(|c| c.legendary_actions.is_some()) {
+            Some(index) => {
+                self.input_mode = InputMode::SpendingLegendaryAction(SelectionState {
+                    selected_index: index,
+                    input: String::new(),
+                });
+                self.clear_message();
+            }
+            None => self.set_message("No combatants have legendary actions!".to_string()),
+        }
+    }
+
+    pub fn start_managing_resources(&mut self) {
+        if !self.has_creatures() {
+            self.set_message("No combatants to track resources for!".to_string());
+            return;
+        }
+        self.input_mode = InputMode::SelectingResourceTarget(SelectionState {
+            selected_index: self.encounter.current_turn_index,
+            input: String::new(),
+        });
+        self.clear_message();
+    }
+
+    pub fn start_managing_ongoing_effects(&mut self) {
+        if !self.has_creatures() {
+            self.set_message("No combatants to add ongoing effects to!".to_string());
+            return;
+        }
+        self.input_mode = InputMode::SelectingOngoingTarget(SelectionState {
+            selected_index: self.encounter.current_turn_index,
+            input: String::new(),
+        });
+        self.clear_message();
+    }
+
+    pub fn start_using_legendary_resistance(&mut self) {
+        match self.first_index_where(|c| c.legendary_resistances.is_some()) {
+            Some(index) => {
+                self.input_mode = InputMode::UsingLegendaryResistance(SelectionState {
+                    selected_index: index,
+                    input: String::new(),
+                });
+                self.clear_message();
+            }
+            None => self.set_message("No combatants have legendary resistances!".to_string()),
+        }
+    }
+
+    pub fn start_attack(&mut self) {
+        let current = self.encounter.current_turn_index;
+        let attacker = match self.encounter.combatants.get(current) {
+            Some(c) if c.is_creature() => Some(current),
+            _ => self.first_index_where(|c| c.is_creature()),
+        };
+        match attacker {
+            Some(attacker) => {
+                self.input_mode = InputMode::Attacking(AttackState {
+                    attacker,
+                    target: attacker,
+                    ..Default::default()
+                });
+                self.clear_message();
+            }
+            None => self.set_message("No combatants to attack with!".to_string()),
+        }
+    }
+
+    pub fn start_area_damage(&mut self) {
+        match self.first_index_where(|c| c.is_creature()) {
+            Some(index) => {
+                self.input_mode = InputMode::DealingAreaDamage(AreaDamageState {
+                    cursor: index,
+                    ..Default::default()
+                });
+                self.clear_message();
+            }
+            None => self.set_message("No combatants to damage!".to_string()),
+        }
+    }
+
+    pub fn start_editing_defenses(&mut self) {
+        if !self.has_creatures() {
+            self.set_message("No combatants to set damage defenses on!".to_string());
+            return;
+        }
+        self.input_mode = InputMode::SelectingDefensesTarget(SelectionState::default());
+        self.clear_message();
+    }
+
+    pub fn start_editing_notes(&mut self) {
+        if !self.has_creatures() {
+            self.set_message("No combatants to add notes to!".to_string());
+            return;
+        }
+        self.input_mode = InputMode::SelectingNotesTarget(SelectionState::default());
+        self.clear_message();
+    }
+
+    pub fn choose_notes_target(&mut self, index: usize) {
+        let input = self
+            .encounter
+            .combatants
+            .get(index)
+            .map(|c| c.notes.clone())
+            .unwrap_or_default();
+        self.input_mode = InputMode::EditingNotes(EditNotesState {
+            combatant_index: index,
+            input,
+        });
+    }
+
+    pub fn toggle_details(&mut self) {
+        self.show_details = !self.show_details;
+    }
+
+    /// Shows the difficulty breakdown with the party levels ready to edit.
+    pub fn start_viewing_difficulty(&mut self) {
+        let levels: Vec<String> = self.party.levels.iter().map(u32::to_string).collect();
+        self.input_mode = InputMode::EncounterDifficulty(levels.join(" "));
+        self.clear_message();
+    }
+
+    /// XP of the encounter's NPCs, delayed ones included; 0 for those without a CR
+    pub fn encounter_monster_xp(&self) -> Vec<u32> {
+        self.encounter
+            .combatants
+            .iter()
+            .chain(&self.encounter.delayed)
+            .filter(|c| c.is_creature() && !c.is_player)
+            .map(|c| c.stat_block.as_ref().map_or(0, |b| b.xp))
+            .collect()
+    }
+
+    /// Difficulty of the encounter for the configured party
+    pub fn encounter_difficulty(&self) -> DifficultyReport {
+        difficulty::assess(&self.encounter_monster_xp(), &self.party.levels)
+    }
+
+    pub fn library_difficulty(&self, template: &EncounterTemplate) -> DifficultyReport {
+        difficulty::assess(&template.monster_xp(), &self.party.levels)
+    }
+
+    /// Switches NPCs with hit dice between average and rolled HP for later adds and loads.
+    pub fn toggle_hit_point_mode(&mut self) {
+        self.roll_hit_points = !self.roll_hit_points;
+        self.set_message(if self.roll_hit_points {
+            "NPC hit points: rolled from hit dice".to_string()
+        } else {
+            "NPC hit points: average".to_string()
+        });
+    }
+
+    /// Unpins if something is pinned, otherwise asks which creature to pin.
+    pub fn start_pinning(&mut self) {
+        if let Some(name) = self.pinned.take() {
+            self.set_message(format!("Unpinned {}; details follow the turn", name));
+            return;
+        }
+        if !self.has_creatures() {
+            self.set_message("No combatants to pin!".to_string());
+            return;
+        }
+        self.input_mode = InputMode::PinningCombatant(SelectionState {
+            selected_index: self.encounter.current_turn_index,
+            input: String::new(),
+        });
+        self.clear_message();
+    }
+
+    pub fn pin_combatant(&mut self, index: usize) {
+        self.input_mode = InputMode::Normal;
+        if let Some(c) = self.encounter.combatants.get(index) {
+            self.show_details = true;
+            self.pinned = Some(c.name.clone());
+            self.set_message(format!("Pinned {} to the details panel", c.name));
+        }
+    }
+
+    /// Combatant the details panel shows: the pinned one while it is still in
+    /// the order, otherwise whoever's turn it is.
+    pub fn details_index(&self) -> Option<usize> {
+        let combatants = &self.encounter.combatants;
+        self.pinned
+            .as_ref()
+            .and_then(|name| combatants.iter().position(|c| &c.name == name))
+            .or_else(|| {
+                (self.encounter.current_turn_index < combatants.len())
+                    .then_some(self.encounter.current_turn_index)
+            })
+    }
+
+    pub fn start_readying_action(&mut self) {
+        let index = self.encounter.current_turn_index;
+        match self.encounter.combatants.get(index) {
+            Some(c) if c.is_creature() => {
+                self.input_mode = InputMode::ReadyingAction(String::new());
+                self.clear_message();
+            }
+            _ => self.set_message("Only a creature can ready an action".to_string()),
+        }
+    }
+
+    pub fn start_triggering_readied(&mut self) {
+        match self.first_index_where(|c| c.readied.is_some()) {
+            Some(index) => {
+                self.input_mode = InputMode::TriggeringReadied(SelectionState {
+                    selected_index: index,
+                    input: String::new(),
+                });
+                self.clear_message();
+            }
+            None => self.set_message("No combatants have a readied action!".to_string()),
+        }
+    }
+
+    /// Skips the delayed list when only one creature is waiting, and re-enters
+    /// it straight away when the order is otherwise empty.
+    pub fn start_resuming_delayed(&mut self) {
+        match self.encounter.delayed.len() {
+            0 => self.set_message("No creatures are delaying".to_string()),
+            1 => self.choose_delayed(0),
+            _ => {
+                self.input_mode = InputMode::ChoosingDelayed(0);
+                self.clear_message();
+            }
+        }
+    }
+
+    pub fn choose_delayed(&mut self, delayed_index: usize) {
+        if self.encounter.combatants.is_empty() {
+            if let Err(e) = self.complete_resume_delayed(delayed_index, None) {
+                self.set_message(e);
+            }
+            return;
+        }
+        self.input_mode = InputMode::ResumingDelayed(
+            delayed_index,
+            SelectionState {
+                selected_index: self.encounter.current_turn_index,
+                input: String::new(),
+            },
+        );
+        self.clear_message();
+    }
+
+    fn first_index_where(&self, predicate: impl Fn(&Combatant) -> bool) -> Option<usize> {
+        self.encounter.combatants.iter().position(predicate)
+    }
+
     pub fn start_saving_encounter(&mut self) {
         self.input_mode = InputMode::SavingEncounter(SaveEncounterState::default());
     }
@@ -173,9 +637,28 @@ impl App {
     pub fn cancel_input(&mut self) {
         self.input_mode = InputMode::Normal;
         self.clear_message();
+        self.finish_prompt();
+    }
+
+    /// Moves on to the next queued prompt once the current one is done.
+    pub fn finish_prompt(&mut self) {
+        if self.input_mode == InputMode::Normal {
+            if let Some(next) = self.pending_prompts.pop_front() {
+                self.input_mode = next;
+            }
+        }
     }
 }
 
+/// Uses `DND_TRACKER_SEED` for reproducible rolls when set, entropy otherwise.
+fn dice_roller_from_env() -> DiceRoller {
+    std::env::var("DND_TRACKER_SEED")
+        .ok()
+        .and_then(|seed| seed.parse::<u64>().ok())
+        .map(DiceRoller::seeded)
+        .unwrap_or_default()
+}
+
 impl Default for App {
     fn default() -> Self {
         Self::new()
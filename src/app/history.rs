@@ -0,0 +1,112 @@
+use crate::combat::CombatEncounter;
+use crate::models::LogEntry;
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of undo steps kept in memory and in save files
+pub const MAX_HISTORY: usize = 50;
+
+/// Snapshot of the mutable encounter state taken before an action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistorySnapshot {
+    pub label: String,
+    pub encounter: CombatEncounter,
+    pub log: Vec<LogEntry>,
+}
+
+/// Bounded undo/redo stacks over encounter and log state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UndoHistory {
+    pub undo_stack: Vec<HistorySnapshot>,
+    pub redo_stack: Vec<HistorySnapshot>,
+}
+
+impl UndoHistory {
+    /// Records the state before a new action. Clears the redo stack.
+    pub fn record(&mut self, label: &str, encounter: &CombatEncounter, log: &[LogEntry]) {
+        self.undo_stack.push(HistorySnapshot {
+            label: label.to_string(),
+            encounter: encounter.clone(),
+            log: log.to_vec(),
+        });
+        if self.undo_stack.len() > MAX_HISTORY {
+            let overflow = self.undo_stack.len() - MAX_HISTORY;
+            self.undo_stack.drain(0..overflow);
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Pops the last snapshot, pushing the current state onto the redo stack.
+    pub fn undo(
+        &mut self,
+        encounter: &CombatEncounter,
+        log: &[LogEntry],
+    ) -> Option<HistorySnapshot> {
+        let snapshot = self.undo_stack.pop()?;
+        self.redo_stack.push(HistorySnapshot {
+            label: snapshot.label.clone(),
+            encounter: encounter.clone(),
+            log: log.to_vec(),
+        });
+        Some(snapshot)
+    }
+
+    /// Pops the last undone snapshot, pushing the current state back onto the undo stack.
+    pub fn redo(
+        &mut self,
+        encounter: &CombatEncounter,
+        log: &[LogEntry],
+    ) -> Option<HistorySnapshot> {
+        let snapshot = self.redo_stack.pop()?;
+        self.undo_stack.push(HistorySnapshot {
+            label: snapshot.label.clone(),
+            encounter: encounter.clone(),
+            log: log.to_vec(),
+        });
+        Some(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Combatant;
+
+    fn encounter_with(name: &str) -> CombatEncounter {
+        let mut enc = CombatEncounter::new();
+        enc.add_combatant(Combatant::new(name.to_string(), 10, 10, 10, false));
+        enc
+    }
+
+    #[test]
+    fn undo_then_redo_round_trips() {
+        let mut history = UndoHistory::default();
+        let before = encounter_with("A");
+        let after = encounter_with("B");
+        history.record("change", &before, &[]);
+
+        let undone = history.undo(&after, &[]).unwrap();
+        assert_eq!(undone.encounter.combatants[0].name, "A");
+        assert_eq!(history.redo_stack.len(), 1);
+
+        let redone = history.redo(&undone.encounter, &[]).unwrap();
+        assert_eq!(redone.encounter.combatants[0].name, "B");
+        assert_eq!(history.undo_stack.len(), 1);
+        assert!(history.redo_stack.is_empty());
+    }
+
+    #[test]
+    fn record_clears_redo_and_is_bounded() {
+        let mut history = UndoHistory::default();
+        let enc = encounter_with("A");
+        history.record("first", &enc, &[]);
+        history.undo(&enc, &[]);
+        assert_eq!(history.redo_stack.len(), 1);
+
+        for i in 0..(MAX_HISTORY + 5) {
+            history.record(&format!("step {}", i), &enc, &[]);
+        }
+        assert!(history.redo_stack.is_empty());
+        assert_eq!(history.undo_stack.len(), MAX_HISTORY);
+        assert_eq!(history.undo_stack[0].label, "step 5");
+    }
+}
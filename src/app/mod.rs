@@ -1,7 +1,11 @@
 // Module declarations
 pub mod actions;
+pub mod bestiary;
 pub mod core;
+pub mod history;
 pub mod persistence;
+pub mod rest;
+pub mod session;
 pub mod state;
 
 // Re-export main types
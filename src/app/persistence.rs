@@ -1,5 +1,10 @@
+use super::history::UndoHistory;
+use super::session::SessionRecord;
 use crate::combat::CombatEncounter;
-use crate::models::{CombatantTemplate, LogEntry};
+use crate::models::{
+    Combatant, CombatantKind, CombatantTemplate, DamageDefenses, LogEntry, Resource, StatBlock,
+    StatusEffect,
+};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
@@ -12,6 +17,8 @@ pub struct SavedEncounter {
     pub log: Vec<LogEntry>,
     pub saved_at: u64,
     pub version: String,
+    #[serde(default)]
+    pub history: UndoHistory,
 }
 
 /// Lightweight combatant for encounter library (no runtime state or initiative)
@@ -21,6 +28,43 @@ pub struct LibraryCombatant {
     pub hp_max: i32,
     pub armor_class: i32,
     pub is_player: bool,
+    #[serde(default)]
+    pub initiative_modifier: i32,
+    #[serde(default)]
+    pub legendary_actions: u32,
+    #[serde(default)]
+    pub legendary_resistances: u32,
+    #[serde(default)]
+    pub kind: CombatantKind,
+    #[serde(default)]
+    pub reminder: Option<String>,
+    /// Initiative count for lair/hazard/event entries, which are never rolled
+    #[serde(default)]
+    pub fixed_initiative: Option<i32>,
+    #[serde(default)]
+    pub defenses: DamageDefenses,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stat_block: Option<StatBlock>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hit_dice: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resources: Vec<Resource>,
+    /// Identical copies added as "Name 1".."Name N" when the encounter loads
+    #[serde(default = "default_count", skip_serializing_if = "is_single")]
+    pub count: u32,
+    /// Copies share one initiative roll instead of rolling separately
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub group_initiative: bool,
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+fn is_single(count: &u32) -> bool {
+    *count <= 1
 }
 
 /// Encounter template for library with metadata
@@ -33,19 +77,201 @@ pub struct EncounterTemplate {
     pub created_at: u64,
 }
 
+impl EncounterTemplate {
+    /// XP of every monster once counts are expanded; 0 for those without a CR
+    pub fn monster_xp(&self) -> Vec<u32> {
+        self.combatants
+            .iter()
+            .filter(|c| c.kind.is_creature() && !c.is_player)
+            .flat_map(|c| {
+                let xp = c.stat_block.as_ref().map_or(0, |b| b.xp);
+                std::iter::repeat_n(xp, c.count.max(1) as usize)
+            })
+            .collect()
+    }
+}
+
+/// Character levels of the party, used to rate encounter difficulty, and the
+/// roster of PCs carried from one encounter to the next
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Party {
+    #[serde(default)]
+    pub levels: Vec<u32>,
+    #[serde(default)]
+    pub members: Vec<PartyMember>,
+}
+
+impl Party {
+    /// Copies the current state of PCs in `combatants` onto their roster entries
+    /// and returns how many were updated. With `add_new`, PCs not on the roster
+    /// yet are added to it.
+    pub fn record<'a>(
+        &mut self,
+        combatants: impl IntoIterator<Item = &'a Combatant>,
+        add_new: bool,
+    ) -> usize {
+        let mut recorded = 0;
+        for combatant in combatants {
+            if !combatant.is_player || !combatant.is_creature() {
+                continue;
+            }
+            let member = PartyMember::from_combatant(combatant);
+            match self.members.iter_mut().find(|m| m.name == combatant.name) {
+                Some(existing) => *existing = member,
+                None if add_new => self.members.push(member),
+                None => continue,
+            }
+            recorded += 1;
+        }
+        recorded
+    }
+}
+
+/// A PC on the party roster with the state that outlasts a single encounter
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PartyMember {
+    pub name: String,
+    pub hp_max: i32,
+    pub hp_current: i32,
+    #[serde(default)]
+    pub temp_hp: i32,
+    pub armor_class: i32,
+    #[serde(default)]
+    pub initiative_modifier: i32,
+    /// Hit dice pool such as "5d10"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hit_dice: Option<String>,
+    #[serde(default)]
+    pub hit_dice_spent: u32,
+    #[serde(default)]
+    pub exhaustion: u8,
+    /// Slots and features keep what was spent until the party rests
+    #[serde(default)]
+    pub resources: Vec<Resource>,
+    /// Conditions without a duration; timed ones end with the encounter
+    #[serde(default)]
+    pub lasting_effects: Vec<StatusEffect>,
+    #[serde(default)]
+    pub defenses: DamageDefenses,
+    #[serde(default)]
+    pub notes: String,
+}
+
+impl PartyMember {
+    pub fn from_combatant(combatant: &Combatant) -> Self {
+        Self {
+            name: combatant.name.clone(),
+            hp_max: combatant.hp_max,
+            hp_current: combatant.hp_current,
+            temp_hp: combatant.temp_hp,
+            armor_class: combatant.armor_class,
+            initiative_modifier: combatant.initiative_modifier,
+            hit_dice: combatant.hit_dice_pool.clone(),
+            hit_dice_spent: combatant.hit_dice_spent,
+            exhaustion: combatant.exhaustion,
+            resources: combatant.resources.clone(),
+            lasting_effects: combatant
+                .status_effects
+                .iter()
+                .filter(|effect| effect.duration == 0)
+                .cloned()
+                .collect(),
+            defenses: combatant.defenses.clone(),
+            notes: combatant.notes.clone(),
+        }
+    }
+
+    /// The member as a PC outside initiative, e.g. to take a rest on the roster
+    pub fn to_combatant(&self) -> Combatant {
+        let mut combatant =
+            Combatant::new(self.name.clone(), 0, self.hp_max, self.armor_class, true);
+        combatant.initiative_modifier = self.initiative_modifier;
+        combatant.defenses = self.defenses.clone();
+        self.apply_to(&mut combatant);
+        combatant
+    }
+
+    /// Carries the member's lasting state onto a freshly built combatant.
+    pub fn apply_to(&self, combatant: &mut Combatant) {
+        combatant.hp_current = self.hp_current.min(combatant.hp_max);
+        combatant.temp_hp = self.temp_hp;
+        combatant.hit_dice_pool = self.hit_dice.clone();
+        combatant.hit_dice_spent = self.hit_dice_spent;
+        combatant.exhaustion = self.exhaustion;
+        combatant.resources = self.resources.clone();
+        combatant.status_effects = self.lasting_effects.clone();
+        combatant.notes = self.notes.clone();
+        if combatant.hp_current <= 0 {
+            combatant.ensure_death_saves();
+        }
+    }
+}
+
+/// Path of a file or directory inside the data directory, which holds the party,
+/// templates and save directories (the working directory for the app itself)
+pub fn data_path(data_dir: &Path, name: &str) -> String {
+    data_dir.join(name).to_string_lossy().into_owned()
+}
+
+/// Creates a save directory (and the data directory above it) if it's missing.
+fn ensure_dir(dir: &str, what: &str) -> Result<(), String> {
+    if !Path::new(dir).exists() {
+        fs::create_dir_all(dir).map_err(|e| {
+            log::error!("Failed to create {} directory: {}", what, e);
+            format!("Could not create {} directory: {}", what, e)
+        })?;
+    }
+    Ok(())
+}
+
+pub fn party_path(data_dir: &Path) -> String {
+    data_path(data_dir, "party.json")
+}
+
+pub fn load_party(data_dir: &Path) -> Result<Party, String> {
+    let path = party_path(data_dir);
+    if !Path::new(&path).exists() {
+        return Ok(Party::default());
+    }
+
+    let content = fs::read_to_string(&path).map_err(|e| {
+        log::error!("Failed to read party from {}: {}", path, e);
+        format!("Could not read party file: {}", e)
+    })?;
+
+    serde_json::from_str(&content).map_err(|e| {
+        log::error!("Failed to parse party JSON from {}: {}", path, e);
+        format!("Party file is corrupted: {}", e)
+    })
+}
+
+pub fn save_party(data_dir: &Path, party: &Party) -> Result<(), String> {
+    ensure_dir(&data_dir.to_string_lossy(), "data")?;
+    let path = party_path(data_dir);
+    let json = serde_json::to_string_pretty(party).map_err(|e| {
+        log::error!("Failed to serialize party to JSON: {}", e);
+        e.to_string()
+    })?;
+
+    fs::write(&path, json).map_err(|e| {
+        log::error!("Failed to write party to {}: {}", path, e);
+        e.to_string()
+    })
+}
+
 // Template file operations
 
-pub fn templates_path() -> &'static str {
-    "templates.json"
+pub fn templates_path(data_dir: &Path) -> String {
+    data_path(data_dir, "templates.json")
 }
 
-pub fn load_templates() -> Result<Vec<CombatantTemplate>, String> {
-    let path = templates_path();
-    if !Path::new(path).exists() {
+pub fn load_templates(data_dir: &Path) -> Result<Vec<CombatantTemplate>, String> {
+    let path = templates_path(data_dir);
+    if !Path::new(&path).exists() {
         return Ok(Vec::new());
     }
 
-    let content = fs::read_to_string(path).map_err(|e| {
+    let content = fs::read_to_string(&path).map_err(|e| {
         log::error!("Failed to read templates from {}: {}", path, e);
         format!("Could not read templates file: {}", e)
     })?;
@@ -56,14 +282,15 @@ pub fn load_templates() -> Result<Vec<CombatantTemplate>, String> {
     })
 }
 
-pub fn save_templates(templates: &[CombatantTemplate]) -> Result<(), String> {
-    let path = templates_path();
+pub fn save_templates(data_dir: &Path, templates: &[CombatantTemplate]) -> Result<(), String> {
+    ensure_dir(&data_dir.to_string_lossy(), "data")?;
+    let path = templates_path(data_dir);
     let json = serde_json::to_string_pretty(templates).map_err(|e| {
         log::error!("Failed to serialize templates to JSON: {}", e);
         e.to_string()
     })?;
 
-    fs::write(path, json).map_err(|e| {
+    fs::write(&path, json).map_err(|e| {
         log::error!("Failed to write templates to {}: {}", path, e);
         e.to_string()
     })
@@ -71,27 +298,22 @@ pub fn save_templates(templates: &[CombatantTemplate]) -> Result<(), String> {
 
 // Encounter save/load functions
 
-pub fn encounters_dir() -> &'static str {
-    "encounters"
+pub fn encounters_dir(data_dir: &Path) -> String {
+    data_path(data_dir, "encounters")
 }
 
-pub fn ensure_encounters_dir() -> Result<(), String> {
-    let dir = encounters_dir();
-    if !Path::new(dir).exists() {
-        fs::create_dir(dir).map_err(|e| {
-            log::error!("Failed to create encounters directory: {}", e);
-            format!("Could not create encounters directory: {}", e)
-        })?;
-    }
-    Ok(())
+pub fn ensure_encounters_dir(data_dir: &Path) -> Result<(), String> {
+    ensure_dir(&encounters_dir(data_dir), "encounters")
 }
 
 pub fn save_encounter(
+    data_dir: &Path,
     encounter: &CombatEncounter,
     log: &[LogEntry],
+    history: &UndoHistory,
     filename: &str,
 ) -> Result<(), String> {
-    ensure_encounters_dir()?;
+    ensure_encounters_dir(data_dir)?;
 
     let timestamp = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -103,9 +325,10 @@ pub fn save_encounter(
         log: log.to_vec(),
         saved_at: timestamp,
         version: env!("CARGO_PKG_VERSION").to_string(),
+        history: history.clone(),
     };
 
-    let path = format!("{}/{}.json", encounters_dir(), filename);
+    let path = format!("{}/{}.json", encounters_dir(data_dir), filename);
     let json = serde_json::to_string_pretty(&saved).map_err(|e| {
         log::error!("Failed to serialize encounter to JSON: {}", e);
         format!("Could not save encounter: {}", e)
@@ -119,8 +342,8 @@ pub fn save_encounter(
     Ok(())
 }
 
-pub fn load_encounter(filename: &str) -> Result<SavedEncounter, String> {
-    let path = format!("{}/{}.json", encounters_dir(), filename);
+pub fn load_encounter(data_dir: &Path, filename: &str) -> Result<SavedEncounter, String> {
+    let path = format!("{}/{}.json", encounters_dir(data_dir), filename);
 
     if !Path::new(&path).exists() {
         return Err(format!("Encounter file not found: {}", filename));
@@ -137,14 +360,14 @@ pub fn load_encounter(filename: &str) -> Result<SavedEncounter, String> {
     })
 }
 
-pub fn list_encounter_files() -> Result<Vec<String>, String> {
-    let dir = encounters_dir();
+pub fn list_encounter_files(data_dir: &Path) -> Result<Vec<String>, String> {
+    let dir = encounters_dir(data_dir);
 
-    if !Path::new(dir).exists() {
+    if !Path::new(&dir).exists() {
         return Ok(Vec::new());
     }
 
-    let entries = fs::read_dir(dir).map_err(|e| {
+    let entries = fs::read_dir(&dir).map_err(|e| {
         log::error!("Failed to read encounters directory: {}", e);
         format!("Could not read encounters directory: {}", e)
     })?;
@@ -164,27 +387,50 @@ pub fn list_encounter_files() -> Result<Vec<String>, String> {
     Ok(files)
 }
 
+// Session records
+
+pub fn sessions_dir(data_dir: &Path) -> String {
+    data_path(data_dir, "sessions")
+}
+
+/// Writes an end-of-combat summary to `sessions/` and returns its file name.
+pub fn save_session(data_dir: &Path, record: &SessionRecord) -> Result<String, String> {
+    let dir = sessions_dir(data_dir);
+    ensure_dir(&dir, "sessions")?;
+
+    let filename = format!("session_{}", record.encounter_id);
+    let path = format!("{}/{}.json", dir, filename);
+    let json = serde_json::to_string_pretty(record).map_err(|e| {
+        log::error!("Failed to serialize session record to JSON: {}", e);
+        format!("Could not save session record: {}", e)
+    })?;
+
+    fs::write(&path, json).map_err(|e| {
+        log::error!("Failed to write session record to {}: {}", path, e);
+        format!("Could not write session file: {}", e)
+    })?;
+
+    Ok(filename)
+}
+
 // Library file operations
 
-pub fn library_dir() -> &'static str {
-    "library"
+pub fn library_dir(data_dir: &Path) -> String {
+    data_path(data_dir, "library")
 }
 
-pub fn ensure_library_dir() -> Result<(), String> {
-    let dir = library_dir();
-    if !Path::new(dir).exists() {
-        fs::create_dir(dir).map_err(|e| {
-            log::error!("Failed to create library directory: {}", e);
-            format!("Could not create library directory: {}", e)
-        })?;
-    }
-    Ok(())
+pub fn ensure_library_dir(data_dir: &Path) -> Result<(), String> {
+    ensure_dir(&library_dir(data_dir), "library")
 }
 
-pub fn save_library_template(template: &EncounterTemplate, filename: &str) -> Result<(), String> {
-    ensure_library_dir()?;
+pub fn save_library_template(
+    data_dir: &Path,
+    template: &EncounterTemplate,
+    filename: &str,
+) -> Result<(), String> {
+    ensure_library_dir(data_dir)?;
 
-    let path = format!("{}/{}.json", library_dir(), filename);
+    let path = format!("{}/{}.json", library_dir(data_dir), filename);
     let json = serde_json::to_string_pretty(template).map_err(|e| {
         log::error!("Failed to serialize library template to JSON: {}", e);
         format!("Could not save library template: {}", e)
@@ -198,8 +444,8 @@ pub fn save_library_template(template: &EncounterTemplate, filename: &str) -> Re
     Ok(())
 }
 
-pub fn load_library_template(filename: &str) -> Result<EncounterTemplate, String> {
-    let path = format!("{}/{}.json", library_dir(), filename);
+pub fn load_library_template(data_dir: &Path, filename: &str) -> Result<EncounterTemplate, String> {
+    let path = format!("{}/{}.json", library_dir(data_dir), filename);
 
     if !Path::new(&path).exists() {
         return Err(format!("Library template not found: {}", filename));
@@ -216,14 +462,14 @@ pub fn load_library_template(filename: &str) -> Result<EncounterTemplate, String
     })
 }
 
-pub fn list_library_files() -> Result<Vec<String>, String> {
-    let dir = library_dir();
+pub fn list_library_files(data_dir: &Path) -> Result<Vec<String>, String> {
+    let dir = library_dir(data_dir);
 
-    if !Path::new(dir).exists() {
+    if !Path::new(&dir).exists() {
         return Ok(Vec::new());
     }
 
-    let entries = fs::read_dir(dir).map_err(|e| {
+    let entries = fs::read_dir(&dir).map_err(|e| {
         log::error!("Failed to read library directory: {}", e);
         format!("Could not read library directory: {}", e)
     })?;
@@ -243,7 +489,7 @@ pub fn list_library_files() -> Result<Vec<String>, String> {
     Ok(files)
 }
 
-pub fn library_template_exists(filename: &str) -> bool {
-    let path = format!("{}/{}.json", library_dir(), filename);
+pub fn library_template_exists(data_dir: &Path, filename: &str) -> bool {
+    let path = format!("{}/{}.json", library_dir(data_dir), filename);
     Path::new(&path).exists()
 }
@@ -0,0 +1,214 @@
+//! Short and long rests for PCs, whether in the encounter or on the party roster.
+
+use crate::dice::{DiceExpr, DiceRoller, DiceTerm, Keep};
+use crate::models::{Combatant, resources};
+
+/// A PC's hit dice, e.g. "5d10+10" is five d10s with +2 CON per die
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HitDicePool {
+    pub count: u32,
+    pub sides: u32,
+    pub con_modifier: i32,
+}
+
+impl HitDicePool {
+    /// Reads a single group of dice plus an optional flat bonus, which is CON per die
+    /// and so must split evenly over the dice ("3d8+10" is rejected).
+    pub fn parse(formula: &str) -> Option<Self> {
+        let mut pool = None;
+        let mut bonus = 0;
+        for term in DiceExpr::parse(formula).ok()?.terms {
+            match term {
+                DiceTerm::Dice {
+                    count,
+                    sides,
+                    keep: Keep::All,
+                    negative: false,
+                } if pool.is_none() => pool = Some((count, sides)),
+                DiceTerm::Modifier(value) => bonus += value,
+                _ => return None,
+            }
+        }
+        let (count, sides) = pool?;
+        if bonus % count as i32 != 0 {
+            return None;
+        }
+        Some(Self {
+            count,
+            sides,
+            con_modifier: bonus / count as i32,
+        })
+    }
+
+    pub fn of(combatant: &Combatant) -> Option<Self> {
+        combatant.hit_dice_pool.as_deref().and_then(Self::parse)
+    }
+
+    /// Die and per-die bonus, e.g. "d10+2"
+    pub fn die(&self) -> String {
+        match self.con_modifier {
+            0 => format!("d{}", self.sides),
+            modifier => format!("d{}{:+}", self.sides, modifier),
+        }
+    }
+}
+
+/// Hit dice the combatant can still spend, or `None` without a hit dice pool
+pub fn hit_dice_remaining(combatant: &Combatant) -> Option<u32> {
+    HitDicePool::of(combatant).map(|pool| pool.count.saturating_sub(combatant.hit_dice_spent))
+}
+
+/// Spends up to `count` hit dice, each healing its roll plus CON (at least 0),
+/// and restores short-rest resources. Returns the log line, or `None` if
+/// nothing changed.
+pub fn short_rest(combatant: &mut Combatant, count: u32, dice: &mut DiceRoller) -> Option<String> {
+    if combatant.is_dead() {
+        return None;
+    }
+
+    let mut line = None;
+    if let Some(pool) = HitDicePool::of(combatant) {
+        let count = count.min(pool.count.saturating_sub(combatant.hit_dice_spent));
+        if count > 0 {
+            let rolls: Vec<i32> = (0..count)
+                .map(|_| (dice.roll_die(pool.sides) + pool.con_modifier).max(0))
+                .collect();
+            let before = combatant.hp_current;
+            combatant.heal(rolls.iter().sum());
+            combatant.hit_dice_spent += count;
+            if combatant.hp_current > 0 {
+                combatant.clear_death_saves();
+            }
+
+            let rolls: Vec<String> = rolls.iter().map(i32::to_string).collect();
+            line = Some(format!(
+                "{} spends {} hit {} ({}: {}) and regains {} HP ({}/{})",
+                combatant.name,
+                count,
+                if count == 1 { "die" } else { "dice" },
+                pool.die(),
+                rolls.join(", "),
+                combatant.hp_current - before,
+                combatant.hp_current,
+                combatant.hp_max
+            ));
+        }
+    }
+
+    let restored = resources::restore_on_rest(&mut combatant.resources, false);
+    if restored.is_empty() {
+        return line;
+    }
+    let restored = format!("regains {}", restored.join(", "));
+    Some(match line {
+        Some(line) => format!("{}; {}", line, restored),
+        None => format!("{} {}", combatant.name, restored),
+    })
+}
+
+/// Restores HP and all resources, regains up to half the hit dice (at least
+/// one), removes one exhaustion level and clears temp HP and death saves. A PC
+/// needs at least 1 HP to benefit. Returns the log line.
+pub fn long_rest(combatant: &mut Combatant) -> String {
+    if combatant.hp_current <= 0 {
+        return format!(
+            "{} needs at least 1 HP to benefit from a long rest",
+            combatant.name
+        );
+    }
+
+    let healed = combatant.hp_max - combatant.hp_current;
+    combatant.hp_current = combatant.hp_max;
+    combatant.temp_hp = 0;
+    combatant.clear_death_saves();
+
+    let mut changes = vec![format!("regains {} HP", healed)];
+    if let Some(pool) = HitDicePool::of(combatant) {
+        let regained = combatant.hit_dice_spent.min((pool.count / 2).max(1));
+        combatant.hit_dice_spent -= regained;
+        if regained > 0 {
+            changes.push(format!("{} hit dice", regained));
+        }
+    }
+    if combatant.exhaustion > 0 {
+        combatant.exhaustion -= 1;
+        changes.push(format!("exhaustion now {}", combatant.exhaustion));
+    }
+    changes.extend(resources::restore_on_rest(&mut combatant.resources, true));
+
+    format!(
+        "{} finishes a long rest: {}",
+        combatant.name,
+        changes.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fighter() -> Combatant {
+        let mut c = Combatant::new("Fighter".to_string(), 10, 44, 18, true);
+        c.hit_dice_pool = Some("4d10+8".to_string());
+        c
+    }
+
+    #[test]
+    fn hit_dice_pools_spread_the_bonus_over_the_dice() {
+        let pool = HitDicePool::parse("4d10+8").unwrap();
+        assert_eq!((pool.count, pool.sides, pool.con_modifier), (4, 10, 2));
+        assert_eq!(pool.die(), "d10+2");
+        assert_eq!(HitDicePool::parse("3d8").unwrap().die(), "d8");
+        assert_eq!(HitDicePool::parse("5d6-5").unwrap().con_modifier, -1);
+        assert_eq!(HitDicePool::parse("2d6+1d8"), None);
+        assert_eq!(HitDicePool::parse("12"), None);
+        assert_eq!(HitDicePool::parse("3d8+10"), None);
+        assert_eq!(HitDicePool::parse("4d6-3"), None);
+    }
+
+    #[test]
+    fn short_rest_spends_only_the_dice_left() {
+        let mut c = fighter();
+        c.hp_current = 5;
+        c.hit_dice_spent = 2;
+        let mut dice = DiceRoller::seeded(7);
+
+        let line = short_rest(&mut c, 5, &mut dice).unwrap();
+        assert_eq!(c.hit_dice_spent, 4);
+        assert!(c.hp_current >= 5 + 2 * 3 && c.hp_current <= 5 + 2 * 12);
+        assert!(line.starts_with("Fighter spends 2 hit dice (d10+2: "));
+
+        assert_eq!(hit_dice_remaining(&c), Some(0));
+        assert_eq!(short_rest(&mut c, 1, &mut dice), None);
+    }
+
+    #[test]
+    fn long_rest_follows_the_rules() {
+        let mut c = fighter();
+        c.hp_current = 10;
+        c.temp_hp = 5;
+        c.hit_dice_spent = 4;
+        c.exhaustion = 2;
+
+        let line = long_rest(&mut c);
+        assert_eq!((c.hp_current, c.temp_hp), (44, 0));
+        assert_eq!(c.hit_dice_spent, 2); // Half of four dice
+        assert_eq!(c.exhaustion, 1);
+        assert_eq!(
+            line,
+            "Fighter finishes a long rest: regains 34 HP, 2 hit dice, exhaustion now 1"
+        );
+
+        // A single hit die still comes back
+        let mut apprentice = Combatant::new("Apprentice".to_string(), 10, 6, 12, true);
+        apprentice.hit_dice_pool = Some("1d6".to_string());
+        apprentice.hit_dice_spent = 1;
+        long_rest(&mut apprentice);
+        assert_eq!(apprentice.hit_dice_spent, 0);
+
+        c.hp_current = 0;
+        c.exhaustion = 2;
+        long_rest(&mut c);
+        assert_eq!((c.hp_current, c.exhaustion), (0, 2));
+    }
+}
@@ -0,0 +1,162 @@
+//! End-of-combat summaries, saved as session records next to the encounter saves.
+
+use crate::combat::{CombatEncounter, DamageTally};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A monster that was at 0 HP when combat ended or when it was removed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DefeatedMonster {
+    pub name: String,
+    pub xp: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    /// Id of the encounter it summarizes; the session file is named after it
+    #[serde(default)]
+    pub encounter_id: u64,
+    pub ended_at: u64,
+    pub rounds: u32,
+    pub defeated: Vec<DefeatedMonster>,
+    pub total_xp: u32,
+    /// PCs still alive at the end, who split the XP
+    pub survivors: Vec<String>,
+    pub xp_per_survivor: u32,
+    pub damage: Vec<DamageTally>,
+}
+
+/// Summarizes the encounter as it stands, including its running damage totals.
+pub fn summarize(encounter: &CombatEncounter) -> SessionRecord {
+    let everyone: Vec<_> = encounter
+        .combatants
+        .iter()
+        .chain(&encounter.delayed)
+        .collect();
+
+    let defeated: Vec<DefeatedMonster> = everyone
+        .iter()
+        .copied()
+        .chain(&encounter.defeated)
+        .filter(|c| c.is_creature() && !c.is_player && c.hp_current <= 0)
+        .map(|c| DefeatedMonster {
+            name: c.name.clone(),
+            xp: c.stat_block.as_ref().map_or(0, |b| b.xp),
+        })
+        .collect();
+    let total_xp = defeated.iter().map(|m| m.xp).sum();
+    let survivors: Vec<String> = everyone
+        .iter()
+        .filter(|c| c.is_player && !c.is_dead())
+        .map(|c| c.name.clone())
+        .collect();
+    let xp_per_survivor = match survivors.len() {
+        0 => 0,
+        n => total_xp / n as u32,
+    };
+
+    // Creatures in initiative order first, then anyone who has since been removed
+    let mut damage: Vec<DamageTally> = everyone
+        .iter()
+        .filter(|c| c.is_creature())
+        .map(|c| {
+            encounter
+                .damage
+                .iter()
+                .find(|t| t.name == c.name)
+                .cloned()
+                .unwrap_or(DamageTally {
+                    name: c.name.clone(),
+                    dealt: 0,
+                    taken: 0,
+                })
+        })
+        .collect();
+    let removed: Vec<DamageTally> = encounter
+        .damage
+        .iter()
+        .filter(|t| !damage.iter().any(|d| d.name == t.name))
+        .cloned()
+        .collect();
+    damage.extend(removed);
+
+    SessionRecord {
+        encounter_id: encounter.id,
+        ended_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        rounds: encounter.round_number,
+        defeated,
+        total_xp,
+        survivors,
+        xp_per_survivor,
+        damage,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Combatant, StatBlock};
+
+    #[test]
+    fn summary_splits_xp_and_tallies_damage() {
+        let mut encounter = CombatEncounter::new();
+        encounter.add_combatant(Combatant::new("Fighter".to_string(), 15, 30, 18, true));
+        encounter.add_combatant(Combatant::new("Wizard".to_string(), 12, 14, 12, true));
+        let mut orc = Combatant::new("Orc".to_string(), 10, 15, 13, false);
+        orc.stat_block = Some(StatBlock {
+            xp: 100,
+            ..StatBlock::default()
+        });
+        orc.hp_current = 0;
+        encounter.add_combatant(orc);
+        let mut goblin = Combatant::new("Goblin".to_string(), 8, 7, 15, false);
+        goblin.stat_block = Some(StatBlock {
+            xp: 50,
+            ..StatBlock::default()
+        });
+        goblin.hp_current = 0;
+        encounter.add_combatant(goblin);
+        encounter.add_combatant(Combatant::new("Wolf".to_string(), 5, 11, 13, false));
+        // A dead goblin cleared off the board still counts; a wolf that fled doesn't
+        encounter.remove_combatant(3);
+        encounter.remove_combatant(3);
+        encounter.round_number = 3;
+
+        encounter.record_damage(Some("Fighter"), "Orc", 9);
+        encounter.record_damage(Some("Orc"), "Wizard", 5);
+        encounter.record_damage(Some("Wizard"), "Orc", 6);
+        encounter.record_damage(Some("Wolf"), "Fighter", 4); // Removed from the encounter since
+        encounter.record_damage(None, "Wizard", 2); // Ongoing effects credit nobody
+        let record = summarize(&encounter);
+
+        assert_eq!(record.rounds, 3);
+        assert_eq!(
+            record.defeated,
+            vec![
+                DefeatedMonster {
+                    name: "Orc".to_string(),
+                    xp: 100
+                },
+                DefeatedMonster {
+                    name: "Goblin".to_string(),
+                    xp: 50
+                }
+            ]
+        );
+        assert_eq!((record.total_xp, record.xp_per_survivor), (150, 75));
+        assert_eq!(record.survivors, vec!["Fighter", "Wizard"]);
+
+        let tally = |name: &str| {
+            let t = record.damage.iter().find(|t| t.name == name).unwrap();
+            (t.dealt, t.taken)
+        };
+        assert_eq!(tally("Fighter"), (9, 4));
+        assert_eq!(tally("Wizard"), (6, 7));
+        assert_eq!(tally("Orc"), (5, 15));
+        assert_eq!(tally("Wolf"), (4, 0));
+        assert_eq!(record.damage.last().unwrap().name, "Wolf");
+    }
+}
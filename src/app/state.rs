@@ -1,10 +1,16 @@
-use super::persistence::{EncounterTemplate, LibraryCombatant};
+use super::persistence::{EncounterTemplate, LibraryCombatant, PartyMember};
+use super::session::SessionRecord;
+use crate::models::{ConditionType, DamageDefenses, RepeatSave, Resource, SaveOutcome, StatBlock};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum InputMode {
     Normal,
     AddingCombatant(AddCombatantState),
+    AddingTurnEntry(AddTurnEntryState),
     DealingDamage(SelectionState),
+    ChoosingDamageType(DamageTypeState),
+    DealingAreaDamage(AreaDamageState),
+    Attacking(AttackState),
     Healing(SelectionState),
     AddingStatus(SelectionState),
     SelectingCondition(ConditionSelectionState),
@@ -12,17 +18,47 @@ pub enum InputMode {
     ConcentrationTarget(SelectionState),
     ApplyingConcentration(AddConcentrationState),
     ConcentrationCheck(ConcentrationCheckState),
+    RepeatSave(RepeatSaveState),
     ClearingConcentration(SelectionState),
     ClearActionSelection(ClearAction),
     ClearingStatus(SelectionState),
     SelectingStatusToClear(StatusSelectionState),
     SelectingTemplate(SelectionState),
+    BrowsingBestiary(BestiaryState),
+    AddingCopies(CopiesState),
+    EncounterDifficulty(String), // Party levels being edited
+    CombatSummary(Box<SessionRecord>),
+    PartyRoster(usize),
+    Resting(RestState),
+    EditingHitDice(usize, String), // roster member index, formula being typed
+    SelectingResourceTarget(SelectionState),
+    ManagingResources(ResourceState),
+    EditingResources(usize, String), // combatant index, resource spec being typed
+    SelectingOngoingTarget(SelectionState),
+    ManagingOngoingEffects(StatusSelectionState),
+    AddingOngoingEffect(usize, String), // combatant index, effect spec being typed
+    SettingExhaustion(SelectionState),
     SavingTemplate(SelectionState),
     ActionMenu(usize),
     CombatantMenu(usize),
     GrantingTempHp(SelectionState),
     QuickReference(usize),
     Removing(SelectionState),
+    ResolvingTie(SelectionState),
+    UsingReaction(SelectionState),
+    SelectingLegendaryTarget(SelectionState),
+    SettingLegendary(SetLegendaryState),
+    SpendingLegendaryAction(SelectionState),
+    UsingLegendaryResistance(SelectionState),
+    SelectingDefensesTarget(SelectionState),
+    EditingDefenses(EditDefensesState),
+    ReadyingAction(String), // trigger text for the current combatant
+    TriggeringReadied(SelectionState),
+    ChoosingDelayed(usize),                 // index into the delayed list
+    ResumingDelayed(usize, SelectionState), // delayed index, creature to act after
+    PinningCombatant(SelectionState),
+    SelectingNotesTarget(SelectionState),
+    EditingNotes(EditNotesState),
     SavingEncounter(SaveEncounterState),
     LoadingEncounter(SelectionState),
     SavingLibrary(SaveLibraryState),
@@ -34,12 +70,128 @@ pub enum InputMode {
 
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct AddCombatantState {
-    pub step: usize, // 0: name, 1: initiative, 2: hp, 3: ac, 4: is_player
+    pub step: usize, // 0: name, 1: initiative modifier, 2: initiative, 3: hp, 4: ac, 5: is_player
     pub name: String,
+    pub initiative_modifier: String,
     pub initiative: String,
     pub hp: String,
     pub ac: String,
     pub is_player: String,
+    pub legendary_actions: u32, // Carried over from templates, not prompted
+    pub legendary_resistances: u32,
+    pub defenses: DamageDefenses,
+    pub stat_block: Option<Box<StatBlock>>, // Boxed to keep InputMode small
+    pub hit_dice: Option<String>,
+    pub resources: Vec<Resource>,
+    pub roster_member: Option<Box<PartyMember>>, // Lasting state restored when added from the roster
+}
+
+/// Spending and restoring one combatant's slots and features
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ResourceState {
+    pub combatant_index: usize,
+    pub selected_index: usize,
+}
+
+/// Short or long rest for the encounter's PCs or the party roster
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RestState {
+    pub long: bool,
+    pub on_roster: bool,
+    pub selected_index: usize,
+    /// Hit dice each PC spends on a short rest, in `App::rest_targets` order
+    pub hit_dice: Vec<u32>,
+}
+
+/// State for adding a lair action, hazard or event entry to initiative
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AddTurnEntryState {
+    pub step: usize,       // 0: kind, 1: name, 2: initiative, 3: reminder
+    pub kind_index: usize, // Index into CombatantKind::turn_entries()
+    pub name: String,
+    pub initiative: String,
+    pub reminder: String,
+}
+
+/// State for setting a combatant's legendary action/resistance maximums
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SetLegendaryState {
+    pub combatant_index: usize,
+    pub step: usize, // 0: legendary actions, 1: legendary resistances
+    pub actions: String,
+    pub resistances: String,
+}
+
+/// Damage prompt step after the target and amount are entered
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DamageTypeState {
+    pub combatant_index: usize,
+    pub amount: String,        // Number or dice expression, rolled on confirm
+    pub selected_index: usize, // Index into DamageType::all()
+    pub critical: bool,        // Damage dice are doubled
+    pub source: Option<usize>, // Creature credited with the damage
+}
+
+/// Attack roll against a target's AC, continuing into damage on a hit
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AttackState {
+    pub step: usize, // 0: attacker, 1: target, 2: attack roll, 3: damage
+    pub attacker: usize,
+    pub target: usize,
+    pub input: String, // Attack bonus / roll, then the damage amount
+    pub critical: bool,
+}
+
+/// Multi-target damage with a per-target save outcome
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AreaDamageState {
+    pub step: usize,   // 0: targets and saves, 1: amount, 2: damage type
+    pub cursor: usize, // Combatant index under the cursor in step 0
+    pub targets: Vec<(usize, SaveOutcome)>,
+    pub amount: String,
+    pub type_index: usize, // Index into DamageType::all()
+}
+
+/// Resistance/immunity/vulnerability editor for one creature
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EditDefensesState {
+    pub combatant_index: usize,
+    pub selected_index: usize, // Index into DamageType::all(), skipping Untyped
+}
+
+/// Bestiary browser: query with filters, highlighted result and copies to add
+#[derive(Debug, Clone, PartialEq)]
+pub struct BestiaryState {
+    pub query: String,
+    pub selected_index: usize, // Index into the search results, not the bestiary
+    pub copies: u32,
+    pub group_initiative: bool,
+}
+
+impl Default for BestiaryState {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            selected_index: 0,
+            copies: 1,
+            group_initiative: false,
+        }
+    }
+}
+
+/// Number of copies to add from an NPC template
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CopiesState {
+    pub template_index: usize,
+    pub count: String,
+    pub group_initiative: bool,
+}
+
+/// Free-text notes being edited for one combatant
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EditNotesState {
+    pub combatant_index: usize,
+    pub input: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -69,6 +221,15 @@ pub struct ConcentrationCheckState {
     pub input: String,
 }
 
+/// A PC's end-of-turn save against a condition, entered by the player
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepeatSaveState {
+    pub combatant_index: usize,
+    pub condition: ConditionType,
+    pub save: RepeatSave,
+    pub input: String,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ClearAction {
     Concentration,
@@ -110,4 +271,5 @@ pub struct LoadLibraryState {
     pub template: EncounterTemplate,
     pub combatants_with_init: Vec<(LibraryCombatant, String)>, // (combatant, initiative_input)
     pub current_index: usize, // Which combatant we're setting initiative for
+    pub roll_log: Vec<String>, // Dice breakdowns logged once the encounter is loaded
 }
@@ -1,11 +1,46 @@
 use crate::models::Combatant;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Turn order: higher initiative, then creatures before lair/hazard/event
+/// entries, then higher initiative (DEX) modifier, then PCs before NPCs,
+/// then the higher stored tiebreaker roll.
+pub fn initiative_order(a: &Combatant, b: &Combatant) -> Ordering {
+    let (pa, pb) = (a.initiative_place(), b.initiative_place());
+    b.initiative
+        .cmp(&a.initiative)
+        .then(pb.creature.cmp(&pa.creature))
+        .then(pb.modifier.cmp(&pa.modifier))
+        .then(pb.is_player.cmp(&pa.is_player))
+        .then(b.tiebreaker.cmp(&a.tiebreaker))
+}
+
+/// Damage dealt and taken by one combatant over the fight
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DamageTally {
+    pub name: String,
+    pub dealt: i32,
+    pub taken: i32,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CombatEncounter {
     pub combatants: Vec<Combatant>,
     pub current_turn_index: usize,
     pub round_number: u32,
+    /// Creatures that delayed their turn and are waiting to re-enter the order
+    #[serde(default)]
+    pub delayed: Vec<Combatant>,
+    /// Monsters removed from the order at 0 HP, still owed as XP when combat ends
+    #[serde(default)]
+    pub defeated: Vec<Combatant>,
+    /// Tells fights apart, so ending one again after an undo overwrites its session record
+    #[serde(default)]
+    pub id: u64,
+    /// Running damage totals by name; the log is trimmed, so long fights can't be tallied from it
+    #[serde(default)]
+    pub damage: Vec<DamageTally>,
 }
 
 impl CombatEncounter {
@@ -14,21 +49,61 @@ impl CombatEncounter {
             combatants: Vec::new(),
             current_turn_index: 0,
             round_number: 1,
+            delayed: Vec::new(),
+            defeated: Vec::new(),
+            id: new_encounter_id(),
+            damage: Vec::new(),
         }
     }
 
+    /// Inserts after every entry it doesn't beat, so existing positions
+    /// (including ones chosen by delaying) are left untouched. Once turns have
+    /// started moving the turn stays with whoever is acting; before that the
+    /// top of the order keeps it, so setting up a fight in any order works.
     pub fn add_combatant(&mut self, combatant: Combatant) {
-        self.combatants.push(combatant);
-        self.sort_by_initiative();
+        let position = self
+            .combatants
+            .iter()
+            .position(|c| initiative_order(&combatant, c) == Ordering::Less)
+            .unwrap_or(self.combatants.len());
+        self.combatants.insert(position, combatant);
+        let turns_started = self.round_number > 1 || self.current_turn_index > 0;
         // Reset turn index if this is the first combatant
         if self.combatants.len() == 1 {
             self.current_turn_index = 0;
+        } else if turns_started && position <= self.current_turn_index {
+            self.current_turn_index += 1;
+        }
+    }
+
+    /// Adds damage taken by `target`, credited to `source` when a creature dealt it.
+    pub fn record_damage(&mut self, source: Option<&str>, target: &str, amount: i32) {
+        self.tally_for(target).taken += amount;
+        if let Some(source) = source {
+            self.tally_for(source).dealt += amount;
+        }
+    }
+
+    fn tally_for(&mut self, name: &str) -> &mut DamageTally {
+        match self.damage.iter().position(|t| t.name == name) {
+            Some(index) => &mut self.damage[index],
+            None => {
+                self.damage.push(DamageTally {
+                    name: name.to_string(),
+                    dealt: 0,
+                    taken: 0,
+                });
+                self.damage.last_mut().unwrap()
+            }
         }
     }
 
     pub fn remove_combatant(&mut self, index: usize) {
         if index < self.combatants.len() {
-            self.combatants.remove(index);
+            let removed = self.combatants.remove(index);
+            if removed.is_creature() && !removed.is_player && removed.hp_current <= 0 {
+                self.defeated.push(removed);
+            }
             // Adjust current turn index if needed
             if self.current_turn_index >= self.combatants.len() && !self.combatants.is_empty() {
                 self.current_turn_index = 0;
@@ -36,9 +111,148 @@ impl CombatEncounter {
         }
     }
 
-    pub fn sort_by_initiative(&mut self) {
-        self.combatants
-            .sort_by(|a, b| b.initiative.cmp(&a.initiative));
+    /// Re-sorts after initiative changes, keeping the turn on the same combatant.
+    /// The sort is stable, so fully tied combatants keep their relative order.
+    pub fn resort_keeping_turn(&mut self) {
+        if self.combatants.is_empty() {
+            return;
+        }
+        let mut order: Vec<usize> = (0..self.combatants.len()).collect();
+        order.sort_by(|&a, &b| initiative_order(&self.combatants[a], &self.combatants[b]));
+        let new_turn = order
+            .iter()
+            .position(|&i| i == self.current_turn_index)
+            .unwrap_or(0);
+        let mut slots: Vec<Option<Combatant>> = std::mem::take(&mut self.combatants)
+            .into_iter()
+            .map(Some)
+            .collect();
+        self.combatants = order.iter().filter_map(|&i| slots[i].take()).collect();
+        self.current_turn_index = new_turn;
+    }
+
+    /// True when the combatant at `index` ties the next one on every tiebreak rule.
+    pub fn is_tied_with_next(&self, index: usize) -> bool {
+        match (self.combatants.get(index), self.combatants.get(index + 1)) {
+            (Some(a), Some(b)) => initiative_order(a, b) == Ordering::Equal,
+            _ => false,
+        }
+    }
+
+    /// True when the combatant at `index` is part of an unresolved tie.
+    pub fn is_unresolved_tie(&self, index: usize) -> bool {
+        self.is_tied_with_next(index) || (index > 0 && self.is_tied_with_next(index - 1))
+    }
+
+    /// Swaps a combatant with the one it is tied with (the next one if possible)
+    /// and adjusts the stored tiebreakers so the chosen order survives re-sorts.
+    /// Returns the combatant's new index.
+    pub fn swap_tied(&mut self, index: usize) -> Result<usize, String> {
+        let (upper, lower) = if self.is_tied_with_next(index) {
+            (index, index + 1)
+        } else if index > 0 && self.is_tied_with_next(index - 1) {
+            (index - 1, index)
+        } else {
+            return Err("That combatant is not in an unresolved tie".to_string());
+        };
+
+        self.combatants.swap(upper, lower);
+        if self.current_turn_index == upper {
+            self.current_turn_index = lower;
+        } else if self.current_turn_index == lower {
+            self.current_turn_index = upper;
+        }
+
+        self.pin_tied_block(lower);
+
+        Ok(if index == upper { lower } else { upper })
+    }
+
+    /// Raises tiebreakers through the block of entries tied with `index`, bottom-up,
+    /// so each beats the one below it and the current order survives re-sorts.
+    fn pin_tied_block(&mut self, index: usize) {
+        let same_block = |a: &Combatant, b: &Combatant| {
+            a.initiative == b.initiative && a.initiative_place() == b.initiative_place()
+        };
+        let mut end = index;
+        while end + 1 < self.combatants.len()
+            && same_block(&self.combatants[end], &self.combatants[end + 1])
+        {
+            end += 1;
+        }
+        let mut i = end;
+        while i > 0 && same_block(&self.combatants[i - 1], &self.combatants[i]) {
+            let below = self.combatants[i].tiebreaker;
+            let above = &mut self.combatants[i - 1];
+            above.tiebreaker = above.tiebreaker.max(below + 1);
+            i -= 1;
+        }
+    }
+
+    /// Names for `count` new copies of `base`, continuing after the highest existing
+    /// "base N" so a second batch of goblins doesn't reuse "Goblin 1".
+    pub fn numbered_names(&self, base: &str, count: u32) -> Vec<String> {
+        let existing = self.combatants.iter().chain(&self.delayed);
+        next_copy_names(existing.map(|c| c.name.as_str()), base, count)
+    }
+
+    /// Takes the current combatant out of the order; the next combatant's turn begins.
+    /// Returns the delayed combatant's name.
+    pub fn delay_current(&mut self) -> Result<String, String> {
+        if self.current_turn_index >= self.combatants.len() {
+            return Err("No combatant is taking a turn".to_string());
+        }
+        let combatant = self.combatants.remove(self.current_turn_index);
+        let name = combatant.name.clone();
+        self.delayed.push(combatant);
+
+        if self.combatants.is_empty() {
+            self.current_turn_index = 0;
+        } else if self.current_turn_index >= self.combatants.len() {
+            self.current_turn_index = 0;
+            self.round_number += 1;
+        }
+        if let Some(next) = self.combatants.get_mut(self.current_turn_index) {
+            next.economy.start_turn();
+        }
+        Ok(name)
+    }
+
+    /// Re-enters a delayed combatant directly after `after_index`, taking that
+    /// creature's initiative count and tie-break place from now on.
+    /// Returns the new position.
+    pub fn resume_delayed(
+        &mut self,
+        delayed_index: usize,
+        after_index: Option<usize>,
+    ) -> Result<usize, String> {
+        if delayed_index >= self.delayed.len() {
+            return Err("Invalid delayed combatant".to_string());
+        }
+        let position = match after_index {
+            Some(index) if index < self.combatants.len() => index + 1,
+            Some(_) => return Err("Invalid combatant index".to_string()),
+            None if self.combatants.is_empty() => 0,
+            None => return Err("Choose a creature to act after".to_string()),
+        };
+
+        let mut combatant = self.delayed.remove(delayed_index);
+        combatant.held_place = None;
+        if let Some(index) = after_index {
+            // Borrow the anchor's whole tie-break place so re-sorts keep the chosen slot
+            let anchor = &self.combatants[index];
+            combatant.initiative = anchor.initiative;
+            combatant.held_place = Some(anchor.initiative_place());
+            combatant.tiebreaker = anchor.tiebreaker;
+        }
+        self.combatants.insert(position, combatant);
+        if after_index.is_some() {
+            self.pin_tied_block(position);
+        }
+        if position <= self.current_turn_index && self.combatants.len() > 1 {
+            self.current_turn_index += 1;
+        }
+        Ok(position)
     }
 
     pub fn next_turn(&mut self) {
@@ -46,9 +260,10 @@ impl CombatEncounter {
             return;
         }
 
-        // Decrement status effects for current combatant
+        // Decrement status effects and end the turn for current combatant
         if let Some(combatant) = self.combatants.get_mut(self.current_turn_index) {
             combatant.decrement_status_effects();
+            combatant.economy.end_turn();
         }
 
         // Move to next combatant
@@ -59,6 +274,11 @@ impl CombatEncounter {
             self.current_turn_index = 0;
             self.round_number += 1;
         }
+
+        // Reactions come back at the start of the creature's own turn
+        if let Some(combatant) = self.combatants.get_mut(self.current_turn_index) {
+            combatant.economy.start_turn();
+        }
     }
 
     #[allow(dead_code)]
@@ -93,10 +313,42 @@ impl Default for CombatEncounter {
     }
 }
 
+/// Millisecond timestamp used as an encounter id; 0 is left for saves from before ids.
+pub fn new_encounter_id() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+        .max(1)
+}
+
+/// Splits "Goblin 3" into ("Goblin", 3); None for names without a copy number.
+pub fn split_copy_number(name: &str) -> Option<(&str, u32)> {
+    let (base, number) = name.rsplit_once(' ')?;
+    Some((base, number.parse().ok()?))
+}
+
+/// Names for `count` copies of `base` numbered after the highest "base N" in `existing`.
+pub fn next_copy_names<'a>(
+    existing: impl Iterator<Item = &'a str>,
+    base: &str,
+    count: u32,
+) -> Vec<String> {
+    let highest = existing
+        .filter_map(split_copy_number)
+        .filter(|(name, _)| *name == base)
+        .map(|(_, number)| number)
+        .max()
+        .unwrap_or(0);
+    (highest + 1..=highest + count)
+        .map(|n| format!("{} {}", base, n))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::Combatant;
+    use crate::models::{Combatant, CombatantKind};
 
     fn combatant(name: &str, init: i32) -> Combatant {
         Combatant::new(name.to_string(), init, 10, 10, false)
@@ -113,6 +365,97 @@ mod tests {
         assert_eq!(enc.current_turn_index, 0);
     }
 
+    #[test]
+    fn adding_mid_combat_keeps_the_current_turn() {
+        let mut enc = CombatEncounter::new();
+        enc.add_combatant(combatant("A", 15));
+        enc.add_combatant(combatant("B", 10));
+        enc.next_turn();
+        assert_eq!(enc.combatants[enc.current_turn_index].name, "B");
+
+        enc.add_combatant(combatant("C", 20));
+        let names: Vec<_> = enc.combatants.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["C", "A", "B"]);
+        assert_eq!(enc.combatants[enc.current_turn_index].name, "B");
+
+        enc.add_combatant(combatant("D", 5));
+        assert_eq!(enc.combatants[enc.current_turn_index].name, "B");
+    }
+
+    #[test]
+    fn ties_break_on_modifier_then_pc_then_tiebreaker() {
+        let mut enc = CombatEncounter::new();
+        let mut npc = combatant("Npc", 12);
+        npc.initiative_modifier = 1;
+        let mut dex = combatant("Dex", 12);
+        dex.initiative_modifier = 3;
+        let mut pc = Combatant::new("Pc".to_string(), 12, 10, 10, true);
+        pc.initiative_modifier = 1;
+        let mut lucky = combatant("Lucky", 12);
+        lucky.initiative_modifier = 1;
+        lucky.tiebreaker = 18;
+        enc.add_combatant(npc);
+        enc.add_combatant(dex);
+        enc.add_combatant(pc);
+        enc.add_combatant(lucky);
+        let names: Vec<_> = enc.combatants.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Dex", "Pc", "Lucky", "Npc"]);
+        assert!(!enc.is_unresolved_tie(0));
+        assert!(!enc.is_unresolved_tie(3));
+    }
+
+    #[test]
+    fn copy_numbers_continue_after_the_highest_existing() {
+        let mut enc = CombatEncounter::new();
+        enc.add_combatant(combatant("Goblin 3", 10));
+        enc.add_combatant(combatant("Goblin Boss", 10));
+        enc.add_combatant(combatant("Hobgoblin 7", 10));
+        enc.delayed.push(combatant("Goblin 4", 10));
+        assert_eq!(
+            enc.numbered_names("Goblin", 2),
+            vec!["Goblin 5", "Goblin 6"]
+        );
+        assert_eq!(enc.numbered_names("Orc", 1), vec!["Orc 1"]);
+        assert_eq!(split_copy_number("Young Red Dragon"), None);
+    }
+
+    #[test]
+    fn lair_entries_lose_initiative_ties() {
+        let mut enc = CombatEncounter::new();
+        enc.add_combatant(Combatant::new_turn_entry(
+            "Lair".to_string(),
+            20,
+            CombatantKind::Lair,
+            None,
+        ));
+        enc.add_combatant(combatant("Dragon", 20));
+        enc.add_combatant(combatant("Kobold", 21));
+        let names: Vec<_> = enc.combatants.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Kobold", "Dragon", "Lair"]);
+        assert!(!enc.is_unresolved_tie(1));
+    }
+
+    #[test]
+    fn swapping_a_tie_sticks_across_resorts() {
+        let mut enc = CombatEncounter::new();
+        enc.add_combatant(combatant("A", 10));
+        enc.add_combatant(combatant("B", 10));
+        enc.add_combatant(combatant("C", 10));
+        assert!(enc.is_unresolved_tie(1));
+
+        assert_eq!(enc.swap_tied(2), Ok(1));
+        let names: Vec<_> = enc.combatants.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["A", "C", "B"]);
+        assert!(!enc.is_unresolved_tie(0));
+        assert!(!enc.is_unresolved_tie(2));
+
+        enc.add_combatant(combatant("D", 20));
+        enc.resort_keeping_turn();
+        let names: Vec<_> = enc.combatants.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["D", "A", "C", "B"]);
+        assert!(enc.swap_tied(0).is_err());
+    }
+
     #[test]
     fn next_turn_wraps_and_increments_round() {
         let mut enc = CombatEncounter::new();
@@ -127,6 +470,85 @@ mod tests {
         assert_eq!(enc.round_number, 2);
     }
 
+    #[test]
+    fn next_turn_resets_action_economy_at_the_right_time() {
+        let mut enc = CombatEncounter::new();
+        enc.add_combatant(combatant("A", 15));
+        enc.add_combatant(combatant("B", 5));
+        enc.combatants[0].economy.action = true;
+        enc.combatants[0].economy.reaction = true;
+        enc.combatants[1].economy.reaction = true;
+
+        enc.next_turn();
+        // A's turn ended: action back, reaction still spent; B's reaction is back
+        assert!(!enc.combatants[0].economy.action);
+        assert!(enc.combatants[0].economy.reaction);
+        assert!(!enc.combatants[1].economy.reaction);
+
+        enc.next_turn();
+        assert!(!enc.combatants[0].economy.reaction);
+    }
+
+    #[test]
+    fn delay_and_resume_keep_turn_index_correct() {
+        let mut enc = CombatEncounter::new();
+        enc.add_combatant(combatant("A", 20));
+        enc.add_combatant(combatant("B", 15));
+        enc.add_combatant(combatant("C", 10));
+
+        // A delays: B's turn starts
+        assert_eq!(enc.delay_current(), Ok("A".to_string()));
+        assert_eq!(enc.combatants[enc.current_turn_index].name, "B");
+        assert_eq!(enc.delayed.len(), 1);
+
+        // After B, A re-enters and acts next
+        assert_eq!(enc.resume_delayed(0, Some(0)), Ok(1));
+        assert_eq!(enc.combatants[enc.current_turn_index].name, "B");
+        assert_eq!(enc.combatants[1].initiative, 15);
+        enc.next_turn();
+        assert_eq!(enc.combatants[enc.current_turn_index].name, "A");
+
+        // The new position survives later additions
+        enc.add_combatant(combatant("D", 12));
+        let names: Vec<_> = enc.combatants.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["B", "A", "D", "C"]);
+        assert_eq!(enc.combatants[enc.current_turn_index].name, "A");
+    }
+
+    #[test]
+    fn resumed_slot_survives_a_resort() {
+        let mut enc = CombatEncounter::new();
+        let mut rogue = Combatant::new("Rogue".to_string(), 18, 10, 10, true);
+        rogue.initiative_modifier = 4;
+        enc.add_combatant(rogue);
+        enc.add_combatant(combatant("B", 15));
+        enc.add_combatant(combatant("C", 15));
+        enc.add_combatant(combatant("D", 5));
+
+        // The rogue would beat B and C on DEX and being a PC, but chose to go after B
+        enc.delay_current().unwrap();
+        assert_eq!(enc.resume_delayed(0, Some(0)), Ok(1));
+        enc.resort_keeping_turn();
+        let names: Vec<_> = enc.combatants.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["B", "Rogue", "C", "D"]);
+        assert_eq!(enc.combatants[enc.current_turn_index].name, "B");
+        assert_eq!(enc.combatants[1].initiative_modifier, 4);
+        assert!(enc.combatants[1].is_player);
+    }
+
+    #[test]
+    fn delaying_the_last_combatant_starts_a_new_round() {
+        let mut enc = CombatEncounter::new();
+        enc.add_combatant(combatant("A", 20));
+        enc.add_combatant(combatant("B", 10));
+        enc.next_turn();
+        enc.delay_current().unwrap();
+        assert_eq!(enc.current_turn_index, 0);
+        assert_eq!(enc.round_number, 2);
+        assert!(enc.resume_delayed(0, None).is_err());
+        assert!(enc.resume_delayed(3, Some(0)).is_err());
+    }
+
     #[test]
     fn previous_turn_wraps_backwards() {
         let mut enc = CombatEncounter::new();
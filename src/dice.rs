@@ -0,0 +1,580 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+
+const MAX_DICE: u32 = 100;
+const MAX_SIDES: u32 = 1000;
+
+/// Which dice of a group count towards the total
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keep {
+    All,
+    Highest(u32),
+    Lowest(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiceTerm {
+    Dice {
+        count: u32,
+        sides: u32,
+        keep: Keep,
+        negative: bool,
+    },
+    Modifier(i32),
+}
+
+/// Parsed dice expression such as `2d6+3`, `4d6kh3` or `1d20adv`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiceExpr {
+    pub terms: Vec<DiceTerm>,
+}
+
+impl DiceExpr {
+    /// Parses an expression made of `+`/`-` separated dice groups and constants.
+    ///
+    /// Dice groups are `[count]d<sides>` optionally followed by `kh<n>`/`k<n>`
+    /// (keep highest), `kl<n>` (keep lowest), `adv` or `dis`. A plain integer
+    /// is also a valid expression, so pre-rolled values keep working.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let cleaned: String = input
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_lowercase();
+        if cleaned.is_empty() {
+            return Err("Empty dice expression".to_string());
+        }
+
+        let mut terms = Vec::new();
+        let mut current = String::new();
+        let mut negative = false;
+        for (i, c) in cleaned.chars().enumerate() {
+            if c == '+' || c == '-' {
+                if i > 0 {
+                    terms.push(parse_term(&current, negative, input)?);
+                }
+                current.clear();
+                negative = c == '-';
+            } else {
+                current.push(c);
+            }
+        }
+        terms.push(parse_term(&current, negative, input)?);
+
+        Ok(Self { terms })
+    }
+
+    /// A single d20 plus a flat modifier, e.g. an initiative roll.
+    pub fn d20(modifier: i32) -> Self {
+        let mut terms = vec![DiceTerm::Dice {
+            count: 1,
+            sides: 20,
+            keep: Keep::All,
+            negative: false,
+        }];
+        if modifier != 0 {
+            terms.push(DiceTerm::Modifier(modifier));
+        }
+        Self { terms }
+    }
+
+    /// Critical hit damage: every dice group is rolled twice as many times,
+    /// capped at the usual dice limit.
+    pub fn critical(&self) -> Self {
+        let terms = self
+            .terms
+            .iter()
+            .map(|term| match term {
+                DiceTerm::Dice {
+                    count,
+                    sides,
+                    keep,
+                    negative,
+                } => {
+                    let doubled = (count * 2).min(MAX_DICE);
+                    DiceTerm::Dice {
+                        count: doubled,
+                        sides: *sides,
+                        keep: match keep {
+                            Keep::All => Keep::All,
+                            Keep::Highest(n) => Keep::Highest((n * 2).min(doubled)),
+                            Keep::Lowest(n) => Keep::Lowest((n * 2).min(doubled)),
+                        },
+                        negative: *negative,
+                    }
+                }
+                DiceTerm::Modifier(value) => DiceTerm::Modifier(*value),
+            })
+            .collect();
+        Self { terms }
+    }
+
+    /// Rounded-down average, as printed next to a monster's hit points
+    /// (`2d6` averages 7, `17d10+85` averages 178). `None` for keep/advantage dice.
+    pub fn average(&self) -> Option<i32> {
+        let mut twice = 0i64;
+        for term in &self.terms {
+            let (value, negative) = match term {
+                DiceTerm::Dice {
+                    count,
+                    sides,
+                    keep: Keep::All,
+                    negative,
+                } => (*count as i64 * (*sides as i64 + 1), *negative),
+                DiceTerm::Dice { .. } => return None,
+                DiceTerm::Modifier(value) => (*value as i64 * 2, false),
+            };
+            twice += if negative { -value } else { value };
+        }
+        Some(twice.div_euclid(2) as i32)
+    }
+
+    /// True when the expression contains no dice (a pre-rolled number).
+    pub fn is_constant(&self) -> bool {
+        self.terms
+            .iter()
+            .all(|t| matches!(t, DiceTerm::Modifier(_)))
+    }
+}
+
+fn parse_term(term: &str, negative: bool, input: &str) -> Result<DiceTerm, String> {
+    let invalid = || format!("Invalid dice expression: {}", input.trim());
+    if term.is_empty() {
+        return Err(invalid());
+    }
+
+    let Some(d_pos) = term.find('d') else {
+        let value = term.parse::<i32>().map_err(|_| invalid())?;
+        return Ok(DiceTerm::Modifier(if negative { -value } else { value }));
+    };
+
+    let count = if d_pos == 0 {
+        1
+    } else {
+        term[..d_pos].parse::<u32>().map_err(|_| invalid())?
+    };
+    let rest = &term[d_pos + 1..];
+    let sides_len = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+    let sides = rest[..sides_len].parse::<u32>().map_err(|_| invalid())?;
+    let suffix = &rest[sides_len..];
+
+    let (count, keep) = match suffix {
+        "" => (count, Keep::All),
+        "adv" | "dis" => {
+            if count != 1 {
+                return Err("adv/dis only applies to a single die".to_string());
+            }
+            let keep = if suffix == "adv" {
+                Keep::Highest(1)
+            } else {
+                Keep::Lowest(1)
+            };
+            (2, keep)
+        }
+        _ => {
+            let (kind, amount) = if let Some(n) = suffix.strip_prefix("kh") {
+                (Keep::Highest(0), n)
+            } else if let Some(n) = suffix.strip_prefix("kl") {
+                (Keep::Lowest(0), n)
+            } else if let Some(n) = suffix.strip_prefix('k') {
+                (Keep::Highest(0), n)
+            } else {
+                return Err(invalid());
+            };
+            let n = amount.parse::<u32>().map_err(|_| invalid())?;
+            if n == 0 || n > count {
+                return Err(format!("Cannot keep {} of {} dice", n, count));
+            }
+            let keep = match kind {
+                Keep::Lowest(_) => Keep::Lowest(n),
+                _ => Keep::Highest(n),
+            };
+            (count, keep)
+        }
+    };
+
+    if count == 0 || count > MAX_DICE {
+        return Err(format!("Dice count must be between 1 and {}", MAX_DICE));
+    }
+    if sides == 0 || sides > MAX_SIDES {
+        return Err(format!("Dice sides must be between 1 and {}", MAX_SIDES));
+    }
+
+    Ok(DiceTerm::Dice {
+        count,
+        sides,
+        keep,
+        negative,
+    })
+}
+
+impl fmt::Display for DiceExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, term) in self.terms.iter().enumerate() {
+            let (negative, body) = match term {
+                DiceTerm::Dice {
+                    count,
+                    sides,
+                    keep,
+                    negative,
+                } => {
+                    let suffix = match keep {
+                        Keep::All => String::new(),
+                        Keep::Highest(1) if *count == 2 && *sides == 20 => "adv".to_string(),
+                        Keep::Lowest(1) if *count == 2 && *sides == 20 => "dis".to_string(),
+                        Keep::Highest(n) => format!("kh{}", n),
+                        Keep::Lowest(n) => format!("kl{}", n),
+                    };
+                    let shown_count = if suffix == "adv" || suffix == "dis" {
+                        1
+                    } else {
+                        *count
+                    };
+                    (*negative, format!("{}d{}{}", shown_count, sides, suffix))
+                }
+                DiceTerm::Modifier(value) => (*value < 0, value.abs().to_string()),
+            };
+            if negative {
+                write!(f, "-")?;
+            } else if i > 0 {
+                write!(f, "+")?;
+            }
+            write!(f, "{}", body)?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of rolling one term of an expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RolledTerm {
+    Dice {
+        values: Vec<i32>,
+        kept: Vec<bool>,
+        negative: bool,
+    },
+    Modifier(i32),
+}
+
+/// A rolled expression with every individual die kept for the breakdown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiceRoll {
+    pub expression: DiceExpr,
+    pub terms: Vec<RolledTerm>,
+    pub total: i32,
+}
+
+impl DiceRoll {
+    /// True when nothing was actually rolled (the input was a plain number).
+    pub fn is_fixed(&self) -> bool {
+        self.expression.is_constant()
+    }
+
+    /// The kept value of the first d20 group, used to spot natural 1s and 20s.
+    pub fn natural_d20(&self) -> Option<i32> {
+        self.expression
+            .terms
+            .iter()
+            .zip(&self.terms)
+            .find_map(|(term, rolled)| match (term, rolled) {
+                (DiceTerm::Dice { sides: 20, .. }, RolledTerm::Dice { values, kept, .. }) => {
+                    values.iter().zip(kept).find(|(_, k)| **k).map(|(v, _)| *v)
+                }
+                _ => None,
+            })
+    }
+
+    /// Human readable breakdown, e.g. `4d6kh3: [6, 5, 3, (1)] = 14`.
+    /// Dropped dice are shown in parentheses.
+    pub fn breakdown(&self) -> String {
+        let mut parts = String::new();
+        for (i, term) in self.terms.iter().enumerate() {
+            let (negative, body) = match term {
+                RolledTerm::Dice {
+                    values,
+                    kept,
+                    negative,
+                } => {
+                    let dice: Vec<String> = values
+                        .iter()
+                        .zip(kept)
+                        .map(|(v, k)| {
+                            if *k {
+                                v.to_string()
+                            } else {
+                                format!("({})", v)
+                            }
+                        })
+                        .collect();
+                    (*negative, format!("[{}]", dice.join(", ")))
+                }
+                RolledTerm::Modifier(value) => (*value < 0, value.abs().to_string()),
+            };
+            if negative {
+                parts.push_str(if i > 0 { " - " } else { "-" });
+            } else if i > 0 {
+                parts.push_str(" + ");
+            }
+            parts.push_str(&body);
+        }
+        format!("{}: {} = {}", self.expression, parts, self.total)
+    }
+}
+
+/// Seedable random source for all dice rolled by the tracker
+#[derive(Debug, Clone)]
+pub struct DiceRoller {
+    rng: StdRng,
+}
+
+impl DiceRoller {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn roll_die(&mut self, sides: u32) -> i32 {
+        self.rng.gen_range(1..=sides) as i32
+    }
+
+    pub fn roll(&mut self, expr: &DiceExpr) -> DiceRoll {
+        let mut total: i32 = 0;
+        let mut terms = Vec::new();
+
+        for term in &expr.terms {
+            match term {
+                DiceTerm::Dice {
+                    count,
+                    sides,
+                    keep,
+                    negative,
+                } => {
+                    let values: Vec<i32> = (0..*count).map(|_| self.roll_die(*sides)).collect();
+                    let kept = kept_dice(&values, *keep);
+                    let sum: i32 = values
+                        .iter()
+                        .zip(&kept)
+                        .filter(|(_, k)| **k)
+                        .map(|(v, _)| *v)
+                        .sum();
+                    total = if *negative {
+                        total.saturating_sub(sum)
+                    } else {
+                        total.saturating_add(sum)
+                    };
+                    terms.push(RolledTerm::Dice {
+                        values,
+                        kept,
+                        negative: *negative,
+                    });
+                }
+                DiceTerm::Modifier(value) => {
+                    total = total.saturating_add(*value);
+                    terms.push(RolledTerm::Modifier(*value));
+                }
+            }
+        }
+
+        DiceRoll {
+            expression: expr.clone(),
+            terms,
+            total,
+        }
+    }
+
+    pub fn roll_str(&mut self, input: &str) -> Result<DiceRoll, String> {
+        let expr = DiceExpr::parse(input)?;
+        Ok(self.roll(&expr))
+    }
+}
+
+impl Default for DiceRoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn kept_dice(values: &[i32], keep: Keep) -> Vec<bool> {
+    let n = match keep {
+        Keep::All => return vec![true; values.len()],
+        Keep::Highest(n) | Keep::Lowest(n) => n as usize,
+    };
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    match keep {
+        Keep::Highest(_) => order.sort_by_key(|&i| std::cmp::Reverse(values[i])),
+        _ => order.sort_by_key(|&i| values[i]),
+    }
+    let mut kept = vec![false; values.len()];
+    for &i in order.iter().take(n) {
+        kept[i] = true;
+    }
+    kept
+}
+
+/// Characters that may appear in a dice expression typed into a prompt
+pub fn is_dice_char(c: char) -> bool {
+    c.is_ascii_digit() || matches!(c, 'd' | 'k' | 'h' | 'l' | 'a' | 'v' | 'i' | 's' | '+' | '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_number_as_constant() {
+        let expr = DiceExpr::parse("7").unwrap();
+        assert!(expr.is_constant());
+        assert_eq!(DiceRoller::seeded(1).roll(&expr).total, 7);
+        assert_eq!(DiceRoller::seeded(1).roll_str("-2").unwrap().total, -2);
+    }
+
+    #[test]
+    fn averages_round_down_like_stat_blocks() {
+        let average = |input: &str| DiceExpr::parse(input).unwrap().average();
+        assert_eq!(average("2d6"), Some(7));
+        assert_eq!(average("17d10+85"), Some(178));
+        assert_eq!(average("1d8-1"), Some(3));
+        assert_eq!(average("4d6kh3"), None);
+    }
+
+    #[test]
+    fn parses_dice_with_modifier() {
+        let expr = DiceExpr::parse("2d6+3").unwrap();
+        assert_eq!(
+            expr.terms,
+            vec![
+                DiceTerm::Dice {
+                    count: 2,
+                    sides: 6,
+                    keep: Keep::All,
+                    negative: false
+                },
+                DiceTerm::Modifier(3)
+            ]
+        );
+        assert_eq!(expr.to_string(), "2d6+3");
+    }
+
+    #[test]
+    fn d20_helper_formats_modifier() {
+        assert_eq!(DiceExpr::d20(3).to_string(), "1d20+3");
+        assert_eq!(DiceExpr::d20(-1).to_string(), "1d20-1");
+        assert_eq!(DiceExpr::d20(0).to_string(), "1d20");
+    }
+
+    #[test]
+    fn critical_doubles_dice_but_not_modifiers() {
+        let expr = DiceExpr::parse("2d6+1d8+3").unwrap();
+        assert_eq!(expr.critical().to_string(), "4d6+2d8+3");
+        let keep = DiceExpr::parse("4d6kh3").unwrap();
+        assert_eq!(keep.critical().to_string(), "8d6kh6");
+        let many = DiceExpr::parse("80d6kh60").unwrap();
+        assert_eq!(many.critical().to_string(), "100d6kh100");
+    }
+
+    #[test]
+    fn totals_saturate_instead_of_overflowing() {
+        let mut roller = DiceRoller::seeded(1);
+        let big = roller.roll_str("2147483647+2147483647").unwrap();
+        assert_eq!(big.total, i32::MAX);
+        let low = roller.roll_str("-2147483647-2147483647-1d6").unwrap();
+        assert_eq!(low.total, i32::MIN);
+    }
+
+    #[test]
+    fn natural_d20_uses_the_kept_die() {
+        let mut roller = DiceRoller::seeded(11);
+        let roll = roller.roll_str("1d20adv+5").unwrap();
+        let RolledTerm::Dice { values, .. } = &roll.terms[0] else {
+            panic!("Expected dice");
+        };
+        assert_eq!(roll.natural_d20(), values.iter().max().copied());
+        assert_eq!(roller.roll_str("2d6").unwrap().natural_d20(), None);
+    }
+
+    #[test]
+    fn parses_keep_and_advantage() {
+        let keep = DiceExpr::parse("4d6kh3").unwrap();
+        assert!(matches!(
+            keep.terms[0],
+            DiceTerm::Dice {
+                count: 4,
+                keep: Keep::Highest(3),
+                ..
+            }
+        ));
+        let adv = DiceExpr::parse("1d20adv+5").unwrap();
+        assert!(matches!(
+            adv.terms[0],
+            DiceTerm::Dice {
+                count: 2,
+                keep: Keep::Highest(1),
+                ..
+            }
+        ));
+        assert_eq!(adv.to_string(), "1d20adv+5");
+        assert_eq!(DiceExpr::parse("D20DIS").unwrap().to_string(), "1d20dis");
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        assert!(DiceExpr::parse("").is_err());
+        assert!(DiceExpr::parse("2d").is_err());
+        assert!(DiceExpr::parse("2d6+").is_err());
+        assert!(DiceExpr::parse("4d6kh5").is_err());
+        assert!(DiceExpr::parse("2d20adv").is_err());
+        assert!(DiceExpr::parse("1000d6").is_err());
+        assert!(DiceExpr::parse("abc").is_err());
+    }
+
+    #[test]
+    fn rolls_stay_in_range_and_are_reproducible() {
+        let expr = DiceExpr::parse("3d6+2").unwrap();
+        let mut a = DiceRoller::seeded(42);
+        let mut b = DiceRoller::seeded(42);
+        for _ in 0..100 {
+            let roll = a.roll(&expr);
+            assert!((5..=20).contains(&roll.total));
+            assert_eq!(roll, b.roll(&expr));
+        }
+    }
+
+    #[test]
+    fn keep_highest_drops_lowest_dice() {
+        let mut roller = DiceRoller::seeded(7);
+        let roll = roller.roll_str("4d6kh3").unwrap();
+        let RolledTerm::Dice { values, kept, .. } = &roll.terms[0] else {
+            panic!("expected dice term");
+        };
+        let mut sorted = values.clone();
+        sorted.sort();
+        assert_eq!(kept.iter().filter(|k| **k).count(), 3);
+        assert_eq!(roll.total, sorted[1..].iter().sum::<i32>());
+    }
+
+    #[test]
+    fn breakdown_shows_dice_and_total() {
+        let roll = DiceRoll {
+            expression: DiceExpr::parse("4d6kh3-1").unwrap(),
+            terms: vec![
+                RolledTerm::Dice {
+                    values: vec![6, 1, 5, 3],
+                    kept: vec![true, false, true, true],
+                    negative: false,
+                },
+                RolledTerm::Modifier(-1),
+            ],
+            total: 13,
+        };
+        assert_eq!(roll.breakdown(), "4d6kh3-1: [6, (1), 5, 3] - 1 = 13");
+        assert!(!roll.is_fixed());
+    }
+}
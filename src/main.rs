@@ -2,6 +2,7 @@
 
 mod app;
 mod combat;
+mod dice;
 mod models;
 mod ui;
 
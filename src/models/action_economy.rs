@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+/// One of the per-turn resources a creature can spend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EconomySlot {
+    Action,
+    BonusAction,
+    Reaction,
+    Movement,
+}
+
+impl EconomySlot {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EconomySlot::Action => "action",
+            EconomySlot::BonusAction => "bonus action",
+            EconomySlot::Reaction => "reaction",
+            EconomySlot::Movement => "movement",
+        }
+    }
+}
+
+/// Tracks which per-turn resources have been used. `true` means spent.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct ActionEconomy {
+    pub action: bool,
+    pub bonus_action: bool,
+    pub reaction: bool,
+    pub movement: bool,
+}
+
+impl ActionEconomy {
+    pub fn is_used(&self, slot: EconomySlot) -> bool {
+        match slot {
+            EconomySlot::Action => self.action,
+            EconomySlot::BonusAction => self.bonus_action,
+            EconomySlot::Reaction => self.reaction,
+            EconomySlot::Movement => self.movement,
+        }
+    }
+
+    /// Flips a slot between used and available, returning the new used state.
+    pub fn toggle(&mut self, slot: EconomySlot) -> bool {
+        let flag = match slot {
+            EconomySlot::Action => &mut self.action,
+            EconomySlot::BonusAction => &mut self.bonus_action,
+            EconomySlot::Reaction => &mut self.reaction,
+            EconomySlot::Movement => &mut self.movement,
+        };
+        *flag = !*flag;
+        *flag
+    }
+
+    /// Action, bonus action and movement come back once the creature's turn ends.
+    pub fn end_turn(&mut self) {
+        self.action = false;
+        self.bonus_action = false;
+        self.movement = false;
+    }
+
+    /// The reaction comes back at the start of the creature's own turn.
+    pub fn start_turn(&mut self) {
+        self.reaction = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reaction_survives_end_of_turn() {
+        let mut economy = ActionEconomy::default();
+        assert!(economy.toggle(EconomySlot::Action));
+        assert!(economy.toggle(EconomySlot::Reaction));
+        economy.end_turn();
+        assert!(!economy.is_used(EconomySlot::Action));
+        assert!(economy.is_used(EconomySlot::Reaction));
+        economy.start_turn();
+        assert!(!economy.is_used(EconomySlot::Reaction));
+    }
+}
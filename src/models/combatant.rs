@@ -1,4 +1,7 @@
-use super::{ConcentrationInfo, DeathSaveOutcome, DeathSaves, status::StatusEffect};
+use super::{
+    ActionEconomy, CombatantKind, ConcentrationInfo, DamageDefenses, DamageType, DeathSaveOutcome,
+    DeathSaves, LegendaryPool, OngoingEffect, Resource, StatBlock, status::StatusEffect,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,8 +17,69 @@ pub struct Combatant {
     pub status_effects: Vec<StatusEffect>,
     pub death_saves: Option<DeathSaves>,
     pub concentration: Option<ConcentrationInfo>,
+    #[serde(default)]
+    pub initiative_modifier: i32,
+    /// Stored roll used to break initiative ties that DEX and PC/NPC don't settle
+    #[serde(default)]
+    pub tiebreaker: i32,
+    #[serde(default)]
+    pub legendary_actions: Option<LegendaryPool>,
+    #[serde(default)]
+    pub legendary_resistances: Option<LegendaryPool>,
+    #[serde(default)]
+    pub kind: CombatantKind,
+    /// Shown when a lair action, hazard or event entry's turn comes up
+    #[serde(default)]
+    pub reminder: Option<String>,
+    #[serde(default)]
+    pub economy: ActionEconomy,
+    /// Trigger for a readied action, cleared when used or when the next turn starts
+    #[serde(default)]
+    pub readied: Option<String>,
+    #[serde(default)]
+    pub defenses: DamageDefenses,
+    #[serde(default)]
+    pub stat_block: Option<StatBlock>,
+    /// Free-form DM notes shown in the details panel
+    #[serde(default)]
+    pub notes: String,
+    /// Hit dice formula the combatant's HP came from, kept for templates and the library
+    #[serde(default)]
+    pub hit_dice: Option<String>,
+    /// A PC's hit dice for rests, such as "5d10+10", set from the party roster
+    #[serde(default)]
+    pub hit_dice_pool: Option<String>,
+    /// Hit dice used up from the `hit_dice_pool` since the last long rest
+    #[serde(default)]
+    pub hit_dice_spent: u32,
+    /// Exhaustion level, 0 to `MAX_EXHAUSTION`
+    #[serde(default)]
+    pub exhaustion: u8,
+    /// Spell slots, pact slots and limited-use features
+    #[serde(default)]
+    pub resources: Vec<Resource>,
+    /// Damage or healing applied at the start or end of each of its turns
+    #[serde(default)]
+    pub ongoing_effects: Vec<OngoingEffect>,
+    /// Damage types taken since its last turn started, for effects like regeneration
+    #[serde(default)]
+    pub damage_taken_since_turn: Vec<DamageType>,
+    /// Tie-break place borrowed from the creature a delayed turn re-entered after
+    #[serde(default)]
+    pub held_place: Option<InitiativePlace>,
+}
+
+/// The initiative tie-break rules below the count itself, in the order they apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InitiativePlace {
+    pub creature: bool,
+    pub modifier: i32,
+    pub is_player: bool,
 }
 
+/// Exhaustion level at which a creature dies
+pub const MAX_EXHAUSTION: u8 = 6;
+
 impl Combatant {
     pub fn new(
         name: String,
@@ -35,9 +99,55 @@ impl Combatant {
             status_effects: Vec::new(),
             death_saves: None,
             concentration: None,
+            initiative_modifier: 0,
+            tiebreaker: 0,
+            legendary_actions: None,
+            legendary_resistances: None,
+            kind: CombatantKind::Creature,
+            reminder: None,
+            economy: ActionEconomy::default(),
+            readied: None,
+            defenses: DamageDefenses::default(),
+            stat_block: None,
+            notes: String::new(),
+            hit_dice: None,
+            hit_dice_pool: None,
+            hit_dice_spent: 0,
+            exhaustion: 0,
+            resources: Vec::new(),
+            ongoing_effects: Vec::new(),
+            damage_taken_since_turn: Vec::new(),
+            held_place: None,
         }
     }
 
+    /// Creates a non-creature turn entry such as a lair action at initiative 20.
+    pub fn new_turn_entry(
+        name: String,
+        initiative: i32,
+        kind: CombatantKind,
+        reminder: Option<String>,
+    ) -> Self {
+        Self {
+            kind,
+            reminder,
+            ..Self::new(name, initiative, 0, 0, false)
+        }
+    }
+
+    pub fn is_creature(&self) -> bool {
+        self.kind.is_creature()
+    }
+
+    /// Where it sorts among equal initiatives, before the tiebreaker roll
+    pub fn initiative_place(&self) -> InitiativePlace {
+        self.held_place.unwrap_or(InitiativePlace {
+            creature: self.is_creature(),
+            modifier: self.initiative_modifier,
+            is_player: self.is_player,
+        })
+    }
+
     pub fn take_damage(&mut self, damage: i32) {
         let mut remaining = damage;
         if self.temp_hp > 0 {
@@ -79,6 +189,7 @@ impl Combatant {
             effect.decrement_duration();
         }
         self.status_effects.retain(|effect| !effect.is_expired());
+        self.ongoing_effects.retain_mut(|effect| !effect.tick());
     }
 
     pub fn is_unconscious(&self) -> bool {
@@ -1,3 +1,4 @@
+use super::{DamageDefenses, Resource, StatBlock};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -6,15 +7,43 @@ pub struct CombatantTemplate {
     pub hp_max: i32,
     pub armor_class: i32,
     pub is_player: bool,
+    #[serde(default)]
+    pub initiative_modifier: i32,
+    #[serde(default)]
+    pub legendary_actions: u32,
+    #[serde(default)]
+    pub legendary_resistances: u32,
+    #[serde(default)]
+    pub defenses: DamageDefenses,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stat_block: Option<StatBlock>,
+    /// Hit dice formula such as `2d6` or `8d10+16`, rolled instead of `hp_max` on request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hit_dice: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resources: Vec<Resource>,
 }
 
 impl CombatantTemplate {
-    pub fn from_stats(name: String, hp_max: i32, armor_class: i32, is_player: bool) -> Self {
+    pub fn from_stats(
+        name: String,
+        hp_max: i32,
+        armor_class: i32,
+        is_player: bool,
+        initiative_modifier: i32,
+    ) -> Self {
         Self {
             name,
             hp_max,
             armor_class,
             is_player,
+            initiative_modifier,
+            legendary_actions: 0,
+            legendary_resistances: 0,
+            defenses: DamageDefenses::default(),
+            stat_block: None,
+            hit_dice: None,
+            resources: Vec::new(),
         }
     }
 }
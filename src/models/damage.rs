@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum DamageType {
+    #[default]
+    Untyped,
+    Acid,
+    Bludgeoning,
+    Cold,
+    Fire,
+    Force,
+    Lightning,
+    Necrotic,
+    Piercing,
+    Poison,
+    Psychic,
+    Radiant,
+    Slashing,
+    Thunder,
+}
+
+impl DamageType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DamageType::Untyped => "Untyped",
+            DamageType::Acid => "Acid",
+            DamageType::Bludgeoning => "Bludgeoning",
+            DamageType::Cold => "Cold",
+            DamageType::Fire => "Fire",
+            DamageType::Force => "Force",
+            DamageType::Lightning => "Lightning",
+            DamageType::Necrotic => "Necrotic",
+            DamageType::Piercing => "Piercing",
+            DamageType::Poison => "Poison",
+            DamageType::Psychic => "Psychic",
+            DamageType::Radiant => "Radiant",
+            DamageType::Slashing => "Slashing",
+            DamageType::Thunder => "Thunder",
+        }
+    }
+
+    /// Case-insensitive lookup by name, e.g. "fire" or "Fire"
+    pub fn parse(name: &str) -> Option<DamageType> {
+        let name = name.trim();
+        Self::all()
+            .into_iter()
+            .find(|t| t.as_str().eq_ignore_ascii_case(name))
+    }
+
+    /// Untyped first so the damage prompt defaults to it
+    pub fn all() -> Vec<DamageType> {
+        vec![
+            DamageType::Untyped,
+            DamageType::Acid,
+            DamageType::Bludgeoning,
+            DamageType::Cold,
+            DamageType::Fire,
+            DamageType::Force,
+            DamageType::Lightning,
+            DamageType::Necrotic,
+            DamageType::Piercing,
+            DamageType::Poison,
+            DamageType::Psychic,
+            DamageType::Radiant,
+            DamageType::Slashing,
+            DamageType::Thunder,
+        ]
+    }
+
+    /// Types that can be resisted, i.e. everything but Untyped
+    pub fn defendable() -> Vec<DamageType> {
+        Self::all()
+            .into_iter()
+            .filter(|t| *t != DamageType::Untyped)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefenseKind {
+    Resistance,
+    Immunity,
+    Vulnerability,
+}
+
+impl DefenseKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DefenseKind::Resistance => "resistance",
+            DefenseKind::Immunity => "immunity",
+            DefenseKind::Vulnerability => "vulnerability",
+        }
+    }
+}
+
+/// How much of an area effect's damage a target takes after its saving throw
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SaveOutcome {
+    #[default]
+    Full,
+    Half,
+    None, // Evasion and similar features
+}
+
+impl SaveOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SaveOutcome::Full => "full",
+            SaveOutcome::Half => "half on save",
+            SaveOutcome::None => "none (evasion)",
+        }
+    }
+
+    pub fn apply(&self, raw: i32) -> i32 {
+        match self {
+            SaveOutcome::Full => raw,
+            SaveOutcome::Half => raw / 2,
+            SaveOutcome::None => 0,
+        }
+    }
+
+    pub fn next(&self) -> SaveOutcome {
+        match self {
+            SaveOutcome::Full => SaveOutcome::Half,
+            SaveOutcome::Half => SaveOutcome::None,
+            SaveOutcome::None => SaveOutcome::Full,
+        }
+    }
+}
+
+/// Damage types a creature resists, ignores or takes double damage from
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct DamageDefenses {
+    #[serde(default)]
+    pub resistances: Vec<DamageType>,
+    #[serde(default)]
+    pub immunities: Vec<DamageType>,
+    #[serde(default)]
+    pub vulnerabilities: Vec<DamageType>,
+}
+
+impl DamageDefenses {
+    pub fn is_empty(&self) -> bool {
+        self.resistances.is_empty() && self.immunities.is_empty() && self.vulnerabilities.is_empty()
+    }
+
+    fn list(&self, kind: DefenseKind) -> &Vec<DamageType> {
+        match kind {
+            DefenseKind::Resistance => &self.resistances,
+            DefenseKind::Immunity => &self.immunities,
+            DefenseKind::Vulnerability => &self.vulnerabilities,
+        }
+    }
+
+    pub fn has(&self, kind: DefenseKind, damage_type: DamageType) -> bool {
+        self.list(kind).contains(&damage_type)
+    }
+
+    /// Adds or removes a damage type; returns whether it is now present.
+    /// Untyped damage can't be defended against and is ignored.
+    pub fn toggle(&mut self, kind: DefenseKind, damage_type: DamageType) -> bool {
+        if damage_type == DamageType::Untyped {
+            return false;
+        }
+        let list = match kind {
+            DefenseKind::Resistance => &mut self.resistances,
+            DefenseKind::Immunity => &mut self.immunities,
+            DefenseKind::Vulnerability => &mut self.vulnerabilities,
+        };
+        if let Some(pos) = list.iter().position(|t| *t == damage_type) {
+            list.remove(pos);
+            false
+        } else {
+            list.push(damage_type);
+            true
+        }
+    }
+
+    /// Applies immunity, then resistance (halved, rounded down), then vulnerability.
+    pub fn apply(&self, raw: i32, damage_type: DamageType) -> i32 {
+        if self.has(DefenseKind::Immunity, damage_type) {
+            return 0;
+        }
+        let mut amount = raw;
+        if self.has(DefenseKind::Resistance, damage_type) {
+            amount /= 2;
+        }
+        if self.has(DefenseKind::Vulnerability, damage_type) {
+            amount *= 2;
+        }
+        amount
+    }
+
+    /// Short summary such as "Res Fire, Cold | Imm Poison"
+    pub fn summary(&self) -> String {
+        let join = |types: &[DamageType]| {
+            types
+                .iter()
+                .map(|t| t.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        [
+            ("Res", &self.resistances),
+            ("Imm", &self.immunities),
+            ("Vuln", &self.vulnerabilities),
+        ]
+        .iter()
+        .filter(|(_, types)| !types.is_empty())
+        .map(|(label, types)| format!("{} {}", label, join(types)))
+        .collect::<Vec<_>>()
+        .join(" | ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defenses_modify_matching_damage_only() {
+        let mut defenses = DamageDefenses::default();
+        defenses.toggle(DefenseKind::Resistance, DamageType::Fire);
+        defenses.toggle(DefenseKind::Immunity, DamageType::Poison);
+        defenses.toggle(DefenseKind::Vulnerability, DamageType::Radiant);
+
+        assert_eq!(defenses.apply(11, DamageType::Fire), 5);
+        assert_eq!(defenses.apply(11, DamageType::Poison), 0);
+        assert_eq!(defenses.apply(11, DamageType::Radiant), 22);
+        assert_eq!(defenses.apply(11, DamageType::Cold), 11);
+        assert_eq!(defenses.apply(11, DamageType::Untyped), 11);
+
+        // Resistance and vulnerability to the same type cancel out (after rounding)
+        defenses.toggle(DefenseKind::Vulnerability, DamageType::Fire);
+        assert_eq!(defenses.apply(11, DamageType::Fire), 10);
+
+        assert_eq!(
+            defenses.summary(),
+            "Res Fire | Imm Poison | Vuln Radiant, Fire"
+        );
+        assert!(!defenses.toggle(DefenseKind::Resistance, DamageType::Fire));
+        assert!(!defenses.toggle(DefenseKind::Resistance, DamageType::Untyped));
+    }
+}
@@ -0,0 +1,181 @@
+//! Encounter difficulty from the DMG's XP thresholds and group multipliers.
+
+/// Easy, Medium, Hard and Deadly XP thresholds for characters of level 1-20
+const XP_THRESHOLDS: [[u32; 4]; 20] = [
+    [25, 50, 75, 100],
+    [50, 100, 150, 200],
+    [75, 150, 225, 400],
+    [125, 250, 375, 500],
+    [250, 500, 750, 1100],
+    [300, 600, 900, 1400],
+    [350, 750, 1100, 1700],
+    [450, 900, 1400, 2100],
+    [550, 1100, 1600, 2400],
+    [600, 1200, 1900, 2800],
+    [800, 1600, 2400, 3600],
+    [1000, 2000, 3000, 4500],
+    [1100, 2200, 3400, 5100],
+    [1250, 2500, 3800, 5700],
+    [1400, 2800, 4300, 6400],
+    [1600, 3200, 4800, 7200],
+    [2000, 3900, 5900, 8800],
+    [2100, 4200, 6300, 9500],
+    [2400, 4900, 7300, 10900],
+    [2800, 5700, 8500, 12700],
+];
+
+/// Multipliers from one monster up to fifteen or more, with an extra step on
+/// each side for small and large parties
+const MULTIPLIERS: [f64; 8] = [0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 4.0, 5.0];
+
+pub const MAX_LEVEL: u32 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Trivial,
+    Easy,
+    Medium,
+    Hard,
+    Deadly,
+}
+
+impl Difficulty {
+    /// The four ratings with a threshold, in threshold order
+    pub fn rated() -> [Difficulty; 4] {
+        [
+            Difficulty::Easy,
+            Difficulty::Medium,
+            Difficulty::Hard,
+            Difficulty::Deadly,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Difficulty::Trivial => "Trivial",
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Deadly => "Deadly",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DifficultyReport {
+    /// Monsters with a known XP value
+    pub monsters: usize,
+    /// Monsters without a CR, left out of the totals
+    pub unrated: usize,
+    pub base_xp: u32,
+    pub multiplier: f64,
+    pub adjusted_xp: u32,
+    /// Party thresholds in `Difficulty::rated()` order
+    pub thresholds: [u32; 4],
+    /// `None` until a party is configured
+    pub rating: Option<Difficulty>,
+}
+
+/// Rates monsters (by XP, 0 meaning unknown) against a party of character levels.
+pub fn assess(monster_xp: &[u32], levels: &[u32]) -> DifficultyReport {
+    let rated: Vec<u32> = monster_xp.iter().copied().filter(|&xp| xp > 0).collect();
+    let base_xp: u32 = rated.iter().sum();
+    let multiplier = multiplier(rated.len(), levels.len());
+    let adjusted_xp = (base_xp as f64 * multiplier) as u32;
+
+    let mut thresholds = [0; 4];
+    for level in levels {
+        let row = XP_THRESHOLDS[(level.clamp(&1, &MAX_LEVEL) - 1) as usize];
+        for (total, value) in thresholds.iter_mut().zip(row) {
+            *total += value;
+        }
+    }
+
+    let rating = (!levels.is_empty()).then(|| {
+        Difficulty::rated()
+            .into_iter()
+            .zip(thresholds)
+            .rev()
+            .find(|(_, threshold)| adjusted_xp >= *threshold)
+            .map_or(Difficulty::Trivial, |(difficulty, _)| difficulty)
+    });
+
+    DifficultyReport {
+        monsters: rated.len(),
+        unrated: monster_xp.len() - rated.len(),
+        base_xp,
+        multiplier,
+        adjusted_xp,
+        thresholds,
+        rating,
+    }
+}
+
+/// Character levels separated by spaces or commas, e.g. "3 3 4 5"
+pub fn parse_levels(input: &str) -> Result<Vec<u32>, String> {
+    input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            part.parse::<u32>()
+                .ok()
+                .filter(|level| (1..=MAX_LEVEL).contains(level))
+                .ok_or_else(|| format!("Invalid level '{}' (1-{})", part, MAX_LEVEL))
+        })
+        .collect()
+}
+
+fn multiplier(monsters: usize, party_size: usize) -> f64 {
+    let step = match monsters {
+        0 | 1 => 1,
+        2 => 2,
+        3..=6 => 3,
+        7..=10 => 4,
+        11..=14 => 5,
+        _ => 6,
+    };
+    let step = match party_size {
+        1 | 2 => step + 1,
+        n if n >= 6 => step - 1,
+        _ => step,
+    };
+    MULTIPLIERS[step]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_multiplier_and_party_thresholds() {
+        // Four 3rd-level PCs against a bugbear and three hobgoblins (DMG example)
+        let report = assess(&[200, 100, 100, 100], &[3, 3, 3, 3]);
+        assert_eq!(report.base_xp, 500);
+        assert_eq!(report.multiplier, 2.0);
+        assert_eq!(report.adjusted_xp, 1000);
+        assert_eq!(report.thresholds, [300, 600, 900, 1600]);
+        assert_eq!(report.rating, Some(Difficulty::Hard));
+
+        // Small parties step up, large parties step down
+        assert_eq!(assess(&[50], &[1, 1]).multiplier, 1.5);
+        assert_eq!(assess(&[50], &[1; 6]).multiplier, 0.5);
+        assert_eq!(assess(&[50; 20], &[1]).multiplier, 5.0);
+    }
+
+    #[test]
+    fn unknown_monsters_and_missing_party_are_reported() {
+        let report = assess(&[0, 25], &[]);
+        assert_eq!((report.monsters, report.unrated), (1, 1));
+        assert_eq!(report.rating, None);
+        assert_eq!(assess(&[10], &[5]).rating, Some(Difficulty::Trivial));
+        assert_eq!(assess(&[50_000], &[25]).rating, Some(Difficulty::Deadly));
+    }
+
+    #[test]
+    fn party_levels_parse_from_spaces_or_commas() {
+        assert_eq!(parse_levels("3 3, 4,5"), Ok(vec![3, 3, 4, 5]));
+        assert_eq!(parse_levels("  "), Ok(vec![]));
+        assert!(parse_levels("3 21").is_err());
+        assert!(parse_levels("three").is_err());
+    }
+}
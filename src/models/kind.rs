@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+/// What an initiative entry represents. Only creatures have HP, AC and conditions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum CombatantKind {
+    #[default]
+    Creature,
+    Lair,
+    Hazard,
+    Event,
+}
+
+impl CombatantKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CombatantKind::Creature => "Creature",
+            CombatantKind::Lair => "Lair Action",
+            CombatantKind::Hazard => "Hazard",
+            CombatantKind::Event => "Event",
+        }
+    }
+
+    /// Entry kinds that can be added with the turn entry wizard
+    pub fn turn_entries() -> Vec<CombatantKind> {
+        vec![
+            CombatantKind::Lair,
+            CombatantKind::Hazard,
+            CombatantKind::Event,
+        ]
+    }
+
+    pub fn is_creature(&self) -> bool {
+        *self == CombatantKind::Creature
+    }
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+/// Finite pool of legendary actions or legendary resistances
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LegendaryPool {
+    pub max: u32,
+    pub remaining: u32,
+}
+
+impl LegendaryPool {
+    pub fn new(max: u32) -> Self {
+        Self {
+            max,
+            remaining: max,
+        }
+    }
+
+    /// Builds a pool from a template maximum; zero means the creature has none.
+    pub fn from_max(max: u32) -> Option<Self> {
+        (max > 0).then(|| Self::new(max))
+    }
+
+    pub fn spend(&mut self, cost: u32) -> Result<(), String> {
+        if cost == 0 {
+            return Err("Cost must be at least 1".to_string());
+        }
+        if cost > self.remaining {
+            return Err(format!("Only {} of {} remaining", self.remaining, self.max));
+        }
+        self.remaining -= cost;
+        Ok(())
+    }
+
+    pub fn refresh(&mut self) {
+        self.remaining = self.max;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spend_and_refresh() {
+        let mut pool = LegendaryPool::new(3);
+        pool.spend(2).unwrap();
+        assert_eq!(pool.remaining, 1);
+        assert!(pool.spend(2).is_err());
+        assert!(pool.spend(0).is_err());
+        pool.refresh();
+        assert_eq!(pool.remaining, 3);
+        assert!(LegendaryPool::from_max(0).is_none());
+    }
+}
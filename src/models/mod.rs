@@ -1,13 +1,29 @@
+pub mod action_economy;
 pub mod combatant;
 pub mod combatant_template;
 pub mod concentration;
+pub mod damage;
 pub mod death_saves;
+pub mod difficulty;
+pub mod kind;
+pub mod legendary;
 pub mod log;
+pub mod ongoing;
+pub mod resources;
+pub mod stat_block;
 pub mod status;
 
-pub use combatant::Combatant;
+pub use action_economy::{ActionEconomy, EconomySlot};
+pub use combatant::{Combatant, MAX_EXHAUSTION};
 pub use combatant_template::CombatantTemplate;
 pub use concentration::ConcentrationInfo;
+pub use damage::{DamageDefenses, DamageType, DefenseKind, SaveOutcome};
 pub use death_saves::{DeathSaveOutcome, DeathSaves};
+pub use difficulty::{Difficulty, DifficultyReport};
+pub use kind::CombatantKind;
+pub use legendary::LegendaryPool;
 pub use log::LogEntry;
-pub use status::{ConditionType, StatusEffect};
+pub use ongoing::{OngoingEffect, TurnBoundary};
+pub use resources::Resource;
+pub use stat_block::{Ability, StatBlock, StatBlockEntry};
+pub use status::{ConditionType, RepeatSave, StatusEffect};
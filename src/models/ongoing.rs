@@ -0,0 +1,178 @@
+//! Damage or healing that repeats on the bearer's turn, such as burning,
+//! Heat Metal or a troll's regeneration.
+
+use serde::{Deserialize, Serialize};
+
+use super::DamageType;
+
+/// When in the bearer's turn an ongoing effect applies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TurnBoundary {
+    #[default]
+    Start,
+    End,
+}
+
+impl TurnBoundary {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TurnBoundary::Start => "start",
+            TurnBoundary::End => "end",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OngoingEffect {
+    pub name: String,
+    /// Dice expression or flat amount rolled each time the effect applies
+    pub dice: String,
+    pub damage_type: DamageType,
+    /// Heals instead of dealing damage
+    #[serde(default)]
+    pub healing: bool,
+    #[serde(default)]
+    pub timing: TurnBoundary,
+    /// Rounds left, ticking down at the end of the bearer's turn; 0 = until removed
+    #[serde(default)]
+    pub duration: i32,
+    /// Damage types that stop the effect for a turn, e.g. fire or acid for a troll
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressed_by: Vec<DamageType>,
+}
+
+impl OngoingEffect {
+    /// Parses `<name>: <dice> [damage type | heal] [start | end] [rounds] [unless <types>]`,
+    /// e.g. `Burning: 1d6 fire start 3` or `Regeneration: 10 heal start unless fire acid`.
+    /// The dice expression itself is checked by the caller.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (name, rest) = spec
+            .split_once(':')
+            .ok_or("Start with a name and a colon, e.g. 'Burning: 1d6 fire'")?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("The effect needs a name".to_string());
+        }
+        let mut words = rest.split_whitespace();
+        let dice = words
+            .next()
+            .ok_or_else(|| format!("{} needs a dice expression or amount", name))?;
+
+        let mut effect = Self {
+            name: name.to_string(),
+            dice: dice.to_string(),
+            damage_type: DamageType::Untyped,
+            healing: false,
+            timing: TurnBoundary::Start,
+            duration: 0,
+            suppressed_by: Vec::new(),
+        };
+        let mut unless = false;
+        for word in words {
+            let word = word.trim_matches(',');
+            if unless {
+                let damage_type = DamageType::parse(word)
+                    .ok_or_else(|| format!("Unknown damage type '{}'", word))?;
+                effect.suppressed_by.push(damage_type);
+                continue;
+            }
+            match word.to_lowercase().as_str() {
+                "heal" | "healing" => effect.healing = true,
+                "start" => effect.timing = TurnBoundary::Start,
+                "end" => effect.timing = TurnBoundary::End,
+                "unless" => unless = true,
+                other => {
+                    if let Ok(rounds) = other.parse::<i32>() {
+                        effect.duration = rounds.max(0);
+                    } else {
+                        effect.damage_type = DamageType::parse(other)
+                            .ok_or_else(|| format!("Unknown word '{}' in '{}'", word, spec))?;
+                    }
+                }
+            }
+        }
+        if unless && effect.suppressed_by.is_empty() {
+            return Err("'unless' needs at least one damage type".to_string());
+        }
+        Ok(effect)
+    }
+
+    /// "1d6 fire at start of turn" style summary for lists and the details panel
+    pub fn describe(&self) -> String {
+        let what = if self.healing {
+            "healing".to_string()
+        } else if self.damage_type == DamageType::Untyped {
+            "damage".to_string()
+        } else {
+            self.damage_type.as_str().to_lowercase()
+        };
+        let mut text = format!(
+            "{}: {} {} at {} of turn",
+            self.name,
+            self.dice,
+            what,
+            self.timing.as_str()
+        );
+        if !self.suppressed_by.is_empty() {
+            let types: Vec<String> = self
+                .suppressed_by
+                .iter()
+                .map(|t| t.as_str().to_lowercase())
+                .collect();
+            text.push_str(&format!(" unless hit by {}", types.join(" or ")));
+        }
+        if self.duration > 0 {
+            text.push_str(&format!(" ({} rounds)", self.duration));
+        }
+        text
+    }
+
+    /// True once a timed effect has run out; mirrors `StatusEffect`
+    pub fn tick(&mut self) -> bool {
+        if self.duration > 0 {
+            self.duration -= 1;
+            return self.duration == 0;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specs_parse_into_effects() {
+        let burning = OngoingEffect::parse("Burning: 1d6 fire start 3").unwrap();
+        assert_eq!(
+            (burning.dice.as_str(), burning.damage_type, burning.duration),
+            ("1d6", DamageType::Fire, 3)
+        );
+        assert_eq!(
+            burning.describe(),
+            "Burning: 1d6 fire at start of turn (3 rounds)"
+        );
+
+        let regen = OngoingEffect::parse("Regeneration: 10 heal unless fire, acid").unwrap();
+        assert!(regen.healing);
+        assert_eq!(regen.timing, TurnBoundary::Start);
+        assert_eq!(
+            regen.suppressed_by,
+            vec![DamageType::Fire, DamageType::Acid]
+        );
+        assert_eq!(
+            regen.describe(),
+            "Regeneration: 10 healing at start of turn unless hit by fire or acid"
+        );
+
+        let mut heat = OngoingEffect::parse("Heat Metal: 2d8 fire end 2").unwrap();
+        assert_eq!(heat.timing, TurnBoundary::End);
+        assert!(!heat.tick());
+        assert!(heat.tick());
+
+        assert!(OngoingEffect::parse("1d6 fire").is_err());
+        assert!(OngoingEffect::parse("Burning:").is_err());
+        assert!(OngoingEffect::parse("Burning: 1d6 plasma").is_err());
+        assert!(OngoingEffect::parse("Regeneration: 10 heal unless").is_err());
+    }
+}
@@ -0,0 +1,380 @@
+//! Spell slots, pact slots and limited-use features such as Channel Divinity
+//! or a dragon's "Recharge 5–6" breath weapon.
+
+use serde::{Deserialize, Serialize};
+
+pub const MAX_SPELL_LEVEL: u8 = 9;
+
+/// When a feature's uses come back
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Reset {
+    ShortRest,
+    #[default]
+    LongRest,
+    /// Comes back at the start of the creature's turn on a d6 roll of at least this
+    Recharge(u8),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResourceKind {
+    /// Spell slots of one level, regained on a long rest
+    SpellSlot(u8),
+    /// Pact magic slots, all of one level and regained on a short rest
+    PactSlot(u8),
+    /// A named per-rest or per-day ability such as Channel Divinity
+    Feature { name: String, reset: Reset },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Resource {
+    pub kind: ResourceKind,
+    pub max: u32,
+    #[serde(default)]
+    pub used: u32,
+}
+
+impl Resource {
+    pub fn new(kind: ResourceKind, max: u32) -> Self {
+        Self { kind, max, used: 0 }
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.max.saturating_sub(self.used)
+    }
+
+    /// Uses one charge; false if none are left
+    pub fn spend(&mut self) -> bool {
+        if self.remaining() == 0 {
+            return false;
+        }
+        self.used += 1;
+        true
+    }
+
+    /// Gives back one charge; false if already full
+    pub fn restore(&mut self) -> bool {
+        if self.used == 0 {
+            return false;
+        }
+        self.used -= 1;
+        true
+    }
+
+    /// Pact slots, short-rest features and recharge abilities
+    pub fn resets_on_short_rest(&self) -> bool {
+        matches!(
+            self.kind,
+            ResourceKind::PactSlot(_)
+                | ResourceKind::Feature {
+                    reset: Reset::ShortRest | Reset::Recharge(_),
+                    ..
+                }
+        )
+    }
+
+    /// The lowest d6 roll that recharges this ability, if it recharges
+    pub fn recharge(&self) -> Option<u8> {
+        match self.kind {
+            ResourceKind::Feature {
+                reset: Reset::Recharge(min),
+                ..
+            } => Some(min),
+            _ => None,
+        }
+    }
+
+    /// "Level 3 slots", "Pact slots (level 3)" or the feature's name
+    pub fn label(&self) -> String {
+        match &self.kind {
+            ResourceKind::SpellSlot(level) => format!("Level {} slots", level),
+            ResourceKind::PactSlot(level) => format!("Pact slots (level {})", level),
+            ResourceKind::Feature { name, .. } => name.clone(),
+        }
+    }
+
+    /// The label plus a recharge range, e.g. "Fire Breath (Recharge 5–6)"
+    pub fn full_label(&self) -> String {
+        match self.recharge() {
+            Some(6) => format!("{} (Recharge 6)", self.label()),
+            Some(min) => format!("{} (Recharge {}–6)", self.label(), min),
+            None => self.label(),
+        }
+    }
+
+    /// One use, for log lines: "a level 3 slot", "a pact slot" or the feature's name
+    pub fn one_use(&self) -> String {
+        match &self.kind {
+            ResourceKind::SpellSlot(level) => format!("a level {} slot", level),
+            ResourceKind::PactSlot(_) => "a pact slot".to_string(),
+            ResourceKind::Feature { name, .. } => name.clone(),
+        }
+    }
+
+    /// Short form for the initiative list: initials for multi-word names
+    fn abbreviation(&self) -> String {
+        match &self.kind {
+            ResourceKind::SpellSlot(level) => format!("L{}", level),
+            ResourceKind::PactSlot(_) => "Pact".to_string(),
+            ResourceKind::Feature { name, .. } => {
+                let words: Vec<&str> = name.split_whitespace().collect();
+                if words.len() > 1 {
+                    words
+                        .iter()
+                        .filter_map(|w| w.chars().next())
+                        .flat_map(char::to_uppercase)
+                        .collect()
+                } else {
+                    name.chars().take(4).collect()
+                }
+            }
+        }
+    }
+}
+
+/// Unspent copies, for templates and the encounter library
+pub fn fresh(resources: &[Resource]) -> Vec<Resource> {
+    resources
+        .iter()
+        .map(|r| Resource::new(r.kind.clone(), r.max))
+        .collect()
+}
+
+/// Compact "L1 3/4 L2 2/2 Pact 1/2 CD 0/1" summary for the combatant row
+pub fn summary(resources: &[Resource]) -> String {
+    resources
+        .iter()
+        .map(|r| format!("{} {}/{}", r.abbreviation(), r.remaining(), r.max))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Resets everything a rest brings back and returns the labels of what was restored.
+pub fn restore_on_rest(resources: &mut [Resource], long_rest: bool) -> Vec<String> {
+    resources
+        .iter_mut()
+        .filter(|r| r.used > 0 && (long_rest || r.resets_on_short_rest()))
+        .map(|r| {
+            r.used = 0;
+            r.label()
+        })
+        .collect()
+}
+
+/// Parses a spec such as `slots 4 3 2; pact 2x3; Channel Divinity 1/sr; Rage 3/lr`.
+///
+/// `slots` lists spell slots from level 1 up, `pact` is count x slot level and
+/// anything else is a named feature with `/sr` (short rest), `/lr` or `/day`
+/// (long rest, the default), or `/r5` or `/5-6` (recharge on a 5 or 6).
+pub fn parse_resources(spec: &str) -> Result<Vec<Resource>, String> {
+    let mut resources = Vec::new();
+    for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        let (head, rest) = entry.split_once(' ').unwrap_or((entry, ""));
+        match head.to_lowercase().as_str() {
+            "slots" => {
+                for (i, count) in rest.split_whitespace().enumerate() {
+                    let level = i as u8 + 1;
+                    if level > MAX_SPELL_LEVEL {
+                        return Err(format!("At most {} spell levels", MAX_SPELL_LEVEL));
+                    }
+                    let max = parse_count(count, entry)?;
+                    if max > 0 {
+                        resources.push(Resource::new(ResourceKind::SpellSlot(level), max));
+                    }
+                }
+            }
+            "pact" => {
+                let (count, level) = rest
+                    .trim()
+                    .split_once('x')
+                    .ok_or_else(|| format!("Pact slots look like 'pact 2x3', not '{}'", entry))?;
+                let level = parse_count(level, entry)?;
+                if !(1..=MAX_SPELL_LEVEL as u32).contains(&level) {
+                    return Err(format!("Invalid pact slot level in '{}'", entry));
+                }
+                resources.push(Resource::new(
+                    ResourceKind::PactSlot(level as u8),
+                    parse_count(count, entry)?,
+                ));
+            }
+            _ => {
+                let (name, uses) = entry
+                    .rsplit_once(' ')
+                    .ok_or_else(|| format!("'{}' needs a number of uses", entry))?;
+                let (uses, reset) = match uses.split_once('/') {
+                    Some((uses, "sr")) => (uses, Reset::ShortRest),
+                    Some((uses, "lr" | "day")) => (uses, Reset::LongRest),
+                    Some((uses, reset)) => match recharge_threshold(reset) {
+                        Some(min) => (uses, Reset::Recharge(min)),
+                        None => {
+                            return Err(format!(
+                                "Unknown reset in '{}' (sr, lr, day or a recharge like r5)",
+                                entry
+                            ));
+                        }
+                    },
+                    None => (uses, Reset::LongRest),
+                };
+                resources.push(Resource::new(
+                    ResourceKind::Feature {
+                        name: name.trim().to_string(),
+                        reset,
+                    },
+                    parse_count(uses, entry)?,
+                ));
+            }
+        }
+    }
+    Ok(resources)
+}
+
+/// "r5", "5-6" or "5–6" (and "r6" or "6") to the lowest recharging roll
+fn recharge_threshold(text: &str) -> Option<u8> {
+    let text = text.strip_prefix('r').unwrap_or(text);
+    let min = text
+        .strip_suffix("-6")
+        .or_else(|| text.strip_suffix("–6"))
+        .unwrap_or(text);
+    min.parse().ok().filter(|min| (2..=6).contains(min))
+}
+
+/// Recharge abilities named in a stat block, e.g. "Fire Breath (Recharge 5–6)"
+pub fn recharge_abilities(names: &[&str]) -> Vec<Resource> {
+    names
+        .iter()
+        .filter_map(|name| {
+            let (base, rest) = name.split_once('(')?;
+            let range = rest.trim().strip_suffix(')')?;
+            let range = range
+                .strip_prefix("Recharge ")
+                .or_else(|| range.strip_prefix("recharge "))?;
+            Some(Resource::new(
+                ResourceKind::Feature {
+                    name: base.trim().to_string(),
+                    reset: Reset::Recharge(recharge_threshold(range.trim())?),
+                },
+                1,
+            ))
+        })
+        .collect()
+}
+
+fn parse_count(value: &str, entry: &str) -> Result<u32, String> {
+    value
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid number '{}' in '{}'", value.trim(), entry))
+}
+
+/// The spec `parse_resources` reads back, used to prefill the editor
+pub fn format_resources(resources: &[Resource]) -> String {
+    let mut parts = Vec::new();
+    let mut slots = [0; MAX_SPELL_LEVEL as usize];
+    for r in resources {
+        match &r.kind {
+            ResourceKind::SpellSlot(level) => slots[*level as usize - 1] = r.max,
+            ResourceKind::PactSlot(level) => parts.push(format!("pact {}x{}", r.max, level)),
+            ResourceKind::Feature { name, reset } => parts.push(format!(
+                "{} {}/{}",
+                name,
+                r.max,
+                match reset {
+                    Reset::ShortRest => "sr".to_string(),
+                    Reset::LongRest => "lr".to_string(),
+                    Reset::Recharge(min) => format!("r{}", min),
+                }
+            )),
+        }
+    }
+    if let Some(highest) = slots.iter().rposition(|&n| n > 0) {
+        let counts: Vec<String> = slots[..=highest].iter().map(u32::to_string).collect();
+        parts.insert(0, format!("slots {}", counts.join(" ")));
+    }
+    parts.join("; ")
+}
+
+/// Keeps uses already spent on resources that survive an edit
+pub fn carry_over_used(resources: &mut [Resource], previous: &[Resource]) {
+    for r in resources {
+        if let Some(old) = previous.iter().find(|old| old.kind == r.kind) {
+            r.used = old.used.min(r.max);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feature(name: &str, reset: Reset) -> ResourceKind {
+        ResourceKind::Feature {
+            name: name.to_string(),
+            reset,
+        }
+    }
+
+    #[test]
+    fn specs_round_trip() {
+        let spec = "slots 4 3 0 2; pact 2x3; Channel Divinity 1/sr; Rage 3/day; Breath 1/5-6";
+        let resources = parse_resources(spec).unwrap();
+        assert_eq!(
+            resources
+                .iter()
+                .map(|r| (&r.kind, r.max))
+                .collect::<Vec<_>>(),
+            vec![
+                (&ResourceKind::SpellSlot(1), 4),
+                (&ResourceKind::SpellSlot(2), 3),
+                (&ResourceKind::SpellSlot(4), 2),
+                (&ResourceKind::PactSlot(3), 2),
+                (&feature("Channel Divinity", Reset::ShortRest), 1),
+                (&feature("Rage", Reset::LongRest), 3),
+                (&feature("Breath", Reset::Recharge(5)), 1),
+            ]
+        );
+        assert_eq!(
+            format_resources(&resources),
+            "slots 4 3 0 2; pact 2x3; Channel Divinity 1/sr; Rage 3/lr; Breath 1/r5"
+        );
+        assert_eq!(resources[6].full_label(), "Breath (Recharge 5–6)");
+        assert_eq!(parse_resources("  "), Ok(vec![]));
+        assert!(parse_resources("pact 2").is_err());
+        assert!(parse_resources("Rage").is_err());
+        assert!(parse_resources("Rage 2/week").is_err());
+        assert!(parse_resources("Breath 1/r7").is_err());
+        assert!(parse_resources("slots 1 1 1 1 1 1 1 1 1 1").is_err());
+    }
+
+    #[test]
+    fn spending_and_rests() {
+        let mut resources = parse_resources("slots 2; pact 1x1; Channel Divinity 1/sr").unwrap();
+        for r in &mut resources {
+            assert!(r.spend());
+        }
+        assert!(!resources[1].spend());
+        assert_eq!(summary(&resources), "L1 1/2 Pact 0/1 CD 0/1");
+
+        let restored = restore_on_rest(&mut resources, false);
+        assert_eq!(restored, vec!["Pact slots (level 1)", "Channel Divinity"]);
+        assert_eq!(resources[0].remaining(), 1);
+        assert_eq!(restore_on_rest(&mut resources, true), vec!["Level 1 slots"]);
+        assert!(!resources[0].restore());
+
+        let recharging =
+            recharge_abilities(&["Bite", "Fire Breath (Recharge 5–6)", "Spit (Recharge 6)"]);
+        assert_eq!(
+            recharging
+                .iter()
+                .map(|r| (r.label(), r.recharge()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Fire Breath".to_string(), Some(5)),
+                ("Spit".to_string(), Some(6))
+            ]
+        );
+
+        let mut edited = parse_resources("slots 3 1").unwrap();
+        resources[0].used = 2;
+        carry_over_used(&mut edited, &resources);
+        assert_eq!((edited[0].used, edited[1].used), (2, 0));
+    }
+}
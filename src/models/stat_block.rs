@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Ability {
+    Str,
+    Dex,
+    Con,
+    Int,
+    Wis,
+    Cha,
+}
+
+impl Ability {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ability::Str => "STR",
+            Ability::Dex => "DEX",
+            Ability::Con => "CON",
+            Ability::Int => "INT",
+            Ability::Wis => "WIS",
+            Ability::Cha => "CHA",
+        }
+    }
+
+    /// Case-insensitive lookup by abbreviation, e.g. "wis" or "WIS"
+    pub fn parse(name: &str) -> Option<Ability> {
+        let name = name.trim();
+        Self::all()
+            .into_iter()
+            .find(|a| a.as_str().eq_ignore_ascii_case(name))
+    }
+
+    pub fn all() -> [Ability; 6] {
+        [
+            Ability::Str,
+            Ability::Dex,
+            Ability::Con,
+            Ability::Int,
+            Ability::Wis,
+            Ability::Cha,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbilityScores {
+    pub strength: i32,
+    pub dexterity: i32,
+    pub constitution: i32,
+    pub intelligence: i32,
+    pub wisdom: i32,
+    pub charisma: i32,
+}
+
+impl AbilityScores {
+    pub fn score(&self, ability: Ability) -> i32 {
+        match ability {
+            Ability::Str => self.strength,
+            Ability::Dex => self.dexterity,
+            Ability::Con => self.constitution,
+            Ability::Int => self.intelligence,
+            Ability::Wis => self.wisdom,
+            Ability::Cha => self.charisma,
+        }
+    }
+
+    /// Standard 5e modifier, rounding down: 8-9 → -1, 10-11 → 0
+    pub fn modifier(&self, ability: Ability) -> i32 {
+        (self.score(ability) - 10).div_euclid(2)
+    }
+}
+
+impl Default for AbilityScores {
+    fn default() -> Self {
+        Self {
+            strength: 10,
+            dexterity: 10,
+            constitution: 10,
+            intelligence: 10,
+            wisdom: 10,
+            charisma: 10,
+        }
+    }
+}
+
+/// A named trait, action or reaction with its rules text
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct StatBlockEntry {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Monster stat block carried by templates and copied onto combatants.
+/// Every field is optional in JSON so partial blocks load.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct StatBlock {
+    #[serde(default)]
+    pub size: String,
+    /// Lowercase creature type, e.g. "dragon" or "humanoid"
+    #[serde(default)]
+    pub creature_type: String,
+    /// Subtypes and other labels to filter on, e.g. "goblinoid"
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub ability_scores: AbilityScores,
+    /// Proficient saves only; other saves use the ability modifier
+    #[serde(default)]
+    pub saving_throws: BTreeMap<Ability, i32>,
+    #[serde(default)]
+    pub speed: String,
+    #[serde(default)]
+    pub senses: String,
+    /// Challenge rating as printed, e.g. "1/4" or "13"
+    #[serde(default)]
+    pub challenge_rating: String,
+    #[serde(default)]
+    pub xp: u32,
+    #[serde(default)]
+    pub traits: Vec<StatBlockEntry>,
+    #[serde(default)]
+    pub actions: Vec<StatBlockEntry>,
+    #[serde(default)]
+    pub reactions: Vec<StatBlockEntry>,
+    /// Fields this tracker doesn't use yet (languages, legendary actions...),
+    /// kept so imported monsters round-trip through `templates.json`
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl StatBlock {
+    /// Saving throw bonus: the listed proficient save, otherwise the ability modifier
+    pub fn save_modifier(&self, ability: Ability) -> i32 {
+        self.saving_throws
+            .get(&ability)
+            .copied()
+            .unwrap_or_else(|| self.ability_scores.modifier(ability))
+    }
+}
+
+/// Numeric value of a printed challenge rating, so "1/4" compares below "1"
+pub fn challenge_rating_value(challenge_rating: &str) -> Option<f64> {
+    match challenge_rating.trim().split_once('/') {
+        Some((num, den)) => {
+            let den: f64 = den.trim().parse().ok()?;
+            (den != 0.0).then_some(num.trim().parse::<f64>().ok()? / den)
+        }
+        None => challenge_rating.trim().parse().ok(),
+    }
+}
+
+/// Experience points for a challenge rating as printed ("1/4", "5"), from the DMG table
+pub fn xp_for_challenge_rating(challenge_rating: &str) -> Option<u32> {
+    let xp = match challenge_rating.trim() {
+        "0" => 10,
+        "1/8" => 25,
+        "1/4" => 50,
+        "1/2" => 100,
+        cr => {
+            const BY_WHOLE_CR: [u32; 30] = [
+                200, 450, 700, 1100, 1800, 2300, 2900, 3900, 5000, 5900, 7200, 8400, 10000, 11500,
+                13000, 15000, 18000, 20000, 22000, 25000, 33000, 41000, 50000, 62000, 75000, 90000,
+                105000, 120000, 135000, 155000,
+            ];
+            let whole: usize = cr.parse().ok()?;
+            *BY_WHOLE_CR.get(whole.checked_sub(1)?)?
+        }
+    };
+    Some(xp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_stat_block_json_uses_defaults() {
+        let block: StatBlock = serde_json::from_str(
+            r#"{"challenge_rating":"1/4","saving_throws":{"Dex":4},"actions":[{"name":"Scimitar"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(block.challenge_rating, "1/4");
+        assert_eq!(block.ability_scores, AbilityScores::default());
+        assert_eq!(block.saving_throws.get(&Ability::Dex), Some(&4));
+        assert_eq!(block.actions[0].name, "Scimitar");
+        assert!(block.actions[0].description.is_empty());
+    }
+
+    #[test]
+    fn ability_modifiers_round_down() {
+        let scores = AbilityScores {
+            strength: 8,
+            dexterity: 14,
+            constitution: 1,
+            intelligence: 11,
+            wisdom: 9,
+            charisma: 30,
+        };
+        assert_eq!(scores.modifier(Ability::Str), -1);
+        assert_eq!(scores.modifier(Ability::Dex), 2);
+        assert_eq!(scores.modifier(Ability::Con), -5);
+        assert_eq!(scores.modifier(Ability::Int), 0);
+        assert_eq!(scores.modifier(Ability::Wis), -1);
+        assert_eq!(scores.modifier(Ability::Cha), 10);
+
+        // Saves fall back to the modifier unless the stat block lists them
+        let mut block = StatBlock {
+            ability_scores: scores,
+            ..Default::default()
+        };
+        block.saving_throws.insert(Ability::Wis, 3);
+        assert_eq!(block.save_modifier(Ability::Wis), 3);
+        assert_eq!(block.save_modifier(Ability::Dex), 2);
+        assert_eq!(Ability::parse("wis"), Some(Ability::Wis));
+        assert_eq!(Ability::parse("luck"), None);
+    }
+
+    #[test]
+    fn xp_follows_the_challenge_rating_table() {
+        assert_eq!(xp_for_challenge_rating("0"), Some(10));
+        assert_eq!(xp_for_challenge_rating("1/4"), Some(50));
+        assert_eq!(xp_for_challenge_rating("1"), Some(200));
+        assert_eq!(xp_for_challenge_rating("13"), Some(10000));
+        assert_eq!(xp_for_challenge_rating("30"), Some(155000));
+        assert_eq!(xp_for_challenge_rating("31"), None);
+        assert_eq!(xp_for_challenge_rating("3/4"), None);
+    }
+
+    #[test]
+    fn challenge_rating_values_order_fractions_first() {
+        assert_eq!(challenge_rating_value("1/8"), Some(0.125));
+        assert_eq!(challenge_rating_value(" 1/2 "), Some(0.5));
+        assert_eq!(challenge_rating_value("17"), Some(17.0));
+        assert_eq!(challenge_rating_value("1/0"), None);
+        assert_eq!(challenge_rating_value(""), None);
+    }
+}
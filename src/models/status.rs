@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::Ability;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConditionType {
     Blinded,
@@ -123,11 +125,27 @@ impl ConditionType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A save the bearer repeats at the end of each of its turns, ending the effect on a success
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepeatSave {
+    pub ability: Ability,
+    pub dc: i32,
+}
+
+impl RepeatSave {
+    /// e.g. "WIS DC 14"
+    pub fn label(&self) -> String {
+        format!("{} DC {}", self.ability.as_str(), self.dc)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StatusEffect {
     pub condition: ConditionType,
     pub duration: i32,
     pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub save: Option<RepeatSave>,
 }
 
 impl StatusEffect {
@@ -136,9 +154,14 @@ impl StatusEffect {
             condition,
             duration,
             source,
+            save: None,
         }
     }
 
+    pub fn with_save(self, save: Option<RepeatSave>) -> Self {
+        Self { save, ..self }
+    }
+
     pub fn decrement_duration(&mut self) {
         if self.duration > 0 {
             self.duration -= 1;
@@ -1,9 +1,14 @@
 use crate::app::{
-    AddConcentrationState, App, ConcentrationCheckState, InputMode, SelectionState,
-    StatusSelectionState,
+    AddConcentrationState, App, AreaDamageState, AttackState, ConcentrationCheckState,
+    DamageTypeState, EditDefensesState, EditNotesState, InputMode, RepeatSaveState, ResourceState,
+    SelectionState, SetLegendaryState, StatusSelectionState,
 };
+use crate::dice::{DiceExpr, is_dice_char};
+use crate::models::{DamageType, DefenseKind, SaveOutcome, resources};
 use crossterm::event::{KeyCode, KeyEvent};
 
+use super::entry::roll_and_apply;
+
 pub(super) fn handle_selection_mode<F>(app: &mut App, key: KeyEvent, on_confirm: F)
 where
     F: FnOnce(&mut App, usize, String),
@@ -11,6 +16,7 @@ where
     let (selected_index, mut input, allow_empty_confirm) = match &app.input_mode {
         InputMode::DealingDamage(state) => (state.selected_index, state.input.clone(), false),
         InputMode::Healing(state) => (state.selected_index, state.input.clone(), false),
+        InputMode::AddingStatus(state) => (state.selected_index, state.input.clone(), true),
         InputMode::RollingDeathSave(state) => (state.selected_index, state.input.clone(), false),
         InputMode::ConcentrationTarget(state) => (state.selected_index, state.input.clone(), true),
         InputMode::ClearingConcentration(state) => {
@@ -19,6 +25,31 @@ where
         InputMode::ClearingStatus(state) => (state.selected_index, state.input.clone(), true),
         InputMode::SavingTemplate(state) => (state.selected_index, state.input.clone(), true),
         InputMode::GrantingTempHp(state) => (state.selected_index, state.input.clone(), false),
+        InputMode::SettingExhaustion(state) => (state.selected_index, state.input.clone(), false),
+        InputMode::ResolvingTie(state) => (state.selected_index, state.input.clone(), true),
+        InputMode::UsingReaction(state) => (state.selected_index, state.input.clone(), true),
+        InputMode::SelectingLegendaryTarget(state) => {
+            (state.selected_index, state.input.clone(), true)
+        }
+        InputMode::SpendingLegendaryAction(state) => {
+            (state.selected_index, state.input.clone(), true)
+        }
+        InputMode::UsingLegendaryResistance(state) => {
+            (state.selected_index, state.input.clone(), true)
+        }
+        InputMode::TriggeringReadied(state) => (state.selected_index, state.input.clone(), true),
+        InputMode::SelectingDefensesTarget(state) => {
+            (state.selected_index, state.input.clone(), true)
+        }
+        InputMode::SelectingResourceTarget(state) => {
+            (state.selected_index, state.input.clone(), true)
+        }
+        InputMode::SelectingOngoingTarget(state) => {
+            (state.selected_index, state.input.clone(), true)
+        }
+        InputMode::ResumingDelayed(_, state) => (state.selected_index, state.input.clone(), true),
+        InputMode::PinningCombatant(state) => (state.selected_index, state.input.clone(), true),
+        InputMode::SelectingNotesTarget(state) => (state.selected_index, state.input.clone(), true),
         InputMode::SelectingStatusToClear(_) => return,
         InputMode::ActionMenu(_) | InputMode::CombatantMenu(_) | InputMode::QuickReference(_) => {
             return;
@@ -26,38 +57,45 @@ where
         _ => return,
     };
 
+    // Lair/hazard/event entries are skipped by creature-only prompts
+    let selectable = app.selectable_indices();
+    let Some(selected_index) = app.effective_selection(selected_index) else {
+        app.cancel_input();
+        return;
+    };
+    let position = selectable
+        .iter()
+        .position(|&i| i == selected_index)
+        .unwrap_or(0);
+
     match key.code {
         KeyCode::Esc => app.cancel_input(),
         KeyCode::Up => {
-            let new_index = if selected_index > 0 {
-                selected_index - 1
+            let new_position = if position > 0 {
+                position - 1
             } else {
-                app.encounter.combatants.len().saturating_sub(1)
+                selectable.len() - 1
             };
-            update_selection_state(app, new_index, input);
+            update_selection_state(app, selectable[new_position], input);
         }
         KeyCode::Down => {
-            let new_index = if selected_index < app.encounter.combatants.len().saturating_sub(1) {
-                selected_index + 1
+            let new_position = if position + 1 < selectable.len() {
+                position + 1
             } else {
                 0
             };
-            update_selection_state(app, new_index, input);
+            update_selection_state(app, selectable[new_position], input);
         }
-        KeyCode::Enter => {
-            if allow_empty_confirm || !input.is_empty() {
-                on_confirm(app, selected_index, input);
-            }
+        KeyCode::Enter if allow_empty_confirm || !input.is_empty() => {
+            on_confirm(app, selected_index, input);
         }
         KeyCode::Backspace => {
             input.pop();
             update_selection_state(app, selected_index, input);
         }
-        KeyCode::Char(c) => {
-            if c.is_ascii_digit() {
-                input.push(c);
-                update_selection_state(app, selected_index, input);
-            }
+        KeyCode::Char(c) if is_dice_char(c) => {
+            input.push(c);
+            update_selection_state(app, selected_index, input);
         }
         _ => {}
     }
@@ -86,7 +124,22 @@ pub(super) fn update_selection_state(app: &mut App, index: usize, input: String)
         InputMode::SelectingTemplate(_) => InputMode::SelectingTemplate(new_state),
         InputMode::SavingTemplate(_) => InputMode::SavingTemplate(new_state),
         InputMode::GrantingTempHp(_) => InputMode::GrantingTempHp(new_state),
+        InputMode::SettingExhaustion(_) => InputMode::SettingExhaustion(new_state),
         InputMode::Removing(_) => InputMode::Removing(new_state),
+        InputMode::ResolvingTie(_) => InputMode::ResolvingTie(new_state),
+        InputMode::UsingReaction(_) => InputMode::UsingReaction(new_state),
+        InputMode::SelectingLegendaryTarget(_) => InputMode::SelectingLegendaryTarget(new_state),
+        InputMode::SpendingLegendaryAction(_) => InputMode::SpendingLegendaryAction(new_state),
+        InputMode::UsingLegendaryResistance(_) => InputMode::UsingLegendaryResistance(new_state),
+        InputMode::TriggeringReadied(_) => InputMode::TriggeringReadied(new_state),
+        InputMode::SelectingDefensesTarget(_) => InputMode::SelectingDefensesTarget(new_state),
+        InputMode::SelectingResourceTarget(_) => InputMode::SelectingResourceTarget(new_state),
+        InputMode::SelectingOngoingTarget(_) => InputMode::SelectingOngoingTarget(new_state),
+        InputMode::ResumingDelayed(delayed_index, _) => {
+            InputMode::ResumingDelayed(delayed_index, new_state)
+        }
+        InputMode::PinningCombatant(_) => InputMode::PinningCombatant(new_state),
+        InputMode::SelectingNotesTarget(_) => InputMode::SelectingNotesTarget(new_state),
         _ => app.input_mode.clone(),
     };
 }
@@ -123,10 +176,8 @@ pub(super) fn handle_add_concentration_mode(
         KeyCode::Char(c) => {
             match state.step {
                 0 => state.spell_name.push(c),
-                1 => {
-                    if c.is_ascii_digit() || c == '-' {
-                        state.con_mod.push(c);
-                    }
+                1 if c.is_ascii_digit() || c == '-' => {
+                    state.con_mod.push(c);
                 }
                 _ => {}
             }
@@ -149,23 +200,457 @@ pub(super) fn handle_concentration_check_mode(
             app.input_mode =
                 InputMode::ConcentrationCheck(ConcentrationCheckState { input, ..state });
         }
+        KeyCode::Char(c) if is_dice_char(c) => {
+            input.push(c);
+            app.input_mode =
+                InputMode::ConcentrationCheck(ConcentrationCheckState { input, ..state });
+        }
+        KeyCode::Enter if !input.is_empty() => {
+            roll_and_apply(app, &input, "Concentration save", |app, total| {
+                app.complete_concentration_check(state.clone(), total)
+            });
+        }
+        _ => {}
+    }
+}
+
+pub(super) fn handle_repeat_save_mode(app: &mut App, key: KeyEvent, state: RepeatSaveState) {
+    let mut input = state.input.clone();
+    match key.code {
+        KeyCode::Esc => app.cancel_input(),
+        KeyCode::Backspace => {
+            input.pop();
+            app.input_mode = InputMode::RepeatSave(RepeatSaveState { input, ..state });
+        }
+        KeyCode::Char(c) if is_dice_char(c) => {
+            input.push(c);
+            app.input_mode = InputMode::RepeatSave(RepeatSaveState { input, ..state });
+        }
+        KeyCode::Enter if !input.is_empty() => {
+            let purpose = format!("{} save", state.save.ability.as_str());
+            roll_and_apply(app, &input, &purpose, |app, total| {
+                app.complete_repeat_save(state.clone(), total)
+            });
+        }
+        _ => {}
+    }
+}
+
+pub(super) fn handle_set_legendary_mode(app: &mut App, key: KeyEvent, state: SetLegendaryState) {
+    let mut state = state;
+    match key.code {
+        KeyCode::Esc => app.cancel_input(),
+        KeyCode::Enter => {
+            if state.step < 1 {
+                state.step += 1;
+                app.input_mode = InputMode::SettingLegendary(state);
+            } else if let Err(e) = app.complete_set_legendary(state) {
+                app.set_message(e);
+                app.input_mode = InputMode::Normal;
+            }
+        }
+        KeyCode::Backspace => {
+            match state.step {
+                0 => {
+                    state.actions.pop();
+                }
+                1 => {
+                    state.resistances.pop();
+                }
+                _ => {}
+            }
+            app.input_mode = InputMode::SettingLegendary(state);
+        }
+        KeyCode::Char(c) if c.is_ascii_digit() => {
+            match state.step {
+                0 => state.actions.push(c),
+                1 => state.resistances.push(c),
+                _ => {}
+            }
+            app.input_mode = InputMode::SettingLegendary(state);
+        }
+        _ => {}
+    }
+}
+
+pub(super) fn handle_ready_action_mode(app: &mut App, key: KeyEvent, trigger: String) {
+    let mut trigger = trigger;
+    match key.code {
+        KeyCode::Esc => app.cancel_input(),
+        KeyCode::Enter => {
+            if let Err(e) = app.complete_ready_action(trigger) {
+                app.set_message(e);
+            }
+        }
+        KeyCode::Backspace => {
+            trigger.pop();
+            app.input_mode = InputMode::ReadyingAction(trigger);
+        }
         KeyCode::Char(c) => {
-            if c.is_ascii_digit() {
-                input.push(c);
-                app.input_mode =
-                    InputMode::ConcentrationCheck(ConcentrationCheckState { input, ..state });
+            trigger.push(c);
+            app.input_mode = InputMode::ReadyingAction(trigger);
+        }
+        _ => {}
+    }
+}
+
+pub(super) fn handle_edit_notes_mode(app: &mut App, key: KeyEvent, state: EditNotesState) {
+    let mut state = state;
+    match key.code {
+        KeyCode::Esc => app.cancel_input(),
+        KeyCode::Enter => {
+            if let Err(e) = app.complete_edit_notes(state.combatant_index, state.input) {
+                app.set_message(e);
+                app.input_mode = InputMode::Normal;
             }
         }
+        KeyCode::Backspace => {
+            state.input.pop();
+            app.input_mode = InputMode::EditingNotes(state);
+        }
+        KeyCode::Char(c) => {
+            state.input.push(c);
+            app.input_mode = InputMode::EditingNotes(state);
+        }
+        _ => {}
+    }
+}
+
+pub(super) fn handle_choosing_delayed_mode(app: &mut App, key: KeyEvent, selected: usize) {
+    let total = app.encounter.delayed.len();
+    if total == 0 {
+        app.cancel_input();
+        return;
+    }
+    match key.code {
+        KeyCode::Esc => app.cancel_input(),
+        KeyCode::Up => {
+            let new_idx = if selected > 0 {
+                selected - 1
+            } else {
+                total - 1
+            };
+            app.input_mode = InputMode::ChoosingDelayed(new_idx);
+        }
+        KeyCode::Down => {
+            let new_idx = if selected + 1 < total {
+                selected + 1
+            } else {
+                0
+            };
+            app.input_mode = InputMode::ChoosingDelayed(new_idx);
+        }
+        KeyCode::Enter => app.choose_delayed(selected.min(total - 1)),
+        _ => {}
+    }
+}
+
+pub(super) fn handle_damage_type_mode(app: &mut App, key: KeyEvent, state: DamageTypeState) {
+    let mut state = state;
+    let total = DamageType::all().len();
+    match key.code {
+        KeyCode::Esc => app.cancel_input(),
+        KeyCode::Up => {
+            state.selected_index = if state.selected_index > 0 {
+                state.selected_index - 1
+            } else {
+                total - 1
+            };
+            app.input_mode = InputMode::ChoosingDamageType(state);
+        }
+        KeyCode::Down => {
+            state.selected_index = (state.selected_index + 1) % total;
+            app.input_mode = InputMode::ChoosingDamageType(state);
+        }
         KeyCode::Enter => {
-            if let Ok(total) = input.parse::<i32>() {
-                if let Err(e) = app.complete_concentration_check(state.clone(), total) {
-                    app.set_message(e);
+            let damage_type = DamageType::all()[state.selected_index.min(total - 1)];
+            let idx = state.combatant_index;
+            let (amount, purpose) = match DiceExpr::parse(&state.amount) {
+                Ok(expr) if state.critical => (expr.critical().to_string(), "Critical damage"),
+                _ => (state.amount.clone(), "Damage"),
+            };
+            let source = state.source;
+            roll_and_apply(app, &amount, purpose, |app, total| {
+                app.complete_deal_damage(idx, total.max(0), damage_type, source)
+            });
+        }
+        _ => {}
+    }
+}
+
+pub(super) fn handle_edit_defenses_mode(app: &mut App, key: KeyEvent, state: EditDefensesState) {
+    let mut state = state;
+    let types = DamageType::defendable();
+    let kind = match key.code {
+        KeyCode::Esc | KeyCode::Enter => {
+            app.input_mode = InputMode::Normal;
+            return;
+        }
+        KeyCode::Up => {
+            state.selected_index = if state.selected_index > 0 {
+                state.selected_index - 1
+            } else {
+                types.len() - 1
+            };
+            app.input_mode = InputMode::EditingDefenses(state);
+            return;
+        }
+        KeyCode::Down => {
+            state.selected_index = (state.selected_index + 1) % types.len();
+            app.input_mode = InputMode::EditingDefenses(state);
+            return;
+        }
+        KeyCode::Char('r') => DefenseKind::Resistance,
+        KeyCode::Char('i') => DefenseKind::Immunity,
+        KeyCode::Char('v') => DefenseKind::Vulnerability,
+        _ => return,
+    };
+
+    let damage_type = types[state.selected_index.min(types.len() - 1)];
+    if let Err(e) = app.toggle_defense(state.combatant_index, kind, damage_type) {
+        app.set_message(e);
+        app.input_mode = InputMode::Normal;
+    }
+}
+
+pub(super) fn handle_area_damage_mode(app: &mut App, key: KeyEvent, state: AreaDamageState) {
+    let mut state = state;
+    if key.code == KeyCode::Esc {
+        app.cancel_input();
+        return;
+    }
+
+    match state.step {
+        0 => {
+            let creatures = app.selectable_indices();
+            if creatures.is_empty() {
+                app.cancel_input();
+                return;
+            }
+            let position = creatures
+                .iter()
+                .position(|&i| i == state.cursor)
+                .unwrap_or(0);
+            let cursor = creatures[position];
+            let set_outcome = |state: &mut AreaDamageState, outcome: Option<SaveOutcome>| {
+                state.targets.retain(|(i, _)| *i != cursor);
+                if let Some(outcome) = outcome {
+                    state.targets.push((cursor, outcome));
+                    state.targets.sort_by_key(|(i, _)| *i);
+                }
+            };
+            match key.code {
+                KeyCode::Up => {
+                    state.cursor = creatures[(position + creatures.len() - 1) % creatures.len()];
                 }
+                KeyCode::Down => state.cursor = creatures[(position + 1) % creatures.len()],
+                KeyCode::Char(' ') => {
+                    let included = state.targets.iter().any(|(i, _)| *i == cursor);
+                    set_outcome(&mut state, (!included).then_some(SaveOutcome::Full));
+                }
+                KeyCode::Char('f') => set_outcome(&mut state, Some(SaveOutcome::Full)),
+                KeyCode::Char('h') => set_outcome(&mut state, Some(SaveOutcome::Half)),
+                KeyCode::Char('n') => set_outcome(&mut state, Some(SaveOutcome::None)),
+                KeyCode::Tab => {
+                    let outcome = state
+                        .targets
+                        .iter()
+                        .find(|(i, _)| *i == cursor)
+                        .map_or(SaveOutcome::Full, |(_, o)| o.next());
+                    set_outcome(&mut state, Some(outcome));
+                }
+                KeyCode::Char('a') => {
+                    // Toggle every creature at once
+                    if state.targets.len() == creatures.len() {
+                        state.targets.clear();
+                    } else {
+                        state.targets = creatures.iter().map(|&i| (i, SaveOutcome::Full)).collect();
+                    }
+                }
+                KeyCode::Enter => {
+                    if state.targets.is_empty() {
+                        app.set_message("Select at least one target (Space)".to_string());
+                        return;
+                    }
+                    state.step = 1;
+                }
+                _ => return,
+            }
+        }
+        1 => match key.code {
+            KeyCode::Backspace => {
+                state.amount.pop();
+            }
+            KeyCode::Char(c) if is_dice_char(c) => state.amount.push(c),
+            KeyCode::Enter if !state.amount.is_empty() => state.step = 2,
+            _ => return,
+        },
+        _ => {
+            let total = DamageType::all().len();
+            match key.code {
+                KeyCode::Up => state.type_index = (state.type_index + total - 1) % total,
+                KeyCode::Down => state.type_index = (state.type_index + 1) % total,
+                KeyCode::Enter => {
+                    let damage_type = DamageType::all()[state.type_index.min(total - 1)];
+                    let targets = state.targets.clone();
+                    roll_and_apply(app, &state.amount, "Area damage", |app, total| {
+                        app.complete_area_damage(&targets, total.max(0), damage_type)
+                    });
+                    return;
+                }
+                _ => return,
+            }
+        }
+    }
+    app.input_mode = InputMode::DealingAreaDamage(state);
+}
+
+pub(super) fn handle_attack_mode(app: &mut App, key: KeyEvent, state: AttackState) {
+    let mut state = state;
+    if key.code == KeyCode::Esc {
+        app.cancel_input();
+        return;
+    }
+
+    match state.step {
+        0 | 1 => {
+            let creatures = app.selectable_indices();
+            if creatures.is_empty() {
+                app.cancel_input();
+                return;
+            }
+            let current = if state.step == 0 {
+                state.attacker
             } else {
-                app.set_message("Invalid roll total".to_string());
-                app.input_mode = InputMode::Normal;
+                state.target
+            };
+            let position = creatures.iter().position(|&i| i == current).unwrap_or(0);
+            let moved = match key.code {
+                KeyCode::Up => creatures[(position + creatures.len() - 1) % creatures.len()],
+                KeyCode::Down => creatures[(position + 1) % creatures.len()],
+                KeyCode::Enter => {
+                    if state.step == 0 {
+                        state.attacker = creatures[position];
+                        // Default the target to someone other than the attacker
+                        state.target = creatures
+                            .iter()
+                            .copied()
+                            .find(|&i| i != state.attacker)
+                            .unwrap_or(state.attacker);
+                    } else {
+                        state.target = creatures[position];
+                    }
+                    state.step += 1;
+                    app.input_mode = InputMode::Attacking(state);
+                    return;
+                }
+                _ => return,
+            };
+            if state.step == 0 {
+                state.attacker = moved;
+            } else {
+                state.target = moved;
+            }
+        }
+        2 => match key.code {
+            KeyCode::Backspace => {
+                state.input.pop();
+            }
+            KeyCode::Char(c) if is_dice_char(c) => state.input.push(c),
+            KeyCode::Enter => {
+                if let Err(e) = app.complete_attack(state.attacker, state.target, &state.input) {
+                    app.set_message(e);
+                }
+                return;
             }
+            _ => return,
+        },
+        _ => match key.code {
+            KeyCode::Backspace => {
+                state.input.pop();
+            }
+            KeyCode::Char(c) if is_dice_char(c) => state.input.push(c),
+            KeyCode::Enter if !state.input.is_empty() => {
+                app.input_mode = InputMode::ChoosingDamageType(DamageTypeState {
+                    combatant_index: state.target,
+                    amount: state.input,
+                    selected_index: 0,
+                    critical: state.critical,
+                    source: Some(state.attacker),
+                });
+                return;
+            }
+            _ => return,
+        },
+    }
+    app.input_mode = InputMode::Attacking(state);
+}
+
+pub(super) fn handle_resources_mode(app: &mut App, key: KeyEvent, state: ResourceState) {
+    let mut state = state;
+    let Some(combatant) = app.encounter.combatants.get(state.combatant_index) else {
+        app.cancel_input();
+        return;
+    };
+    let count = combatant.resources.len();
+    let result = match key.code {
+        KeyCode::Esc => {
+            app.cancel_input();
+            return;
+        }
+        KeyCode::Char('e') => {
+            let spec = resources::format_resources(&combatant.resources);
+            app.input_mode = InputMode::EditingResources(state.combatant_index, spec);
+            return;
+        }
+        KeyCode::Up if count > 0 => {
+            state.selected_index = (state.selected_index + count - 1) % count;
+            Ok(())
+        }
+        KeyCode::Down if count > 0 => {
+            state.selected_index = (state.selected_index + 1) % count;
+            Ok(())
+        }
+        KeyCode::Enter | KeyCode::Char('-') => {
+            app.adjust_resource(state.combatant_index, state.selected_index, true)
+        }
+        KeyCode::Char('+') => {
+            app.adjust_resource(state.combatant_index, state.selected_index, false)
+        }
+        _ => Ok(()),
+    };
+    if let Err(e) = result {
+        app.set_message(e);
+    }
+    app.input_mode = InputMode::ManagingResources(state);
+}
+
+pub(super) fn handle_edit_resources_mode(
+    app: &mut App,
+    key: KeyEvent,
+    index: usize,
+    input: String,
+) {
+    let mut input = input;
+    match key.code {
+        KeyCode::Esc => {
+            app.input_mode = InputMode::ManagingResources(ResourceState {
+                combatant_index: index,
+                selected_index: 0,
+            });
+            return;
+        }
+        KeyCode::Enter => {
+            if let Err(e) = app.complete_set_resources(index, &input) {
+                app.set_message(e);
+            }
+            return;
+        }
+        KeyCode::Backspace => {
+            input.pop();
         }
+        KeyCode::Char(c) => input.push(c),
         _ => {}
     }
+    app.input_mode = InputMode::EditingResources(index, input);
 }
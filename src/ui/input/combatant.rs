@@ -1,16 +1,22 @@
 #![allow(clippy::collapsible_else_if)]
 
-use crate::app::{App, InputMode, SelectionState};
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::app::bestiary::search_bestiary;
+use crate::app::{AddTurnEntryState, App, BestiaryState, CopiesState, InputMode, SelectionState};
+use crate::dice::is_dice_char;
+use crate::models::CombatantKind;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 use super::combat::update_selection_state;
 
+/// Upper bound for "add N copies" from the bestiary browser and templates
+const MAX_COPIES: u32 = 20;
+
 pub(super) fn handle_add_combatant_mode(app: &mut App, key: KeyEvent) {
     if let InputMode::AddingCombatant(mut state) = app.input_mode.clone() {
         match key.code {
             KeyCode::Esc => app.cancel_input(),
             KeyCode::Enter => {
-                if state.step < 4 {
+                if state.step < 5 {
                     state.step += 1;
                     app.input_mode = InputMode::AddingCombatant(state);
                 } else {
@@ -26,15 +32,18 @@ pub(super) fn handle_add_combatant_mode(app: &mut App, key: KeyEvent) {
                         state.name.pop();
                     }
                     1 => {
-                        state.initiative.pop();
+                        state.initiative_modifier.pop();
                     }
                     2 => {
-                        state.hp.pop();
+                        state.initiative.pop();
                     }
                     3 => {
-                        state.ac.pop();
+                        state.hp.pop();
                     }
                     4 => {
+                        state.ac.pop();
+                    }
+                    5 => {
                         state.is_player.pop();
                     }
                     _ => {}
@@ -44,26 +53,21 @@ pub(super) fn handle_add_combatant_mode(app: &mut App, key: KeyEvent) {
             KeyCode::Char(c) => {
                 match state.step {
                     0 => state.name.push(c),
-                    1 => {
-                        if c.is_ascii_digit() || c == '-' {
-                            state.initiative.push(c);
-                        }
+                    1 if c.is_ascii_digit() || c == '+' || c == '-' => {
+                        state.initiative_modifier.push(c);
                     }
-                    2 => {
-                        if c.is_ascii_digit() {
-                            state.hp.push(c);
-                        }
+                    2 if is_dice_char(c) => {
+                        state.initiative.push(c);
                     }
-                    3 => {
-                        if c.is_ascii_digit() {
-                            state.ac.push(c);
-                        }
+                    3 if c.is_ascii_digit() => {
+                        state.hp.push(c);
                     }
-                    4 => {
-                        if c == 'y' || c == 'n' || c == 'Y' || c == 'N' {
-                            state.is_player.clear();
-                            state.is_player.push(c);
-                        }
+                    4 if c.is_ascii_digit() => {
+                        state.ac.push(c);
+                    }
+                    5 if c == 'y' || c == 'n' || c == 'Y' || c == 'N' => {
+                        state.is_player.clear();
+                        state.is_player.push(c);
                     }
                     _ => {}
                 }
@@ -74,6 +78,58 @@ pub(super) fn handle_add_combatant_mode(app: &mut App, key: KeyEvent) {
     }
 }
 
+pub(super) fn handle_add_turn_entry_mode(app: &mut App, key: KeyEvent, state: AddTurnEntryState) {
+    let mut state = state;
+    let kind_count = CombatantKind::turn_entries().len();
+    match key.code {
+        KeyCode::Esc => app.cancel_input(),
+        KeyCode::Up if state.step == 0 => {
+            state.kind_index = (state.kind_index + kind_count - 1) % kind_count;
+            app.input_mode = InputMode::AddingTurnEntry(state);
+        }
+        KeyCode::Down if state.step == 0 => {
+            state.kind_index = (state.kind_index + 1) % kind_count;
+            app.input_mode = InputMode::AddingTurnEntry(state);
+        }
+        KeyCode::Enter => {
+            if state.step < 3 {
+                state.step += 1;
+                app.input_mode = InputMode::AddingTurnEntry(state);
+            } else if let Err(e) = app.complete_add_turn_entry(state) {
+                app.set_message(e);
+                app.input_mode = InputMode::Normal;
+            }
+        }
+        KeyCode::Backspace => {
+            match state.step {
+                1 => {
+                    state.name.pop();
+                }
+                2 => {
+                    state.initiative.pop();
+                }
+                3 => {
+                    state.reminder.pop();
+                }
+                _ => {}
+            }
+            app.input_mode = InputMode::AddingTurnEntry(state);
+        }
+        KeyCode::Char(c) => {
+            match state.step {
+                1 => state.name.push(c),
+                2 if c.is_ascii_digit() || c == '-' => {
+                    state.initiative.push(c);
+                }
+                3 => state.reminder.push(c),
+                _ => {}
+            }
+            app.input_mode = InputMode::AddingTurnEntry(state);
+        }
+        _ => {}
+    }
+}
+
 pub(super) fn handle_removing_mode(app: &mut App, key: KeyEvent) {
     if let InputMode::Removing(state) = &app.input_mode {
         let selected_index = state.selected_index;
@@ -148,6 +204,13 @@ pub(super) fn handle_template_selection_mode(app: &mut App, key: KeyEvent, state
                 app.input_mode = InputMode::Normal;
             }
         }
+        KeyCode::Tab => match filtered.get(selected_index) {
+            Some(&tpl_idx) => app.start_adding_copies(tpl_idx),
+            None => app.set_message("No matching template".to_string()),
+        },
+        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.toggle_hit_point_mode();
+        }
         KeyCode::Backspace => {
             input.pop();
             update_selection_state(app, selected_index, input);
@@ -160,3 +223,98 @@ pub(super) fn handle_template_selection_mode(app: &mut App, key: KeyEvent, state
         _ => {}
     }
 }
+
+pub(super) fn handle_bestiary_mode(app: &mut App, key: KeyEvent, state: BestiaryState) {
+    let mut state = state;
+    let results = search_bestiary(&app.bestiary, &state.query);
+    match key.code {
+        KeyCode::Esc => {
+            app.cancel_input();
+            return;
+        }
+        KeyCode::Up if !results.is_empty() => {
+            state.selected_index = if state.selected_index > 0 {
+                state.selected_index - 1
+            } else {
+                results.len() - 1
+            };
+        }
+        KeyCode::Down if !results.is_empty() => {
+            state.selected_index = if state.selected_index + 1 < results.len() {
+                state.selected_index + 1
+            } else {
+                0
+            };
+        }
+        KeyCode::Left => state.copies = state.copies.saturating_sub(1).max(1),
+        KeyCode::Right => state.copies = (state.copies + 1).min(MAX_COPIES),
+        KeyCode::Enter => {
+            match results.get(state.selected_index) {
+                Some(&index) => {
+                    if let Err(e) =
+                        app.add_bestiary_copies(index, state.copies, state.group_initiative)
+                    {
+                        app.set_message(e);
+                        app.input_mode = InputMode::Normal;
+                    }
+                }
+                None => app.set_message("No matching monster".to_string()),
+            }
+            return;
+        }
+        KeyCode::Tab => {
+            if let Some(&index) = results.get(state.selected_index) {
+                if let Err(e) = app.save_bestiary_template(index) {
+                    app.set_message(e);
+                }
+            }
+        }
+        KeyCode::Char('g') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            state.group_initiative = !state.group_initiative;
+        }
+        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.toggle_hit_point_mode();
+        }
+        KeyCode::Backspace => {
+            state.query.pop();
+            state.selected_index = 0;
+        }
+        KeyCode::Char(c) => {
+            state.query.push(c);
+            state.selected_index = 0;
+        }
+        _ => {}
+    }
+    app.input_mode = InputMode::BrowsingBestiary(state);
+}
+
+pub(super) fn handle_add_copies_mode(app: &mut App, key: KeyEvent, state: CopiesState) {
+    let mut state = state;
+    match key.code {
+        KeyCode::Esc => {
+            app.cancel_input();
+            return;
+        }
+        KeyCode::Enter => {
+            if let Err(e) = app.complete_add_copies(state) {
+                app.set_message(e);
+                app.input_mode = InputMode::Normal;
+            }
+            return;
+        }
+        KeyCode::Char('g') => state.group_initiative = !state.group_initiative,
+        KeyCode::Char('h') => app.toggle_hit_point_mode(),
+        KeyCode::Backspace => {
+            state.count.pop();
+        }
+        KeyCode::Char(c) if c.is_ascii_digit() => {
+            state.count.push(c);
+            let too_many = state.count.parse::<u32>().map_or(true, |n| n > MAX_COPIES);
+            if too_many {
+                state.count = MAX_COPIES.to_string();
+            }
+        }
+        _ => {}
+    }
+    app.input_mode = InputMode::AddingCopies(state);
+}
@@ -1,61 +1,63 @@
-use crate::app::{AddConcentrationState, App, InputMode, StatusSelectionState};
+use crate::app::{
+    AddConcentrationState, App, DamageTypeState, EditDefensesState, InputMode, ResourceState,
+    SetLegendaryState, StatusSelectionState,
+};
 use crossterm::event::KeyEvent;
 
 use super::combat::{
-    handle_add_concentration_mode, handle_concentration_check_mode, handle_selection_mode,
+    handle_add_concentration_mode, handle_area_damage_mode, handle_attack_mode,
+    handle_choosing_delayed_mode, handle_concentration_check_mode, handle_damage_type_mode,
+    handle_edit_defenses_mode, handle_edit_notes_mode, handle_edit_resources_mode,
+    handle_ready_action_mode, handle_repeat_save_mode, handle_resources_mode,
+    handle_selection_mode, handle_set_legendary_mode,
 };
 use super::combatant::{
-    handle_add_combatant_mode, handle_removing_mode, handle_template_selection_mode,
+    handle_add_combatant_mode, handle_add_copies_mode, handle_add_turn_entry_mode,
+    handle_bestiary_mode, handle_removing_mode, handle_template_selection_mode,
 };
 use super::menus::{
     handle_action_menu_mode, handle_combatant_menu_mode, handle_quick_reference_mode,
 };
 use super::normal::handle_normal_mode;
 use super::persistence::{
-    handle_confirm_library_load, handle_confirm_library_overwrite, handle_load_encounter_mode,
-    handle_loading_library_mode, handle_save_encounter_mode, handle_save_library_mode,
-    handle_setting_library_initiatives_mode,
+    handle_combat_summary_mode, handle_confirm_library_load, handle_confirm_library_overwrite,
+    handle_difficulty_mode, handle_edit_hit_dice_mode, handle_load_encounter_mode,
+    handle_loading_library_mode, handle_party_roster_mode, handle_rest_mode,
+    handle_save_encounter_mode, handle_save_library_mode, handle_setting_library_initiatives_mode,
 };
 use super::status::{
-    handle_clear_choice_mode, handle_condition_selection_mode, handle_status_clear_selection,
-    handle_status_selection_mode,
+    handle_add_ongoing_effect_mode, handle_clear_choice_mode, handle_condition_selection_mode,
+    handle_ongoing_effects_mode, handle_status_clear_selection, handle_status_selection_mode,
 };
 
 pub fn handle_key_event(app: &mut App, key: KeyEvent) {
     match app.input_mode.clone() {
         InputMode::Normal => handle_normal_mode(app, key),
         InputMode::AddingCombatant(_) => handle_add_combatant_mode(app, key),
+        InputMode::AddingTurnEntry(state) => handle_add_turn_entry_mode(app, key, state),
         InputMode::DealingDamage(_) => handle_selection_mode(app, key, |app, idx, input| {
-            if let Ok(damage) = input.parse::<i32>() {
-                if let Err(e) = app.complete_deal_damage(idx, damage) {
-                    app.set_message(e);
-                }
-            } else {
-                app.set_message("Invalid damage value!".to_string());
-                app.input_mode = InputMode::Normal;
-            }
+            app.input_mode = InputMode::ChoosingDamageType(DamageTypeState {
+                combatant_index: idx,
+                amount: input,
+                selected_index: 0,
+                critical: false,
+                source: app.acting_creature(),
+            });
         }),
+        InputMode::ChoosingDamageType(state) => handle_damage_type_mode(app, key, state),
+        InputMode::DealingAreaDamage(state) => handle_area_damage_mode(app, key, state),
+        InputMode::Attacking(state) => handle_attack_mode(app, key, state),
         InputMode::Healing(_) => handle_selection_mode(app, key, |app, idx, input| {
-            if let Ok(amount) = input.parse::<i32>() {
-                if let Err(e) = app.complete_heal(idx, amount) {
-                    app.set_message(e);
-                }
-            } else {
-                app.set_message("Invalid heal value!".to_string());
-                app.input_mode = InputMode::Normal;
-            }
+            roll_and_apply(app, &input, "Healing", |app, total| {
+                app.complete_heal(idx, total.max(0))
+            });
         }),
         InputMode::AddingStatus(_) => handle_status_selection_mode(app, key),
         InputMode::SelectingCondition(state) => handle_condition_selection_mode(app, key, state),
         InputMode::RollingDeathSave(_) => handle_selection_mode(app, key, |app, idx, input| {
-            if let Ok(roll) = input.parse::<i32>() {
-                if let Err(e) = app.complete_death_save_roll(idx, roll) {
-                    app.set_message(e);
-                }
-            } else {
-                app.set_message("Invalid roll value!".to_string());
-                app.input_mode = InputMode::Normal;
-            }
+            roll_and_apply(app, &input, "Death save", |app, total| {
+                app.complete_death_save_roll(idx, total)
+            });
         }),
         InputMode::ConcentrationTarget(_) => handle_selection_mode(app, key, |app, idx, _| {
             app.input_mode = InputMode::ApplyingConcentration(AddConcentrationState {
@@ -65,6 +67,7 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
         }),
         InputMode::ApplyingConcentration(state) => handle_add_concentration_mode(app, key, state),
         InputMode::ConcentrationCheck(state) => handle_concentration_check_mode(app, key, state),
+        InputMode::RepeatSave(state) => handle_repeat_save_mode(app, key, state),
         InputMode::ClearingConcentration(_) => handle_selection_mode(app, key, |app, idx, _| {
             if let Err(e) = app.complete_clear_concentration(idx) {
                 app.set_message(e);
@@ -93,17 +96,23 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
             }
         }),
         InputMode::GrantingTempHp(_) => handle_selection_mode(app, key, |app, idx, input| {
-            if let Ok(amount) = input.parse::<i32>() {
-                if let Err(e) = app.complete_grant_temp_hp(idx, amount) {
-                    app.set_message(e);
-                }
-            } else {
-                app.set_message("Invalid temp HP value!".to_string());
-                app.input_mode = InputMode::Normal;
+            roll_and_apply(app, &input, "Temp HP", |app, total| {
+                app.complete_grant_temp_hp(idx, total.max(0))
+            });
+        }),
+        InputMode::SettingExhaustion(_) => handle_selection_mode(app, key, |app, idx, input| {
+            let result = input
+                .parse::<u8>()
+                .map_err(|_| "Invalid exhaustion level".to_string())
+                .and_then(|level| app.complete_set_exhaustion(idx, level));
+            if let Err(e) = result {
+                app.set_message(e);
             }
         }),
         InputMode::SelectingStatusToClear(state) => handle_status_clear_selection(app, key, state),
         InputMode::SelectingTemplate(state) => handle_template_selection_mode(app, key, state),
+        InputMode::BrowsingBestiary(state) => handle_bestiary_mode(app, key, state),
+        InputMode::AddingCopies(state) => handle_add_copies_mode(app, key, state),
         InputMode::SavingTemplate(_) => handle_selection_mode(app, key, |app, idx, _| {
             if let Err(e) = app.save_template_from_combatant(idx) {
                 app.set_message(e);
@@ -113,10 +122,108 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
         InputMode::CombatantMenu(selected) => handle_combatant_menu_mode(app, key, selected),
         InputMode::QuickReference(selected) => handle_quick_reference_mode(app, key, selected),
         InputMode::Removing(_) => handle_removing_mode(app, key),
+        InputMode::ResolvingTie(_) => handle_selection_mode(app, key, |app, idx, _| {
+            if let Err(e) = app.complete_resolve_tie(idx) {
+                app.set_message(e);
+            }
+        }),
+        InputMode::UsingReaction(_) => handle_selection_mode(app, key, |app, idx, _| {
+            if let Err(e) = app.complete_use_reaction(idx) {
+                app.set_message(e);
+            }
+        }),
+        InputMode::SelectingLegendaryTarget(_) => handle_selection_mode(app, key, |app, idx, _| {
+            let combatant = &app.encounter.combatants[idx];
+            let max_of = |pool: Option<crate::models::LegendaryPool>| {
+                pool.map(|p| p.max.to_string()).unwrap_or_default()
+            };
+            app.input_mode = InputMode::SettingLegendary(SetLegendaryState {
+                combatant_index: idx,
+                step: 0,
+                actions: max_of(combatant.legendary_actions),
+                resistances: max_of(combatant.legendary_resistances),
+            });
+        }),
+        InputMode::SettingLegendary(state) => handle_set_legendary_mode(app, key, state),
+        InputMode::SpendingLegendaryAction(_) => {
+            handle_selection_mode(app, key, |app, idx, input| {
+                let cost = if input.is_empty() {
+                    Ok(1)
+                } else {
+                    input
+                        .parse::<u32>()
+                        .map_err(|_| "Invalid legendary action cost".to_string())
+                };
+                if let Err(e) = cost.and_then(|cost| app.complete_spend_legendary_action(idx, cost))
+                {
+                    app.set_message(e);
+                }
+            })
+        }
+        InputMode::UsingLegendaryResistance(_) => handle_selection_mode(app, key, |app, idx, _| {
+            if let Err(e) = app.complete_use_legendary_resistance(idx) {
+                app.set_message(e);
+            }
+        }),
+        InputMode::SelectingDefensesTarget(_) => handle_selection_mode(app, key, |app, idx, _| {
+            app.input_mode = InputMode::EditingDefenses(EditDefensesState {
+                combatant_index: idx,
+                selected_index: 0,
+            });
+        }),
+        InputMode::SelectingResourceTarget(_) => handle_selection_mode(app, key, |app, idx, _| {
+            app.input_mode = InputMode::ManagingResources(ResourceState {
+                combatant_index: idx,
+                selected_index: 0,
+            });
+        }),
+        InputMode::ManagingResources(state) => handle_resources_mode(app, key, state),
+        InputMode::EditingResources(index, input) => {
+            handle_edit_resources_mode(app, key, index, input)
+        }
+        InputMode::SelectingOngoingTarget(_) => handle_selection_mode(app, key, |app, idx, _| {
+            app.input_mode = InputMode::ManagingOngoingEffects(StatusSelectionState {
+                combatant_index: idx,
+                selected_status_index: 0,
+            });
+        }),
+        InputMode::ManagingOngoingEffects(state) => handle_ongoing_effects_mode(app, key, state),
+        InputMode::AddingOngoingEffect(index, input) => {
+            handle_add_ongoing_effect_mode(app, key, index, input)
+        }
+        InputMode::EditingDefenses(state) => handle_edit_defenses_mode(app, key, state),
+        InputMode::ReadyingAction(trigger) => handle_ready_action_mode(app, key, trigger),
+        InputMode::TriggeringReadied(_) => handle_selection_mode(app, key, |app, idx, _| {
+            if let Err(e) = app.complete_trigger_readied(idx) {
+                app.set_message(e);
+            }
+        }),
+        InputMode::ChoosingDelayed(selected) => handle_choosing_delayed_mode(app, key, selected),
+        InputMode::PinningCombatant(_) => {
+            handle_selection_mode(app, key, |app, idx, _| app.pin_combatant(idx))
+        }
+        InputMode::SelectingNotesTarget(_) => {
+            handle_selection_mode(app, key, |app, idx, _| app.choose_notes_target(idx))
+        }
+        InputMode::EditingNotes(state) => handle_edit_notes_mode(app, key, state),
+        InputMode::ResumingDelayed(delayed_index, _) => {
+            handle_selection_mode(app, key, |app, idx, _| {
+                if let Err(e) = app.complete_resume_delayed(delayed_index, Some(idx)) {
+                    app.set_message(e);
+                }
+            })
+        }
         InputMode::SavingEncounter(state) => handle_save_encounter_mode(app, key, state),
         InputMode::LoadingEncounter(state) => handle_load_encounter_mode(app, key, state),
         InputMode::SavingLibrary(state) => handle_save_library_mode(app, key, state),
         InputMode::LoadingLibrary(state) => handle_loading_library_mode(app, key, state),
+        InputMode::EncounterDifficulty(input) => handle_difficulty_mode(app, key, input),
+        InputMode::CombatSummary(_) => handle_combat_summary_mode(app, key),
+        InputMode::PartyRoster(selected) => handle_party_roster_mode(app, key, selected),
+        InputMode::Resting(state) => handle_rest_mode(app, key, state),
+        InputMode::EditingHitDice(index, input) => {
+            handle_edit_hit_dice_mode(app, key, index, input)
+        }
         InputMode::SettingLibraryInitiatives(state) => {
             handle_setting_library_initiatives_mode(app, key, state)
         }
@@ -128,3 +235,29 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
         }
     }
 }
+
+/// Rolls a numeric prompt's input (number or dice expression) and applies the
+/// total. The dice breakdown is logged ahead of the action's own lines, and
+/// only if the action succeeded.
+pub(super) fn roll_and_apply<F>(app: &mut App, input: &str, purpose: &str, apply: F)
+where
+    F: FnOnce(&mut App, i32) -> Result<(), String>,
+{
+    match app.roll_dice(input) {
+        Ok(roll) => {
+            app.queue_roll_log(purpose, &roll);
+            match apply(app, roll.total) {
+                Ok(()) => app.log_pending_roll(),
+                Err(e) => {
+                    app.pending_roll = None;
+                    app.set_message(e);
+                }
+            }
+        }
+        Err(e) => {
+            app.set_message(e);
+            app.input_mode = InputMode::Normal;
+            app.finish_prompt();
+        }
+    }
+}
@@ -3,24 +3,51 @@ use crossterm::event::{KeyCode, KeyEvent};
 
 #[derive(Clone, Copy)]
 pub(super) enum ActionMenuItem {
+    Attack,
     Damage,
+    AreaDamage,
     Heal,
     AddStatus,
     DeathSave,
     Concentration,
     ClearMenu,
     TempHp,
+    Exhaustion,
+    Resources,
+    OngoingEffect,
+    Reaction,
+    LegendaryAction,
+    LegendaryResistance,
+    Delay,
+    ResumeDelayed,
+    Ready,
+    TriggerReadied,
 }
 
 pub(super) fn action_menu_items() -> Vec<(ActionMenuItem, &'static str)> {
     vec![
+        (ActionMenuItem::Attack, "Attack"),
         (ActionMenuItem::Damage, "Deal Damage"),
+        (ActionMenuItem::AreaDamage, "Area Damage (Saves)"),
         (ActionMenuItem::Heal, "Heal"),
         (ActionMenuItem::AddStatus, "Add Status Effect"),
         (ActionMenuItem::DeathSave, "Roll Death Save"),
         (ActionMenuItem::Concentration, "Set Concentration"),
         (ActionMenuItem::ClearMenu, "Clear Concentration/Status"),
         (ActionMenuItem::TempHp, "Grant Temp HP"),
+        (ActionMenuItem::Exhaustion, "Set Exhaustion"),
+        (ActionMenuItem::Resources, "Resources (Slots/Abilities)"),
+        (ActionMenuItem::OngoingEffect, "Ongoing Damage/Healing"),
+        (ActionMenuItem::Reaction, "Use Reaction"),
+        (ActionMenuItem::LegendaryAction, "Spend Legendary Action"),
+        (
+            ActionMenuItem::LegendaryResistance,
+            "Use Legendary Resistance",
+        ),
+        (ActionMenuItem::Delay, "Delay Turn"),
+        (ActionMenuItem::ResumeDelayed, "Resume Delayed Creature"),
+        (ActionMenuItem::Ready, "Ready Action"),
+        (ActionMenuItem::TriggerReadied, "Trigger Readied Action"),
     ]
 }
 
@@ -47,13 +74,37 @@ pub(super) fn handle_action_menu_mode(app: &mut App, key: KeyEvent, selected_ind
         KeyCode::Enter => {
             if let Some((action, _)) = items.get(selected_index) {
                 match action {
+                    ActionMenuItem::Attack => app.start_attack(),
                     ActionMenuItem::Damage => app.start_dealing_damage(),
+                    ActionMenuItem::AreaDamage => app.start_area_damage(),
                     ActionMenuItem::Heal => app.start_healing(),
                     ActionMenuItem::AddStatus => app.start_adding_status(),
                     ActionMenuItem::DeathSave => app.start_rolling_death_save(),
                     ActionMenuItem::Concentration => app.start_concentration_target(),
                     ActionMenuItem::ClearMenu => app.start_clear_choice(),
                     ActionMenuItem::TempHp => app.start_granting_temp_hp(),
+                    ActionMenuItem::Exhaustion => app.start_setting_exhaustion(),
+                    ActionMenuItem::Resources => app.start_managing_resources(),
+                    ActionMenuItem::OngoingEffect => app.start_managing_ongoing_effects(),
+                    ActionMenuItem::Reaction => app.start_using_reaction(),
+                    ActionMenuItem::LegendaryAction => app.start_spending_legendary_action(),
+                    ActionMenuItem::LegendaryResistance => app.start_using_legendary_resistance(),
+                    ActionMenuItem::Delay => {
+                        app.input_mode = InputMode::Normal;
+                        app.delay_current_turn();
+                    }
+                    ActionMenuItem::ResumeDelayed => {
+                        app.input_mode = InputMode::Normal;
+                        app.start_resuming_delayed();
+                    }
+                    ActionMenuItem::Ready => {
+                        app.input_mode = InputMode::Normal;
+                        app.start_readying_action();
+                    }
+                    ActionMenuItem::TriggerReadied => {
+                        app.input_mode = InputMode::Normal;
+                        app.start_triggering_readied();
+                    }
                 }
             }
         }
@@ -64,9 +115,21 @@ pub(super) fn handle_action_menu_mode(app: &mut App, key: KeyEvent, selected_ind
 #[derive(Clone, Copy)]
 pub(super) enum CombatantMenuItem {
     AddCombatant,
+    AddTurnEntry,
     RemoveCombatant,
     LoadTemplate,
+    Bestiary,
     SaveTemplate,
+    RollNpcInitiative,
+    ResolveTie,
+    SetLegendary,
+    SetDefenses,
+    EditNotes,
+    Difficulty,
+    EndCombat,
+    PartyRoster,
+    ShortRest,
+    LongRest,
     LoadLibrary,
     SaveLibrary,
 }
@@ -74,9 +137,27 @@ pub(super) enum CombatantMenuItem {
 pub(super) fn combatant_menu_items() -> Vec<(CombatantMenuItem, &'static str)> {
     vec![
         (CombatantMenuItem::AddCombatant, "Add Combatant"),
+        (
+            CombatantMenuItem::AddTurnEntry,
+            "Add Lair Action/Hazard/Event",
+        ),
         (CombatantMenuItem::RemoveCombatant, "Remove Combatant"),
         (CombatantMenuItem::LoadTemplate, "Add from Template"),
+        (CombatantMenuItem::Bestiary, "Add from Bestiary"),
         (CombatantMenuItem::SaveTemplate, "Save as Template"),
+        (
+            CombatantMenuItem::RollNpcInitiative,
+            "Roll Initiative for NPCs",
+        ),
+        (CombatantMenuItem::ResolveTie, "Resolve Initiative Tie"),
+        (CombatantMenuItem::SetLegendary, "Set Legendary Pools"),
+        (CombatantMenuItem::SetDefenses, "Set Damage Defenses"),
+        (CombatantMenuItem::EditNotes, "Edit Notes"),
+        (CombatantMenuItem::Difficulty, "Encounter Difficulty"),
+        (CombatantMenuItem::EndCombat, "End Combat"),
+        (CombatantMenuItem::PartyRoster, "Party Roster"),
+        (CombatantMenuItem::ShortRest, "Short Rest"),
+        (CombatantMenuItem::LongRest, "Long Rest"),
         (CombatantMenuItem::LoadLibrary, "Load Encounter Library"),
         (CombatantMenuItem::SaveLibrary, "Save to Encounter Library"),
     ]
@@ -106,9 +187,32 @@ pub(super) fn handle_combatant_menu_mode(app: &mut App, key: KeyEvent, selected_
             if let Some((action, _)) = items.get(selected_index) {
                 match action {
                     CombatantMenuItem::AddCombatant => app.start_adding_combatant(),
+                    CombatantMenuItem::AddTurnEntry => app.start_adding_turn_entry(),
                     CombatantMenuItem::RemoveCombatant => app.start_removing(),
                     CombatantMenuItem::LoadTemplate => app.start_selecting_template(),
+                    CombatantMenuItem::Bestiary => app.start_browsing_bestiary(),
                     CombatantMenuItem::SaveTemplate => app.start_saving_template(),
+                    CombatantMenuItem::RollNpcInitiative => {
+                        app.roll_npc_initiative();
+                        app.input_mode = InputMode::Normal;
+                    }
+                    CombatantMenuItem::ResolveTie => {
+                        app.input_mode = InputMode::Normal;
+                        app.start_resolving_tie();
+                    }
+                    CombatantMenuItem::SetLegendary => app.start_setting_legendary(),
+                    CombatantMenuItem::SetDefenses => app.start_editing_defenses(),
+                    CombatantMenuItem::EditNotes => app.start_editing_notes(),
+                    CombatantMenuItem::Difficulty => app.start_viewing_difficulty(),
+                    CombatantMenuItem::EndCombat => {
+                        app.input_mode = InputMode::Normal;
+                        if let Err(e) = app.end_combat() {
+                            app.set_message(e);
+                        }
+                    }
+                    CombatantMenuItem::PartyRoster => app.start_viewing_roster(),
+                    CombatantMenuItem::ShortRest => app.start_rest(false),
+                    CombatantMenuItem::LongRest => app.start_rest(true),
                     CombatantMenuItem::LoadLibrary => app.start_loading_library(),
                     CombatantMenuItem::SaveLibrary => app.start_saving_library(),
                 }
@@ -1,4 +1,5 @@
 use crate::app::{App, InputMode};
+use crate::models::EconomySlot;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent) {
@@ -13,6 +14,10 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent) {
                 app.start_loading_encounter();
                 return;
             }
+            KeyCode::Char('r') | KeyCode::Char('R') => {
+                app.redo();
+                return;
+            }
             _ => {}
         }
     }
@@ -20,19 +25,42 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent) {
     // Regular key bindings
     match key.code {
         KeyCode::Char('q') => app.quit(),
-        KeyCode::Char('n') => {
-            app.encounter.next_turn();
-            app.clear_message();
-        }
+        KeyCode::Char('n') => app.advance_turn(),
+        KeyCode::Char('u') => app.undo(),
+        KeyCode::Char('i') => app.roll_npc_initiative(),
         KeyCode::Char('a') => app.start_adding_combatant(),
+        KeyCode::Char('f') => app.start_attack(),
         KeyCode::Char('d') => app.start_dealing_damage(),
+        KeyCode::Char('A') => app.start_area_damage(),
         KeyCode::Char('h') => app.start_healing(),
         KeyCode::Char('s') => app.start_adding_status(),
         KeyCode::Char('v') => app.start_rolling_death_save(),
         KeyCode::Char('c') => app.start_concentration_target(),
         KeyCode::Char('x') => app.start_clear_choice(),
+        KeyCode::Char('1') => app.toggle_economy(EconomySlot::Action),
+        KeyCode::Char('2') => app.toggle_economy(EconomySlot::BonusAction),
+        KeyCode::Char('3') => app.toggle_economy(EconomySlot::Reaction),
+        KeyCode::Char('4') => app.toggle_economy(EconomySlot::Movement),
+        KeyCode::Char('r') => app.start_using_reaction(),
+        KeyCode::Char('l') => app.start_spending_legendary_action(),
+        KeyCode::Char('L') => app.start_using_legendary_resistance(),
+        KeyCode::Char('D') => app.delay_current_turn(),
+        KeyCode::Char('e') => app.start_resuming_delayed(),
+        KeyCode::Char('R') => app.start_readying_action(),
+        KeyCode::Char('t') => app.start_triggering_readied(),
         KeyCode::Char('m') => app.open_action_menu(),
         KeyCode::Char('b') => app.open_combatant_menu(),
+        KeyCode::Char('p') => app.toggle_details(),
+        KeyCode::Char('P') => app.start_pinning(),
+        KeyCode::Char('X') => app.start_viewing_difficulty(),
+        KeyCode::Char('g') => app.start_viewing_roster(),
+        KeyCode::Char('o') => app.start_managing_resources(),
+        KeyCode::Char('O') => app.start_managing_ongoing_effects(),
+        KeyCode::Char('E') => {
+            if let Err(e) = app.end_combat() {
+                app.set_message(e);
+            }
+        }
         KeyCode::Char('?') => app.input_mode = InputMode::QuickReference(0),
         _ => {}
     }
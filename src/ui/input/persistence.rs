@@ -1,5 +1,9 @@
-use crate::app::{App, LoadLibraryState, SaveEncounterState, SaveLibraryState, SelectionState};
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::app::rest::hit_dice_remaining;
+use crate::app::{
+    App, LoadLibraryState, RestState, SaveEncounterState, SaveLibraryState, SelectionState,
+};
+use crate::dice::is_dice_char;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 pub(super) fn handle_save_encounter_mode(app: &mut App, key: KeyEvent, state: SaveEncounterState) {
     let mut input = state.input.clone();
@@ -47,31 +51,27 @@ pub(super) fn handle_load_encounter_mode(app: &mut App, key: KeyEvent, state: Se
 
     match key.code {
         KeyCode::Esc => app.cancel_input(),
-        KeyCode::Up => {
-            if !filtered.is_empty() {
-                selected_index = if selected_index > 0 {
-                    selected_index - 1
-                } else {
-                    filtered.len() - 1
-                };
-                app.input_mode = InputMode::LoadingEncounter(SelectionState {
-                    selected_index,
-                    input,
-                });
-            }
+        KeyCode::Up if !filtered.is_empty() => {
+            selected_index = if selected_index > 0 {
+                selected_index - 1
+            } else {
+                filtered.len() - 1
+            };
+            app.input_mode = InputMode::LoadingEncounter(SelectionState {
+                selected_index,
+                input,
+            });
         }
-        KeyCode::Down => {
-            if !filtered.is_empty() {
-                selected_index = if selected_index < filtered.len() - 1 {
-                    selected_index + 1
-                } else {
-                    0
-                };
-                app.input_mode = InputMode::LoadingEncounter(SelectionState {
-                    selected_index,
-                    input,
-                });
-            }
+        KeyCode::Down if !filtered.is_empty() => {
+            selected_index = if selected_index < filtered.len() - 1 {
+                selected_index + 1
+            } else {
+                0
+            };
+            app.input_mode = InputMode::LoadingEncounter(SelectionState {
+                selected_index,
+                input,
+            });
         }
         KeyCode::Enter => {
             if let Some(filename) = filtered.get(selected_index) {
@@ -187,31 +187,27 @@ pub(super) fn handle_loading_library_mode(app: &mut App, key: KeyEvent, state: S
 
     match key.code {
         KeyCode::Esc => app.cancel_input(),
-        KeyCode::Up => {
-            if !filtered.is_empty() {
-                selected_index = if selected_index > 0 {
-                    selected_index - 1
-                } else {
-                    filtered.len() - 1
-                };
-                app.input_mode = InputMode::LoadingLibrary(SelectionState {
-                    selected_index,
-                    input,
-                });
-            }
+        KeyCode::Up if !filtered.is_empty() => {
+            selected_index = if selected_index > 0 {
+                selected_index - 1
+            } else {
+                filtered.len() - 1
+            };
+            app.input_mode = InputMode::LoadingLibrary(SelectionState {
+                selected_index,
+                input,
+            });
         }
-        KeyCode::Down => {
-            if !filtered.is_empty() {
-                selected_index = if selected_index < filtered.len() - 1 {
-                    selected_index + 1
-                } else {
-                    0
-                };
-                app.input_mode = InputMode::LoadingLibrary(SelectionState {
-                    selected_index,
-                    input,
-                });
-            }
+        KeyCode::Down if !filtered.is_empty() => {
+            selected_index = if selected_index < filtered.len() - 1 {
+                selected_index + 1
+            } else {
+                0
+            };
+            app.input_mode = InputMode::LoadingLibrary(SelectionState {
+                selected_index,
+                input,
+            });
         }
         KeyCode::Enter => {
             if let Some(filename) = filtered.get(selected_index) {
@@ -221,6 +217,9 @@ pub(super) fn handle_loading_library_mode(app: &mut App, key: KeyEvent, state: S
                 app.input_mode = InputMode::Normal;
             }
         }
+        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.toggle_hit_point_mode();
+        }
         KeyCode::Backspace => {
             input.pop();
             app.input_mode = InputMode::LoadingLibrary(SelectionState {
@@ -260,7 +259,7 @@ pub(super) fn handle_setting_library_initiatives_mode(
             new_state.combatants_with_init[state.current_index].1 = input;
             app.input_mode = InputMode::SettingLibraryInitiatives(new_state);
         }
-        KeyCode::Char(c) if c.is_numeric() || (c == '-' && input.is_empty()) => {
+        KeyCode::Char(c) if is_dice_char(c) => {
             input.push(c);
             let mut new_state = state.clone();
             new_state.combatants_with_init[state.current_index].1 = input;
@@ -299,3 +298,147 @@ pub(super) fn handle_confirm_library_load(app: &mut App, key: KeyEvent, filename
 }
 
 use crate::app::InputMode;
+
+pub(super) fn handle_difficulty_mode(app: &mut App, key: KeyEvent, input: String) {
+    let mut input = input;
+    match key.code {
+        KeyCode::Esc => {
+            app.cancel_input();
+            return;
+        }
+        KeyCode::Enter => {
+            if let Err(e) = app.complete_set_party(&input) {
+                app.set_message(e);
+            }
+            return;
+        }
+        KeyCode::Backspace => {
+            input.pop();
+        }
+        KeyCode::Char(c) if c.is_ascii_digit() || c == ' ' || c == ',' => input.push(c),
+        _ => {}
+    }
+    app.input_mode = InputMode::EncounterDifficulty(input);
+}
+
+pub(super) fn handle_combat_summary_mode(app: &mut App, key: KeyEvent) {
+    if matches!(key.code, KeyCode::Esc | KeyCode::Enter) {
+        app.input_mode = InputMode::Normal;
+    }
+}
+
+pub(super) fn handle_party_roster_mode(app: &mut App, key: KeyEvent, selected_index: usize) {
+    let count = app.party.members.len();
+    match key.code {
+        KeyCode::Esc => app.cancel_input(),
+        KeyCode::Up if count > 0 => {
+            let new_idx = if selected_index > 0 {
+                selected_index - 1
+            } else {
+                count - 1
+            };
+            app.input_mode = InputMode::PartyRoster(new_idx);
+        }
+        KeyCode::Down if count > 0 => {
+            let new_idx = if selected_index + 1 < count {
+                selected_index + 1
+            } else {
+                0
+            };
+            app.input_mode = InputMode::PartyRoster(new_idx);
+        }
+        KeyCode::Enter => {
+            if let Err(e) = app.add_roster_member(selected_index) {
+                app.set_message(e);
+            }
+        }
+        KeyCode::Char('s') => {
+            if let Err(e) = app.record_party_roster() {
+                app.set_message(e);
+            }
+        }
+        KeyCode::Char('h') => {
+            if let Some(member) = app.party.members.get(selected_index) {
+                let formula = member.hit_dice.clone().unwrap_or_default();
+                app.input_mode = InputMode::EditingHitDice(selected_index, formula);
+            }
+        }
+        KeyCode::Char('r') => app.start_rest_on(false, true),
+        KeyCode::Char('R') => app.start_rest_on(true, true),
+        KeyCode::Delete => match app.remove_roster_member(selected_index) {
+            Ok(()) => {
+                let last = app.party.members.len().saturating_sub(1);
+                app.input_mode = InputMode::PartyRoster(selected_index.min(last));
+            }
+            Err(e) => app.set_message(e),
+        },
+        _ => {}
+    }
+}
+
+pub(super) fn handle_rest_mode(app: &mut App, key: KeyEvent, mut state: RestState) {
+    let targets = app.rest_targets(state.on_roster);
+    let count = targets.len();
+    match key.code {
+        KeyCode::Esc => {
+            app.cancel_input();
+            return;
+        }
+        KeyCode::Enter => {
+            if let Err(e) = app.complete_rest(state) {
+                app.set_message(e);
+                app.input_mode = InputMode::Normal;
+            }
+            return;
+        }
+        KeyCode::Tab => {
+            app.start_rest_on(state.long, !state.on_roster);
+            return;
+        }
+        KeyCode::Up if count > 0 => {
+            state.selected_index = (state.selected_index + count - 1) % count;
+        }
+        KeyCode::Down if count > 0 => {
+            state.selected_index = (state.selected_index + 1) % count;
+        }
+        // Hit dice to spend, short rests only
+        KeyCode::Right | KeyCode::Char('+') if !state.long => {
+            let remaining = targets
+                .get(state.selected_index)
+                .and_then(hit_dice_remaining)
+                .unwrap_or(0);
+            if let Some(spend) = state.hit_dice.get_mut(state.selected_index) {
+                *spend = (*spend + 1).min(remaining);
+            }
+        }
+        KeyCode::Left | KeyCode::Char('-') if !state.long => {
+            if let Some(spend) = state.hit_dice.get_mut(state.selected_index) {
+                *spend = spend.saturating_sub(1);
+            }
+        }
+        _ => {}
+    }
+    app.input_mode = InputMode::Resting(state);
+}
+
+pub(super) fn handle_edit_hit_dice_mode(app: &mut App, key: KeyEvent, index: usize, input: String) {
+    let mut input = input;
+    match key.code {
+        KeyCode::Esc => {
+            app.input_mode = InputMode::PartyRoster(index);
+            return;
+        }
+        KeyCode::Enter => {
+            if let Err(e) = app.complete_set_hit_dice(index, &input) {
+                app.set_message(e);
+            }
+            return;
+        }
+        KeyCode::Backspace => {
+            input.pop();
+        }
+        KeyCode::Char(c) if is_dice_char(c) => input.push(c),
+        _ => {}
+    }
+    app.input_mode = InputMode::EditingHitDice(index, input);
+}
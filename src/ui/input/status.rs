@@ -3,42 +3,22 @@
 use crate::app::{
     App, ClearAction, ConditionSelectionState, InputMode, SelectionState, StatusSelectionState,
 };
+use crate::models::{Ability, RepeatSave};
 use crossterm::event::{KeyCode, KeyEvent};
 
-use super::combat::update_selection_state;
+use super::combat::handle_selection_mode;
 
 pub(super) fn handle_status_selection_mode(app: &mut App, key: KeyEvent) {
-    if let InputMode::AddingStatus(state) = &app.input_mode {
-        let selected_index = state.selected_index;
-
-        match key.code {
-            KeyCode::Esc => app.cancel_input(),
-            KeyCode::Up => {
-                let new_index = if selected_index > 0 {
-                    selected_index - 1
-                } else {
-                    app.encounter.combatants.len().saturating_sub(1)
-                };
-                update_selection_state(app, new_index, String::new());
-            }
-            KeyCode::Down => {
-                let new_index = if selected_index < app.encounter.combatants.len().saturating_sub(1)
-                {
-                    selected_index + 1
-                } else {
-                    0
-                };
-                update_selection_state(app, new_index, String::new());
-            }
-            KeyCode::Enter => {
-                app.input_mode = InputMode::SelectingCondition(ConditionSelectionState {
-                    combatant_index: selected_index,
-                    input: String::new(),
-                });
-            }
-            _ => {}
-        }
+    if let KeyCode::Char(_) = key.code {
+        // Only navigation applies while picking the target
+        return;
     }
+    handle_selection_mode(app, key, |app, idx, _| {
+        app.input_mode = InputMode::SelectingCondition(ConditionSelectionState {
+            combatant_index: idx,
+            input: String::new(),
+        });
+    });
 }
 
 pub(super) fn handle_condition_selection_mode(
@@ -58,22 +38,24 @@ pub(super) fn handle_condition_selection_mode(
                 input,
             });
         }
-        KeyCode::Char(c) => {
-            if c.is_ascii_digit() || c == ' ' {
-                // Allow only one space separator
-                if c != ' ' || !input.contains(' ') {
-                    input.push(c);
-                    app.input_mode = InputMode::SelectingCondition(ConditionSelectionState {
-                        combatant_index,
-                        input,
-                    });
-                }
-            }
+        // Single spaces between the condition, duration, save ability and DC
+        KeyCode::Char(c)
+            if c.is_ascii_alphanumeric()
+                || c == ' ' && !input.is_empty() && !input.ends_with(' ') =>
+        {
+            input.push(c);
+            app.input_mode = InputMode::SelectingCondition(ConditionSelectionState {
+                combatant_index,
+                input,
+            });
         }
         KeyCode::Enter => {
             let parts: Vec<&str> = input.split_whitespace().collect();
-            if parts.len() != 2 {
-                app.set_message("Enter condition number and duration (e.g., 1 3)".to_string());
+            if parts.len() != 2 && parts.len() != 4 {
+                app.set_message(
+                    "Enter condition number and duration, optionally a save (e.g., 10 0 wis 14)"
+                        .to_string(),
+                );
                 return;
             }
 
@@ -93,8 +75,26 @@ pub(super) fn handle_condition_selection_mode(
                 }
             };
 
+            let save = if parts.len() == 4 {
+                let Some(ability) = Ability::parse(parts[2]) else {
+                    app.set_message(
+                        "Save ability must be STR, DEX, CON, INT, WIS or CHA".to_string(),
+                    );
+                    return;
+                };
+                match parts[3].parse::<i32>() {
+                    Ok(dc) if dc > 0 => Some(RepeatSave { ability, dc }),
+                    _ => {
+                        app.set_message("Save DC must be a positive number".to_string());
+                        return;
+                    }
+                }
+            } else {
+                None
+            };
+
             let condition = crate::models::ConditionType::all()[condition_idx];
-            if let Err(e) = app.complete_add_status(combatant_index, condition, duration) {
+            if let Err(e) = app.complete_add_status(combatant_index, condition, duration, save) {
                 app.set_message(e);
             }
         }
@@ -169,3 +169,73 @@ pub(super) fn handle_clear_choice_mode(app: &mut App, key: KeyEvent, choice: Cle
         _ => {}
     }
 }
+
+pub(super) fn handle_ongoing_effects_mode(
+    app: &mut App,
+    key: KeyEvent,
+    state: StatusSelectionState,
+) {
+    let mut state = state;
+    let Some(combatant) = app.encounter.combatants.get(state.combatant_index) else {
+        app.cancel_input();
+        return;
+    };
+    let count = combatant.ongoing_effects.len();
+    match key.code {
+        KeyCode::Esc => {
+            app.cancel_input();
+            return;
+        }
+        KeyCode::Char('a') => {
+            app.input_mode = InputMode::AddingOngoingEffect(state.combatant_index, String::new());
+            return;
+        }
+        KeyCode::Up if count > 0 => {
+            state.selected_status_index = (state.selected_status_index + count - 1) % count;
+        }
+        KeyCode::Down if count > 0 => {
+            state.selected_status_index = (state.selected_status_index + 1) % count;
+        }
+        KeyCode::Delete | KeyCode::Char('d') => {
+            match app.remove_ongoing_effect(state.combatant_index, state.selected_status_index) {
+                Ok(()) => {
+                    state.selected_status_index =
+                        state.selected_status_index.min(count.saturating_sub(2));
+                }
+                Err(e) => app.set_message(e),
+            }
+        }
+        _ => {}
+    }
+    app.input_mode = InputMode::ManagingOngoingEffects(state);
+}
+
+pub(super) fn handle_add_ongoing_effect_mode(
+    app: &mut App,
+    key: KeyEvent,
+    index: usize,
+    input: String,
+) {
+    let mut input = input;
+    match key.code {
+        KeyCode::Esc => {
+            app.input_mode = InputMode::ManagingOngoingEffects(StatusSelectionState {
+                combatant_index: index,
+                selected_status_index: 0,
+            });
+            return;
+        }
+        KeyCode::Enter => {
+            if let Err(e) = app.complete_add_ongoing_effect(index, &input) {
+                app.set_message(e);
+            }
+            return;
+        }
+        KeyCode::Backspace => {
+            input.pop();
+        }
+        KeyCode::Char(c) => input.push(c),
+        _ => {}
+    }
+    app.input_mode = InputMode::AddingOngoingEffect(index, input);
+}
@@ -0,0 +1,353 @@
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+use crate::app::App;
+use crate::models::{Ability, Combatant, CombatantTemplate, StatBlock, StatBlockEntry};
+
+use super::encounter::format_condition_effects;
+
+/// Stat block, defenses, conditions and notes for the pinned or current combatant
+pub fn render_details(f: &mut Frame, area: Rect, app: &App) {
+    let index = app.details_index();
+    let lines = match index.and_then(|i| app.encounter.combatants.get(i)) {
+        Some(c) => details_lines(c),
+        None => vec![Line::from(Span::styled(
+            "No combatant to show",
+            Style::default().fg(Color::DarkGray),
+        ))],
+    };
+
+    let pinned = match (index, &app.pinned) {
+        (Some(i), Some(name)) => app.encounter.combatants[i].name == *name,
+        _ => false,
+    };
+    let title = if pinned {
+        " Details (pinned) "
+    } else {
+        " Details "
+    };
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::White));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(paragraph, area);
+}
+
+fn heading(text: &str) -> Line<'static> {
+    Line::from(Span::styled(
+        text.to_string(),
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    ))
+}
+
+fn signed(value: i32) -> String {
+    format!("{:+}", value)
+}
+
+fn hit_dice_suffix(hit_dice: Option<&str>, spent: u32) -> String {
+    match (hit_dice, spent) {
+        (Some(dice), 0) => format!(" ({})", dice),
+        (Some(dice), spent) => format!(" ({}, {} spent)", dice, spent),
+        (None, _) => String::new(),
+    }
+}
+
+fn details_lines(c: &Combatant) -> Vec<Line<'static>> {
+    let name_color = if !c.is_creature() {
+        Color::Blue
+    } else if c.is_player {
+        Color::Green
+    } else {
+        Color::Red
+    };
+    let mut lines = vec![Line::from(Span::styled(
+        c.name.clone(),
+        Style::default().fg(name_color).add_modifier(Modifier::BOLD),
+    ))];
+
+    if !c.is_creature() {
+        lines.push(Line::from(format!(
+            "{} at initiative {}",
+            c.kind.as_str(),
+            c.initiative
+        )));
+        if let Some(reminder) = &c.reminder {
+            lines.push(Line::from(reminder.clone()));
+        }
+        return lines;
+    }
+
+    let temp = if c.temp_hp > 0 {
+        format!(" (+{} temp)", c.temp_hp)
+    } else {
+        String::new()
+    };
+    lines.push(Line::from(format!(
+        "HP {}/{}{}{}  AC {}  Init {}",
+        c.hp_current,
+        c.hp_max,
+        hit_dice_suffix(
+            c.hit_dice_pool.as_deref().or(c.hit_dice.as_deref()),
+            c.hit_dice_spent,
+        ),
+        temp,
+        c.armor_class,
+        c.initiative
+    )));
+
+    if let Some(block) = &c.stat_block {
+        lines.extend(stat_block_lines(block));
+    }
+
+    if !c.defenses.is_empty() {
+        lines.push(Line::from(Span::styled(
+            c.defenses.summary(),
+            Style::default().fg(Color::LightRed),
+        )));
+    }
+
+    if let Some(info) = &c.concentration {
+        lines.push(Line::from(Span::styled(
+            format!("Concentrating on {}", info.spell_name),
+            Style::default().fg(Color::Magenta),
+        )));
+    }
+
+    if c.exhaustion > 0 {
+        lines.push(Line::from(Span::styled(
+            format!("Exhaustion level {}", c.exhaustion),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+
+    if !c.ongoing_effects.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(heading("Ongoing Effects"));
+        for effect in &c.ongoing_effects {
+            lines.push(Line::from(effect.describe()));
+        }
+    }
+
+    if !c.resources.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(heading("Resources"));
+        for r in &c.resources {
+            lines.push(Line::from(format!(
+                "{}: {}/{}",
+                r.full_label(),
+                r.remaining(),
+                r.max
+            )));
+        }
+    }
+
+    if !c.status_effects.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(heading("Conditions"));
+        lines.extend(format_condition_effects(&c.status_effects));
+    }
+
+    if let Some(block) = &c.stat_block {
+        lines.extend(stat_block_entries(block));
+    }
+
+    if !c.notes.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(heading("Notes"));
+        lines.push(Line::from(c.notes.clone()));
+    }
+
+    lines
+}
+
+/// Preview of a template or bestiary monster before it is added
+pub(super) fn template_lines(tpl: &CombatantTemplate) -> Vec<Line<'static>> {
+    let mut lines = vec![
+        Line::from(Span::styled(
+            tpl.name.clone(),
+            Style::default()
+                .fg(if tpl.is_player {
+                    Color::Green
+                } else {
+                    Color::Red
+                })
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!(
+            "HP {}{}  AC {}  Init {}",
+            tpl.hp_max,
+            hit_dice_suffix(tpl.hit_dice.as_deref(), 0),
+            tpl.armor_class,
+            signed(tpl.initiative_modifier)
+        )),
+    ];
+    if let Some(block) = &tpl.stat_block {
+        lines.extend(stat_block_lines(block));
+    }
+    if !tpl.defenses.is_empty() {
+        lines.push(Line::from(Span::styled(
+            tpl.defenses.summary(),
+            Style::default().fg(Color::LightRed),
+        )));
+    }
+    if tpl.legendary_actions > 0 || tpl.legendary_resistances > 0 {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "Legendary actions {}, resistances {}",
+                tpl.legendary_actions, tpl.legendary_resistances
+            ),
+            Style::default().fg(Color::LightMagenta),
+        )));
+    }
+    if let Some(block) = &tpl.stat_block {
+        lines.extend(stat_block_entries(block));
+    }
+    lines
+}
+
+fn stat_block_lines(block: &StatBlock) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    // "Small humanoid (goblinoid)"
+    let mut kind = [block.size.as_str(), block.creature_type.as_str()]
+        .iter()
+        .filter(|part| !part.is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if !block.tags.is_empty() {
+        kind = format!("{} ({})", kind, block.tags.join(", "))
+            .trim()
+            .to_string();
+    }
+    if !kind.is_empty() {
+        lines.push(Line::from(Span::styled(
+            kind,
+            Style::default().add_modifier(Modifier::ITALIC),
+        )));
+    }
+    if !block.challenge_rating.is_empty() {
+        lines.push(Line::from(format!(
+            "CR {} ({} XP)",
+            block.challenge_rating, block.xp
+        )));
+    }
+    if !block.speed.is_empty() {
+        lines.push(Line::from(format!("Speed {}", block.speed)));
+    }
+    if !block.senses.is_empty() {
+        lines.push(Line::from(format!("Senses {}", block.senses)));
+    }
+
+    // Two rows of three keep the scores readable in a narrow panel
+    let scores = &block.ability_scores;
+    for row in Ability::all().chunks(3) {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|&a| {
+                format!(
+                    "{} {:>2} ({})",
+                    a.as_str(),
+                    scores.score(a),
+                    signed(scores.modifier(a))
+                )
+            })
+            .collect();
+        lines.push(Line::from(cells.join("  ")));
+    }
+
+    if !block.saving_throws.is_empty() {
+        let saves: Vec<String> = block
+            .saving_throws
+            .iter()
+            .map(|(a, bonus)| format!("{} {}", a.as_str(), signed(*bonus)))
+            .collect();
+        lines.push(Line::from(format!("Saves {}", saves.join(", "))));
+    }
+    lines
+}
+
+fn stat_block_entries(block: &StatBlock) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    for (title, entries) in [
+        ("Traits", &block.traits),
+        ("Actions", &block.actions),
+        ("Reactions", &block.reactions),
+    ] {
+        if !entries.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(heading(title));
+            lines.extend(entries.iter().map(entry_line));
+        }
+    }
+    lines
+}
+
+fn entry_line(entry: &StatBlockEntry) -> Line<'static> {
+    let mut spans = vec![Span::styled(
+        format!("{}.", entry.name),
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+    if !entry.description.is_empty() {
+        spans.push(Span::raw(format!(" {}", entry.description)));
+    }
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ConditionType, StatusEffect};
+
+    fn text(lines: &[Line]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn details_include_stat_block_conditions_and_notes() {
+        let mut goblin = Combatant::new("Goblin".to_string(), 12, 7, 15, false);
+        let mut block = StatBlock {
+            challenge_rating: "1/4".to_string(),
+            xp: 50,
+            ..StatBlock::default()
+        };
+        block.ability_scores.dexterity = 14;
+        block.saving_throws.insert(Ability::Dex, 4);
+        block.actions.push(StatBlockEntry {
+            name: "Scimitar".to_string(),
+            description: "+4 to hit, 1d6+2 slashing.".to_string(),
+        });
+        goblin.stat_block = Some(block);
+        goblin.add_status_effect(StatusEffect::new(ConditionType::Prone, 1, None));
+        goblin.notes = "Carries the cellar key".to_string();
+
+        let lines = text(&details_lines(&goblin));
+        assert!(lines.contains(&"HP 7/7  AC 15  Init 12".to_string()));
+        assert!(lines.contains(&"CR 1/4 (50 XP)".to_string()));
+        assert!(lines.contains(&"STR 10 (+0)  DEX 14 (+2)  CON 10 (+0)".to_string()));
+        assert!(lines.contains(&"Saves DEX +4".to_string()));
+        assert!(lines.iter().any(|l| l.contains("Prone:")));
+        assert!(lines.contains(&"Scimitar. +4 to hit, 1d6+2 slashing.".to_string()));
+        assert_eq!(lines.last().unwrap(), "Carries the cellar key");
+    }
+
+    #[test]
+    fn details_without_stat_block_skip_empty_sections() {
+        let hero = Combatant::new("Hero".to_string(), 15, 20, 16, true);
+        let lines = text(&details_lines(&hero));
+        assert_eq!(lines, vec!["Hero", "HP 20/20  AC 16  Init 15"]);
+    }
+}
@@ -9,10 +9,10 @@ use ratatui::{
 };
 
 use crate::app::App;
-use crate::models::{Combatant, StatusEffect};
+use crate::models::{Combatant, EconomySlot, StatusEffect, resources};
 
 pub fn render_combatants(f: &mut Frame, area: Rect, app: &App) {
-    let items: Vec<ListItem> = app
+    let mut items: Vec<ListItem> = app
         .encounter
         .combatants
         .iter()
@@ -20,6 +20,11 @@ pub fn render_combatants(f: &mut Frame, area: Rect, app: &App) {
         .map(|(i, c)| {
             let is_current = i == app.encounter.current_turn_index;
             let arrow = if is_current { "→ " } else { "  " };
+            let tie_marker = if app.encounter.is_unresolved_tie(i) {
+                "="
+            } else {
+                " "
+            };
 
             let name_color = if c.is_player {
                 Color::Green
@@ -27,24 +32,70 @@ pub fn render_combatants(f: &mut Frame, area: Rect, app: &App) {
                 Color::Red
             };
 
-            let status_str = if c.status_effects.is_empty() {
+            let status_str = if c.status_effects.is_empty() && c.exhaustion == 0 {
                 String::new()
             } else {
-                let effects: Vec<String> = c
+                let mut effects: Vec<String> = c
                     .status_effects
                     .iter()
-                    .map(|e| format!("{}({})", e.condition.as_str(), e.duration))
+                    .map(|e| match e.save {
+                        Some(save) => {
+                            format!("{}({}, {})", e.condition.as_str(), e.duration, save.label())
+                        }
+                        None => format!("{}({})", e.condition.as_str(), e.duration),
+                    })
                     .collect();
+                if c.exhaustion > 0 {
+                    effects.push(format!("Exhaustion {}", c.exhaustion));
+                }
                 format!(" [{}]", effects.join(", "))
             };
 
+            if !c.is_creature() {
+                let mut spans = vec![
+                    Span::raw(arrow),
+                    Span::raw(format!("[{:2}]", c.initiative)),
+                    Span::styled(
+                        format!("{} ", tie_marker),
+                        Style::default()
+                            .fg(Color::Magenta)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        format!("{:<20}", c.name),
+                        Style::default()
+                            .fg(Color::Blue)
+                            .add_modifier(Modifier::BOLD | Modifier::ITALIC),
+                    ),
+                    Span::styled(
+                        format!(" <{}>", c.kind.as_str()),
+                        Style::default().fg(Color::Blue),
+                    ),
+                ];
+                if let Some(reminder) = &c.reminder {
+                    spans.push(Span::styled(
+                        format!("  {}", reminder),
+                        Style::default()
+                            .fg(Color::Gray)
+                            .add_modifier(Modifier::ITALIC),
+                    ));
+                }
+                return ListItem::new(Line::from(spans));
+            }
+
             let hp_color = hp_color(c);
             let hp_style = Style::default().fg(hp_color);
             let hp_bar = hp_bar(c);
 
             let main_line = Line::from(vec![
                 Span::raw(arrow),
-                Span::raw(format!("[{:2}] ", c.initiative)),
+                Span::raw(format!("[{:2}]", c.initiative)),
+                Span::styled(
+                    format!("{} ", tie_marker),
+                    Style::default()
+                        .fg(Color::Magenta)
+                        .add_modifier(Modifier::BOLD),
+                ),
                 Span::styled(
                     format!("{:<20}", c.name),
                     Style::default().fg(name_color).add_modifier(Modifier::BOLD),
@@ -56,12 +107,37 @@ pub fn render_combatants(f: &mut Frame, area: Rect, app: &App) {
                 temp_hp_span(c),
                 death_save_span(c),
                 concentration_span(c),
+                legendary_span(c),
+                resources_span(c),
                 Span::raw(format!("  AC: {}  ", c.armor_class)),
+                economy_span(c, EconomySlot::Action, "A"),
+                economy_span(c, EconomySlot::BonusAction, "B"),
+                economy_span(c, EconomySlot::Reaction, "R"),
+                economy_span(c, EconomySlot::Movement, "M"),
+                Span::raw(" "),
                 Span::styled(status_str, Style::default().fg(Color::Yellow)),
+                readied_span(c),
             ]);
 
             // Build multi-line item with condition effects
             let mut lines = vec![main_line];
+            if !c.defenses.is_empty() {
+                lines.push(Line::from(Span::styled(
+                    format!("    {}", c.defenses.summary()),
+                    Style::default().fg(Color::LightRed),
+                )));
+            }
+            for effect in &c.ongoing_effects {
+                let color = if effect.healing {
+                    Color::Green
+                } else {
+                    Color::LightRed
+                };
+                lines.push(Line::from(Span::styled(
+                    format!("    ~ {}", effect.describe()),
+                    Style::default().fg(color),
+                )));
+            }
             let effect_lines = format_condition_effects(&c.status_effects);
             lines.extend(effect_lines);
 
@@ -69,6 +145,23 @@ pub fn render_combatants(f: &mut Frame, area: Rect, app: &App) {
         })
         .collect();
 
+    // Delayed creatures wait below the order until they re-enter
+    for c in &app.encounter.delayed {
+        items.push(ListItem::new(Line::from(vec![
+            Span::raw("  [--]  "),
+            Span::styled(
+                format!("{:<20}", c.name),
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(" delaying  HP {}/{}", c.hp_current, c.hp_max),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ])));
+    }
+
     let list = List::new(items).block(
         Block::default()
             .title(" Initiative Order ")
@@ -153,6 +246,64 @@ pub fn concentration_span(combatant: &Combatant) -> Span<'static> {
     }
 }
 
+pub fn readied_span(combatant: &Combatant) -> Span<'static> {
+    match &combatant.readied {
+        Some(trigger) => Span::styled(
+            format!(" [Ready: {}]", trigger),
+            Style::default()
+                .fg(Color::LightBlue)
+                .add_modifier(Modifier::ITALIC),
+        ),
+        None => Span::raw(""),
+    }
+}
+
+/// Green letter while the slot is available, struck-through gray once used
+pub fn economy_span(
+    combatant: &Combatant,
+    slot: EconomySlot,
+    label: &'static str,
+) -> Span<'static> {
+    let style = if combatant.economy.is_used(slot) {
+        Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::CROSSED_OUT)
+    } else {
+        Style::default().fg(Color::Green)
+    };
+    Span::styled(label, style)
+}
+
+pub fn legendary_span(combatant: &Combatant) -> Span<'static> {
+    let mut parts = Vec::new();
+    if let Some(pool) = &combatant.legendary_actions {
+        parts.push(format!("LA {}/{}", pool.remaining, pool.max));
+    }
+    if let Some(pool) = &combatant.legendary_resistances {
+        parts.push(format!("LR {}/{}", pool.remaining, pool.max));
+    }
+    if parts.is_empty() {
+        Span::raw("")
+    } else {
+        Span::styled(
+            format!(" [{}]", parts.join(" ")),
+            Style::default()
+                .fg(Color::LightMagenta)
+                .add_modifier(Modifier::BOLD),
+        )
+    }
+}
+
+pub fn resources_span(combatant: &Combatant) -> Span<'static> {
+    if combatant.resources.is_empty() {
+        return Span::raw("");
+    }
+    Span::styled(
+        format!(" [{}]", resources::summary(&combatant.resources)),
+        Style::default().fg(Color::Cyan),
+    )
+}
+
 /// Creates formatted lines showing mechanical effects for active conditions
 pub fn format_condition_effects(status_effects: &[StatusEffect]) -> Vec<Line<'static>> {
     if status_effects.is_empty() {
@@ -8,16 +8,23 @@ use ratatui::{
 use crate::app::{App, InputMode};
 
 use super::{
+    details::render_details,
     encounter::render_combatants,
     log::render_log,
     menus::{render_action_menu, render_combatant_menu, render_quick_reference},
     modals::{
-        render_add_combatant_modal, render_add_concentration_modal, render_clear_choice_modal,
-        render_concentration_check, render_condition_selection, render_confirm_load_modal,
-        render_confirm_overwrite_modal, render_library_initiative_modal,
-        render_load_encounter_modal, render_loading_library_modal, render_save_encounter_modal,
-        render_save_library_modal, render_selection_modal, render_status_clear_modal,
-        render_template_selection_modal,
+        render_add_combatant_modal, render_add_concentration_modal, render_add_copies_modal,
+        render_add_ongoing_effect_modal, render_add_turn_entry_modal, render_area_damage_modal,
+        render_attack_modal, render_bestiary_modal, render_choose_delayed_modal,
+        render_clear_choice_modal, render_combat_summary_modal, render_concentration_check,
+        render_condition_selection, render_confirm_load_modal, render_confirm_overwrite_modal,
+        render_damage_type_modal, render_difficulty_modal, render_edit_defenses_modal,
+        render_edit_hit_dice_modal, render_edit_notes_modal, render_edit_resources_modal,
+        render_library_initiative_modal, render_load_encounter_modal, render_loading_library_modal,
+        render_ongoing_effects_modal, render_party_roster_modal, render_ready_action_modal,
+        render_repeat_save_modal, render_resources_modal, render_rest_modal,
+        render_save_encounter_modal, render_save_library_modal, render_selection_modal,
+        render_set_legendary_modal, render_status_clear_modal, render_template_selection_modal,
     },
 };
 
@@ -34,7 +41,10 @@ pub fn render(f: &mut Frame, app: &App) {
 
     let content_chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Min(40), Constraint::Length(40)])
+        .constraints([
+            Constraint::Min(40),
+            Constraint::Length(if app.show_details { 48 } else { 40 }),
+        ])
         .split(chunks[1]);
 
     // Render header
@@ -42,7 +52,11 @@ pub fn render(f: &mut Frame, app: &App) {
 
     // Render main content
     render_combatants(f, content_chunks[0], app);
-    render_log(f, content_chunks[1], app);
+    if app.show_details {
+        render_details(f, content_chunks[1], app);
+    } else {
+        render_log(f, content_chunks[1], app);
+    }
 
     // Render commands
     render_commands(f, chunks[2], app);
@@ -55,24 +69,34 @@ pub fn render(f: &mut Frame, app: &App) {
     // Render modal if needed
     match &app.input_mode {
         InputMode::AddingCombatant(state) => render_add_combatant_modal(f, state),
-        InputMode::DealingDamage(state) => {
-            render_selection_modal(f, state, "Deal Damage", "Enter damage amount:", app)
-        }
-        InputMode::Healing(state) => {
-            render_selection_modal(f, state, "Heal", "Enter heal amount:", app)
-        }
+        InputMode::AddingTurnEntry(state) => render_add_turn_entry_modal(f, state),
+        InputMode::DealingDamage(state) => render_selection_modal(
+            f,
+            state,
+            "Deal Damage",
+            "Enter damage (number or dice, e.g. 2d6+3):",
+            app,
+        ),
+        InputMode::Healing(state) => render_selection_modal(
+            f,
+            state,
+            "Heal",
+            "Enter heal amount (number or dice, e.g. 2d4+2):",
+            app,
+        ),
         InputMode::AddingStatus(state) => {
             render_selection_modal(f, state, "Add Status Effect", "Select combatant:", app)
         }
         InputMode::SelectingCondition(state) => render_condition_selection(f, state, app),
         InputMode::RollingDeathSave(state) => {
-            render_selection_modal(f, state, "Death Save", "Enter d20 roll:", app)
+            render_selection_modal(f, state, "Death Save", "Enter d20 roll (or 1d20):", app)
         }
         InputMode::ConcentrationTarget(state) => {
             render_selection_modal(f, state, "Set Concentration", "Select combatant:", app)
         }
         InputMode::ApplyingConcentration(state) => render_add_concentration_modal(f, state, app),
         InputMode::ConcentrationCheck(state) => render_concentration_check(f, state, app),
+        InputMode::RepeatSave(state) => render_repeat_save_modal(f, state, app),
         InputMode::ClearActionSelection(choice) => render_clear_choice_modal(f, choice),
         InputMode::ClearingConcentration(state) => {
             render_selection_modal(f, state, "Clear Concentration", "Select combatant:", app)
@@ -82,12 +106,25 @@ pub fn render(f: &mut Frame, app: &App) {
         }
         InputMode::SelectingStatusToClear(state) => render_status_clear_modal(f, state, app),
         InputMode::SelectingTemplate(state) => render_template_selection_modal(f, state, app),
+        InputMode::BrowsingBestiary(state) => render_bestiary_modal(f, state, app),
+        InputMode::AddingCopies(state) => render_add_copies_modal(f, state, app),
         InputMode::SavingTemplate(state) => {
             render_selection_modal(f, state, "Save Template", "Select combatant to save:", app)
         }
-        InputMode::GrantingTempHp(state) => {
-            render_selection_modal(f, state, "Grant Temp HP", "Enter temp HP amount:", app)
-        }
+        InputMode::GrantingTempHp(state) => render_selection_modal(
+            f,
+            state,
+            "Grant Temp HP",
+            "Enter temp HP amount (number or dice):",
+            app,
+        ),
+        InputMode::SettingExhaustion(state) => render_selection_modal(
+            f,
+            state,
+            "Set Exhaustion",
+            "Enter exhaustion level (0-6):",
+            app,
+        ),
         InputMode::ActionMenu(selected) => render_action_menu(f, *selected),
         InputMode::CombatantMenu(selected) => render_combatant_menu(f, *selected),
         InputMode::QuickReference(selected) => render_quick_reference(f, *selected, app),
@@ -98,10 +135,122 @@ pub fn render(f: &mut Frame, app: &App) {
             "Select combatant to remove:",
             app,
         ),
+        InputMode::ResolvingTie(state) => render_selection_modal(
+            f,
+            state,
+            "Resolve Initiative Tie",
+            "Select a tied combatant to swap with its tie partner:",
+            app,
+        ),
+        InputMode::UsingReaction(state) => render_selection_modal(
+            f,
+            state,
+            "Use Reaction",
+            "Select creature using its reaction:",
+            app,
+        ),
+        InputMode::SelectingLegendaryTarget(state) => render_selection_modal(
+            f,
+            state,
+            "Legendary Pools",
+            "Select combatant to set legendary actions/resistances:",
+            app,
+        ),
+        InputMode::SettingLegendary(state) => render_set_legendary_modal(f, state, app),
+        InputMode::SpendingLegendaryAction(state) => render_selection_modal(
+            f,
+            state,
+            "Legendary Action",
+            "Select creature, type cost (blank = 1), Enter:",
+            app,
+        ),
+        InputMode::UsingLegendaryResistance(state) => render_selection_modal(
+            f,
+            state,
+            "Legendary Resistance",
+            "Select creature turning a failed save into a success:",
+            app,
+        ),
+        InputMode::ChoosingDamageType(state) => render_damage_type_modal(f, state, app),
+        InputMode::DealingAreaDamage(state) => render_area_damage_modal(f, state, app),
+        InputMode::Attacking(state) => render_attack_modal(f, state, app),
+        InputMode::SelectingDefensesTarget(state) => render_selection_modal(
+            f,
+            state,
+            "Damage Defenses",
+            "Select creature to edit resistances/immunities/vulnerabilities:",
+            app,
+        ),
+        InputMode::EditingDefenses(state) => render_edit_defenses_modal(f, state, app),
+        InputMode::SelectingResourceTarget(state) => render_selection_modal(
+            f,
+            state,
+            "Resources",
+            "Select creature to spend or restore slots and abilities:",
+            app,
+        ),
+        InputMode::ManagingResources(state) => render_resources_modal(f, state, app),
+        InputMode::SelectingOngoingTarget(state) => render_selection_modal(
+            f,
+            state,
+            "Ongoing Effects",
+            "Select creature for ongoing damage or healing:",
+            app,
+        ),
+        InputMode::ManagingOngoingEffects(state) => render_ongoing_effects_modal(f, state, app),
+        InputMode::AddingOngoingEffect(index, input) => {
+            render_add_ongoing_effect_modal(f, *index, input, app)
+        }
+        InputMode::EditingResources(index, input) => {
+            render_edit_resources_modal(f, *index, input, app)
+        }
+        InputMode::ReadyingAction(trigger) => render_ready_action_modal(f, trigger, app),
+        InputMode::TriggeringReadied(state) => render_selection_modal(
+            f,
+            state,
+            "Readied Action",
+            "Select creature whose trigger occurred:",
+            app,
+        ),
+        InputMode::ChoosingDelayed(selected) => render_choose_delayed_modal(f, *selected, app),
+        InputMode::ResumingDelayed(delayed_index, state) => {
+            let name = app
+                .encounter
+                .delayed
+                .get(*delayed_index)
+                .map(|c| c.name.as_str())
+                .unwrap_or("");
+            render_selection_modal(
+                f,
+                state,
+                "Resume Delayed",
+                &format!("{} acts right after:", name),
+                app,
+            )
+        }
+        InputMode::PinningCombatant(state) => render_selection_modal(
+            f,
+            state,
+            "Pin Details",
+            "Select creature to keep in the details panel:",
+            app,
+        ),
+        InputMode::SelectingNotesTarget(state) => {
+            render_selection_modal(f, state, "Edit Notes", "Select combatant:", app)
+        }
+        InputMode::EditingNotes(state) => render_edit_notes_modal(f, state, app),
         InputMode::SavingEncounter(state) => render_save_encounter_modal(f, state),
         InputMode::LoadingEncounter(state) => render_load_encounter_modal(f, state, app),
-        InputMode::SavingLibrary(state) => render_save_library_modal(f, state),
+        InputMode::SavingLibrary(state) => render_save_library_modal(f, state, app),
         InputMode::LoadingLibrary(state) => render_loading_library_modal(f, state, app),
+        InputMode::EncounterDifficulty(input) => render_difficulty_modal(f, input, app),
+        InputMode::CombatSummary(record) => render_combat_summary_modal(f, record),
+        InputMode::PartyRoster(selected) => render_party_roster_modal(f, *selected, app),
+        InputMode::Resting(state) => render_rest_modal(f, state, app),
+        InputMode::EditingHitDice(index, input) => {
+            render_party_roster_modal(f, *index, app);
+            render_edit_hit_dice_modal(f, *index, input, app);
+        }
         InputMode::SettingLibraryInitiatives(state) => {
             render_library_initiative_modal(f, state, app)
         }
@@ -112,10 +261,15 @@ pub fn render(f: &mut Frame, app: &App) {
 }
 
 fn render_header(f: &mut Frame, area: Rect, app: &App) {
-    let title = format!(
+    let mut title = format!(
         " D&D 5e Combat Tracker | Round: {} ",
         app.encounter.round_number
     );
+    let report = app.encounter_difficulty();
+    if report.monsters > 0 {
+        let rating = report.rating.map_or("Set party [X]", |r| r.as_str());
+        title.push_str(&format!("| {} ({} XP) ", rating, report.adjusted_xp));
+    }
     let block = Block::default()
         .title(title)
         .borders(Borders::ALL)
@@ -126,7 +280,7 @@ fn render_header(f: &mut Frame, area: Rect, app: &App) {
 fn render_commands(f: &mut Frame, area: Rect, app: &App) {
     let commands = match app.input_mode {
         InputMode::Normal => {
-            "[n] Next  [m] Action  [b] Combatant  [Ctrl+S] Save  [Ctrl+O] Load  [?] Ref  [q] Quit"
+            "[n] Next  [f] Attack  [m] Action  [b] Combatant  [u] Undo  [Ctrl+R] Redo  [i] NPC Init  [1-4] A/B/R/M  [A] Area  [r] React  [D] Delay  [R] Ready  [p/P] Details/Pin  [X] Difficulty  [E] End  [g] Party  [o] Resources  [O] Ongoing  [Ctrl+S] Save  [Ctrl+O] Load  [?] Ref  [q] Quit"
         }
         _ => "[Esc] Cancel",
     };
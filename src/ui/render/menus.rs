@@ -13,15 +13,27 @@ use crate::models::ConditionType;
 use super::modals::centered_rect;
 
 pub fn render_action_menu(f: &mut Frame, selected: usize) {
-    let area = centered_rect(50, 40, f.area());
+    let area = centered_rect(50, 70, f.area());
     let items = [
+        "Attack",
         "Deal Damage",
+        "Area Damage (Saves)",
         "Heal",
         "Add Status Effect",
         "Roll Death Save",
         "Set Concentration",
         "Clear Concentration/Status",
         "Grant Temp HP",
+        "Set Exhaustion",
+        "Resources (Slots/Abilities)",
+        "Ongoing Damage/Healing",
+        "Use Reaction",
+        "Spend Legendary Action",
+        "Use Legendary Resistance",
+        "Delay Turn",
+        "Resume Delayed Creature",
+        "Ready Action",
+        "Trigger Readied Action",
     ];
 
     let mut lines = vec![Line::from(Span::styled(
@@ -59,12 +71,24 @@ pub fn render_action_menu(f: &mut Frame, selected: usize) {
 }
 
 pub fn render_combatant_menu(f: &mut Frame, selected: usize) {
-    let area = centered_rect(50, 50, f.area());
+    let area = centered_rect(50, 70, f.area());
     let items = [
         "Add Combatant",
+        "Add Lair Action/Hazard/Event",
         "Remove Combatant",
         "Add from Template",
+        "Add from Bestiary",
         "Save as Template",
+        "Roll Initiative for NPCs",
+        "Resolve Initiative Tie",
+        "Set Legendary Pools",
+        "Set Damage Defenses",
+        "Edit Notes",
+        "Encounter Difficulty",
+        "End Combat",
+        "Party Roster",
+        "Short Rest",
+        "Long Rest",
         "Load Encounter Library",
         "Save to Encounter Library",
     ];
@@ -1,3 +1,4 @@
+mod details;
 mod encounter;
 mod layout;
 mod log;
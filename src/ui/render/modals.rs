@@ -8,19 +8,31 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
 
+use crate::app::bestiary::search_bestiary;
+use crate::app::persistence::load_library_template;
+use crate::app::rest::{HitDicePool, hit_dice_remaining};
+use crate::app::session::SessionRecord;
 use crate::app::{
-    AddCombatantState, AddConcentrationState, App, ClearAction, ConcentrationCheckState,
-    ConditionSelectionState, LoadLibraryState, SaveEncounterState, SaveLibraryState,
-    SelectionState, StatusSelectionState,
+    AddCombatantState, AddConcentrationState, AddTurnEntryState, App, AreaDamageState, AttackState,
+    BestiaryState, ClearAction, ConcentrationCheckState, ConditionSelectionState, CopiesState,
+    DamageTypeState, EditDefensesState, EditNotesState, LoadLibraryState, RepeatSaveState,
+    ResourceState, RestState, SaveEncounterState, SaveLibraryState, SelectionState,
+    SetLegendaryState, StatusSelectionState,
 };
-use crate::models::ConditionType;
+use crate::models::difficulty;
+use crate::models::{
+    CombatantKind, ConditionType, DamageType, DefenseKind, Difficulty, DifficultyReport,
+};
+
+use super::details::template_lines;
 
 pub fn render_add_combatant_modal(f: &mut Frame, state: &AddCombatantState) {
     let area = centered_rect(60, 40, f.area());
 
     let prompts = [
         "Enter name:",
-        "Enter initiative:",
+        "Initiative modifier (e.g. +2, blank for +0):",
+        "Enter initiative (number or dice, blank rolls d20 + modifier):",
         "Enter max HP:",
         "Enter AC:",
         "Is player? (y/n):",
@@ -28,6 +40,7 @@ pub fn render_add_combatant_modal(f: &mut Frame, state: &AddCombatantState) {
 
     let values = [
         &state.name,
+        &state.initiative_modifier,
         &state.initiative,
         &state.hp,
         &state.ac,
@@ -78,6 +91,85 @@ pub fn render_add_combatant_modal(f: &mut Frame, state: &AddCombatantState) {
     f.render_widget(paragraph, area);
 }
 
+pub fn render_add_turn_entry_modal(f: &mut Frame, state: &AddTurnEntryState) {
+    let area = centered_rect(60, 50, f.area());
+    let kinds = CombatantKind::turn_entries();
+
+    let mut lines = vec![Line::from(Span::styled(
+        "Entry type (↑/↓):",
+        if state.step == 0 {
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        },
+    ))];
+    for (i, kind) in kinds.iter().enumerate() {
+        let selected = i == state.kind_index;
+        let style = if selected {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        let marker = if selected { "> " } else { "  " };
+        lines.push(Line::from(Span::styled(
+            format!("{}{}", marker, kind.as_str()),
+            style,
+        )));
+    }
+    lines.push(Line::from(""));
+
+    let prompts = [
+        (1, "Name (blank uses the entry type):", &state.name),
+        (2, "Initiative count (blank for 20):", &state.initiative),
+        (3, "Reminder shown on its turn:", &state.reminder),
+    ];
+    for (step, prompt, value) in prompts {
+        if step < state.step {
+            lines.push(Line::from(vec![
+                Span::raw(prompt),
+                Span::raw(" "),
+                Span::styled(value.clone(), Style::default().fg(Color::Green)),
+            ]));
+        } else if step == state.step {
+            lines.push(Line::from(vec![Span::styled(
+                prompt,
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )]));
+            lines.push(Line::from(vec![
+                Span::raw("> "),
+                Span::styled(value.clone(), Style::default().fg(Color::White)),
+                Span::styled(
+                    "_",
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::SLOW_BLINK),
+                ),
+            ]));
+        } else {
+            lines.push(Line::from(Span::styled(
+                prompt,
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+    }
+
+    let block = Block::default()
+        .title(" Add Lair Action / Hazard / Event ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
 pub fn render_selection_modal(
     f: &mut Frame,
     state: &SelectionState,
@@ -96,8 +188,10 @@ pub fn render_selection_modal(
 
     lines.push(Line::from(""));
 
-    for (i, c) in app.encounter.combatants.iter().enumerate() {
-        let style = if i == state.selected_index {
+    let selected_index = app.effective_selection(state.selected_index);
+    for i in app.selectable_indices() {
+        let c = &app.encounter.combatants[i];
+        let style = if Some(i) == selected_index {
             Style::default()
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD)
@@ -105,11 +199,18 @@ pub fn render_selection_modal(
             Style::default().fg(Color::White)
         };
 
-        let marker = if i == state.selected_index {
+        let marker = if Some(i) == selected_index {
             "> "
         } else {
             "  "
         };
+        if !c.is_creature() {
+            lines.push(Line::from(Span::styled(
+                format!("{}{}. {} ({})", marker, i + 1, c.name, c.kind.as_str()),
+                style,
+            )));
+            continue;
+        }
         lines.push(Line::from(Span::styled(
             format!(
                 "{}{}. {} (HP: {}/{})",
@@ -172,7 +273,7 @@ pub fn render_condition_selection(f: &mut Frame, state: &ConditionSelectionState
 
     lines.push(Line::from(""));
     lines.push(Line::from(Span::raw(
-        "Enter number and duration (e.g., 1 3):",
+        "Enter number and duration, plus an optional end-of-turn save (e.g., 1 3 or 10 0 wis 14):",
     )));
     lines.push(Line::from(vec![
         Span::raw("> "),
@@ -320,6 +421,72 @@ pub fn render_add_concentration_modal(f: &mut Frame, state: &AddConcentrationSta
     f.render_widget(paragraph, area);
 }
 
+pub fn render_set_legendary_modal(f: &mut Frame, state: &SetLegendaryState, app: &App) {
+    let area = centered_rect(60, 40, f.area());
+    let combatant_name = app
+        .encounter
+        .combatants
+        .get(state.combatant_index)
+        .map(|c| c.name.as_str())
+        .unwrap_or("Unknown");
+
+    let prompts = [
+        "Legendary actions per round (0 for none):",
+        "Legendary resistances per day (0 for none):",
+    ];
+    let values = [&state.actions, &state.resistances];
+
+    let mut lines = vec![Line::from(Span::styled(
+        format!("Legendary pools for {}", combatant_name),
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    ))];
+    lines.push(Line::from(""));
+
+    for (i, prompt) in prompts.iter().enumerate() {
+        if i < state.step {
+            lines.push(Line::from(vec![
+                Span::raw(*prompt),
+                Span::raw(" "),
+                Span::styled(values[i].clone(), Style::default().fg(Color::Green)),
+            ]));
+        } else if i == state.step {
+            lines.push(Line::from(vec![Span::styled(
+                *prompt,
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )]));
+            lines.push(Line::from(vec![
+                Span::raw("> "),
+                Span::styled(values[i].clone(), Style::default().fg(Color::White)),
+                Span::styled(
+                    "_",
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::SLOW_BLINK),
+                ),
+            ]));
+        } else {
+            lines.push(Line::from(Span::styled(
+                *prompt,
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+    }
+
+    let block = Block::default()
+        .title(" Legendary Pools ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
 pub fn render_concentration_check(f: &mut Frame, state: &ConcentrationCheckState, app: &App) {
     let area = centered_rect(60, 40, f.area());
     let combatant_name = app
@@ -339,7 +506,7 @@ pub fn render_concentration_check(f: &mut Frame, state: &ConcentrationCheckState
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
         )),
-        Line::from("Enter total CON save roll:"),
+        Line::from("Enter total CON save roll (or dice, e.g. 1d20+3):"),
         Line::from(""),
         Line::from(vec![
             Span::raw("> "),
@@ -364,6 +531,57 @@ pub fn render_concentration_check(f: &mut Frame, state: &ConcentrationCheckState
     f.render_widget(paragraph, area);
 }
 
+pub fn render_repeat_save_modal(f: &mut Frame, state: &RepeatSaveState, app: &App) {
+    let area = centered_rect(60, 40, f.area());
+    let combatant_name = app
+        .encounter
+        .combatants
+        .get(state.combatant_index)
+        .map(|c| c.name.as_str())
+        .unwrap_or("Unknown");
+
+    let lines = vec![
+        Line::from(Span::styled(
+            format!(
+                "{} save for {} to end {} (DC {})",
+                state.save.ability.as_str(),
+                combatant_name,
+                state.condition.as_str(),
+                state.save.dc
+            ),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!(
+            "Enter total {} save roll (or dice, e.g. 1d20+3):",
+            state.save.ability.as_str()
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("> "),
+            Span::styled(state.input.clone(), Style::default().fg(Color::White)),
+            Span::styled(
+                "_",
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::SLOW_BLINK),
+            ),
+        ]),
+    ];
+
+    let block = Block::default()
+        .title(" End-of-Turn Save ")
+        .title_bottom(" [Enter] Roll  [Esc] Skip ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
 pub fn render_clear_choice_modal(f: &mut Frame, choice: &ClearAction) {
     let area = centered_rect(40, 40, f.area());
     let options = [
@@ -450,13 +668,18 @@ pub fn render_template_selection_modal(f: &mut Frame, state: &SelectionState, ap
         let prefix = if selected { "> " } else { "  " };
         lines.push(Line::from(Span::styled(
             format!(
-                "{}{}. {} (HP: {}, AC: {}, {})",
+                "{}{}. {} (HP: {}, AC: {}, {}){}",
                 prefix,
                 visible_idx + 1,
                 t.name,
                 t.hp_max,
                 t.armor_class,
-                if t.is_player { "PC" } else { "NPC" }
+                if t.is_player { "PC" } else { "NPC" },
+                t.stat_block
+                    .as_ref()
+                    .filter(|b| !b.challenge_rating.is_empty())
+                    .map(|b| format!(" CR {}", b.challenge_rating))
+                    .unwrap_or_default()
             ),
             style,
         )));
@@ -482,6 +705,10 @@ pub fn render_template_selection_modal(f: &mut Frame, state: &SelectionState, ap
 
     let block = Block::default()
         .title(" Templates ")
+        .title_bottom(format!(
+            " [Enter] Add  [Tab] Add copies  [Ctrl+R] HP: {} ",
+            hit_point_mode(app)
+        ))
         .borders(Borders::ALL)
         .style(Style::default().fg(Color::Yellow));
 
@@ -491,38 +718,99 @@ pub fn render_template_selection_modal(f: &mut Frame, state: &SelectionState, ap
     f.render_widget(paragraph, area);
 }
 
-pub fn render_save_encounter_modal(f: &mut Frame, state: &SaveEncounterState) {
-    let area = centered_rect(60, 30, f.area());
+/// Whether NPCs with hit dice are added with average or rolled HP
+fn hit_point_mode(app: &App) -> &'static str {
+    if app.roll_hit_points {
+        "rolled"
+    } else {
+        "average"
+    }
+}
 
-    let lines = vec![
+/// XP totals and the party's thresholds, highlighting the encounter's rating
+fn difficulty_lines(report: &DifficultyReport) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::from(format!(
+        "{} monsters: {} XP x{} = {} XP",
+        report.monsters, report.base_xp, report.multiplier, report.adjusted_xp
+    ))];
+    if report.unrated > 0 {
+        lines.push(Line::from(Span::styled(
+            format!("{} without a CR are not counted", report.unrated),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    let Some(rating) = report.rating else {
+        lines.push(Line::from(Span::styled(
+            "Set party levels to rate difficulty",
+            Style::default().fg(Color::DarkGray),
+        )));
+        return lines;
+    };
+
+    let mut thresholds = vec![Span::raw("Thresholds: ")];
+    for (difficulty, xp) in Difficulty::rated().into_iter().zip(report.thresholds) {
+        let style = if difficulty == rating {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        thresholds.push(Span::styled(
+            format!("{} {}  ", difficulty.as_str(), xp),
+            style,
+        ));
+    }
+    lines.push(Line::from(thresholds));
+    lines.push(Line::from(Span::styled(
+        format!("Difficulty: {}", rating.as_str()),
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    )));
+    lines
+}
+
+pub fn render_difficulty_modal(f: &mut Frame, input: &str, app: &App) {
+    let area = centered_rect(60, 50, f.area());
+    // Preview the levels being typed; fall back to the saved party while they don't parse
+    let levels = difficulty::parse_levels(input).unwrap_or_else(|_| app.party.levels.clone());
+    let report = difficulty::assess(&app.encounter_monster_xp(), &levels);
+
+    let mut lines = vec![
         Line::from(Span::styled(
-            "Save Encounter",
+            "Encounter Difficulty",
             Style::default()
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
-        Line::from("Enter filename (alphanumeric, underscore, hyphen only):"),
-        Line::from(""),
-        Line::from(vec![
-            Span::raw("> "),
-            Span::styled(state.input.clone(), Style::default().fg(Color::White)),
-            Span::styled(
-                "_",
-                Style::default()
-                    .fg(Color::White)
-                    .add_modifier(Modifier::SLOW_BLINK),
-            ),
-        ]),
-        Line::from(""),
-        Line::from(Span::styled(
-            "Press Enter to save, Esc to cancel",
-            Style::default().fg(Color::DarkGray),
-        )),
     ];
+    lines.extend(difficulty_lines(&report));
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::raw("Party levels: "),
+        Span::styled(
+            input.to_string(),
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            "_",
+            Style::default()
+                .fg(Color::White)
+                .add_modifier(Modifier::SLOW_BLINK),
+        ),
+    ]));
+    lines.push(Line::from(Span::styled(
+        "One level per PC, e.g. 3 3 4 5",
+        Style::default().fg(Color::DarkGray),
+    )));
 
     let block = Block::default()
-        .title(" Save Encounter ")
+        .title(" Difficulty ")
+        .title_bottom(" [Enter] Save party  [Esc] Close ")
         .borders(Borders::ALL)
         .style(Style::default().fg(Color::Yellow));
 
@@ -532,30 +820,1191 @@ pub fn render_save_encounter_modal(f: &mut Frame, state: &SaveEncounterState) {
     f.render_widget(paragraph, area);
 }
 
-pub fn render_load_encounter_modal(f: &mut Frame, state: &SelectionState, app: &App) {
-    let area = centered_rect(60, 50, f.area());
-
-    let saved_encounters = app.list_saved_encounters();
-    let filtered: Vec<&String> = saved_encounters
-        .iter()
-        .filter(|name| name.to_lowercase().contains(&state.input.to_lowercase()))
-        .collect();
-
-    let selected_index = state.selected_index.min(filtered.len().saturating_sub(1));
-    let max_visible = 10;
-    let start = if selected_index + 1 > max_visible {
-        selected_index + 1 - max_visible
-    } else {
-        0
+pub fn render_combat_summary_modal(f: &mut Frame, record: &SessionRecord) {
+    let area = centered_rect(60, 70, f.area());
+    let heading = |text: &str| {
+        Line::from(Span::styled(
+            text.to_string(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ))
     };
-    let end = (start + max_visible).min(filtered.len());
 
-    let mut lines = vec![Line::from(Span::styled(
-        "Load Encounter",
-        Style::default()
-            .fg(Color::Cyan)
-            .add_modifier(Modifier::BOLD),
-    ))];
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Combat Summary",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!("Rounds: {}", record.rounds)),
+        Line::from(""),
+        heading("Defeated"),
+    ];
+    if record.defeated.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  None",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    for monster in &record.defeated {
+        lines.push(Line::from(format!(
+            "  {:<24} {:>6} XP",
+            monster.name, monster.xp
+        )));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::raw("Total XP: "),
+        Span::styled(
+            record.total_xp.to_string(),
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ]));
+    lines.push(Line::from(if record.survivors.is_empty() {
+        "No surviving PCs to award".to_string()
+    } else {
+        format!(
+            "{} XP each to {}",
+            record.xp_per_survivor,
+            record.survivors.join(", ")
+        )
+    }));
+
+    lines.push(Line::from(""));
+    lines.push(heading(&format!(
+        "  {:<24} {:>6} {:>6}",
+        "Damage", "Dealt", "Taken"
+    )));
+    for tally in &record.damage {
+        lines.push(Line::from(format!(
+            "  {:<24} {:>6} {:>6}",
+            tally.name, tally.dealt, tally.taken
+        )));
+    }
+
+    let block = Block::default()
+        .title(" End of Combat ")
+        .title_bottom(" [Enter/Esc] Close ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block);
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_party_roster_modal(f: &mut Frame, selected: usize, app: &App) {
+    let area = centered_rect(70, 60, f.area());
+    let in_encounter = |name: &str| {
+        app.encounter
+            .combatants
+            .iter()
+            .chain(&app.encounter.delayed)
+            .any(|c| c.name == name)
+    };
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Party Roster",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+
+    if app.party.members.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No PCs on the roster. Press [s] to add the encounter's PCs.",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    for (i, member) in app.party.members.iter().enumerate() {
+        let style = if i == selected {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        let prefix = if i == selected { "> " } else { "  " };
+        let mut text = format!(
+            "{}{:<20} HP {}/{}",
+            prefix, member.name, member.hp_current, member.hp_max
+        );
+        if member.temp_hp > 0 {
+            text.push_str(&format!(" (+{})", member.temp_hp));
+        }
+        text.push_str(&format!("  AC {}", member.armor_class));
+        if let Some(dice) = &member.hit_dice {
+            text.push_str(&format!("  HD {} ({} spent)", dice, member.hit_dice_spent));
+        }
+        if member.exhaustion > 0 {
+            text.push_str(&format!("  Exhaustion {}", member.exhaustion));
+        }
+        let mut spans = vec![Span::styled(text, style)];
+        if !member.lasting_effects.is_empty() {
+            let effects: Vec<&str> = member
+                .lasting_effects
+                .iter()
+                .map(|e| e.condition.as_str())
+                .collect();
+            spans.push(Span::styled(
+                format!("  [{}]", effects.join(", ")),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        if in_encounter(&member.name) {
+            spans.push(Span::styled(
+                "  (in encounter)",
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "HP, hit dice, exhaustion and lasting conditions are written back when combat ends or the encounter is saved",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let block = Block::default()
+        .title(" Party ")
+        .title_bottom(
+            " [Enter] Add to encounter  [s] Save encounter PCs  [h] Hit dice  [r/R] Rest  [Del] Remove  [Esc] Close ",
+        )
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_edit_hit_dice_modal(f: &mut Frame, index: usize, input: &str, app: &App) {
+    let area = centered_rect(50, 20, f.area());
+    let name = app
+        .party
+        .members
+        .get(index)
+        .map_or("", |member| member.name.as_str());
+
+    let lines = vec![
+        Line::from(format!("Hit dice for {}:", name)),
+        Line::from(vec![
+            Span::styled(
+                input.to_string(),
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                "_",
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::SLOW_BLINK),
+            ),
+        ]),
+        Line::from(Span::styled(
+            "One die per level plus the total CON bonus, e.g. 5d10+10",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+
+    let block = Block::default()
+        .title(" Hit Dice ")
+        .title_bottom(" [Enter] Save  [Esc] Back ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_resources_modal(f: &mut Frame, state: &ResourceState, app: &App) {
+    let area = centered_rect(60, 60, f.area());
+    let Some(combatant) = app.encounter.combatants.get(state.combatant_index) else {
+        return;
+    };
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("Resources: {}", combatant.name),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+
+    if combatant.resources.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No slots or abilities yet. Press [e] to set them up.",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    for (i, resource) in combatant.resources.iter().enumerate() {
+        let selected = i == state.selected_index;
+        let style = if selected {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        let prefix = if selected { "> " } else { "  " };
+        let pips = "●".repeat(resource.remaining() as usize) + &"○".repeat(resource.used as usize);
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}{:<24}", prefix, resource.full_label()), style),
+            Span::styled(pips, Style::default().fg(Color::Cyan)),
+            Span::raw(format!("  {}/{}", resource.remaining(), resource.max)),
+        ]));
+    }
+
+    let block = Block::default()
+        .title(" Resources ")
+        .title_bottom(" [Enter/-] Spend  [+] Restore  [e] Edit  [Esc] Close ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_edit_resources_modal(f: &mut Frame, index: usize, input: &str, app: &App) {
+    let area = centered_rect(70, 35, f.area());
+    let name = app
+        .encounter
+        .combatants
+        .get(index)
+        .map_or("", |c| c.name.as_str());
+
+    let hint = |text: &str| {
+        Line::from(Span::styled(
+            text.to_string(),
+            Style::default().fg(Color::DarkGray),
+        ))
+    };
+    let lines = vec![
+        Line::from(format!("Resources for {}:", name)),
+        Line::from(vec![
+            Span::styled(
+                input.to_string(),
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                "_",
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::SLOW_BLINK),
+            ),
+        ]),
+        Line::from(""),
+        hint("Entries separated by ';', e.g. slots 4 3 2; pact 2x3; Channel Divinity 1/sr"),
+        hint("slots: spell slots from level 1 up    pact: count x slot level"),
+        hint("Anything else is a named ability: uses, then /sr (short rest), /lr or /day"),
+        hint("or a recharge such as /r5 (Recharge 5–6, rolled at the start of its turn)"),
+    ];
+
+    let block = Block::default()
+        .title(" Edit Resources ")
+        .title_bottom(" [Enter] Save  [Esc] Back ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_ongoing_effects_modal(f: &mut Frame, state: &StatusSelectionState, app: &App) {
+    let area = centered_rect(65, 50, f.area());
+    let Some(combatant) = app.encounter.combatants.get(state.combatant_index) else {
+        return;
+    };
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("Ongoing Effects: {}", combatant.name),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+
+    if combatant.ongoing_effects.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No ongoing effects. Press [a] to add one.",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    for (i, effect) in combatant.ongoing_effects.iter().enumerate() {
+        let selected = i == state.selected_status_index;
+        let style = if selected {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else if effect.healing {
+            Style::default().fg(Color::Green)
+        } else {
+            Style::default().fg(Color::LightRed)
+        };
+        let prefix = if selected { "> " } else { "  " };
+        lines.push(Line::from(Span::styled(
+            format!("{}{}", prefix, effect.describe()),
+            style,
+        )));
+    }
+
+    let block = Block::default()
+        .title(" Ongoing Effects ")
+        .title_bottom(" [a] Add  [d/Del] Remove  [Esc] Close ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_add_ongoing_effect_modal(f: &mut Frame, index: usize, input: &str, app: &App) {
+    let area = centered_rect(70, 35, f.area());
+    let name = app
+        .encounter
+        .combatants
+        .get(index)
+        .map_or("", |c| c.name.as_str());
+
+    let hint = |text: &str| {
+        Line::from(Span::styled(
+            text.to_string(),
+            Style::default().fg(Color::DarkGray),
+        ))
+    };
+    let lines = vec![
+        Line::from(format!("New ongoing effect on {}:", name)),
+        Line::from(vec![
+            Span::styled(
+                input.to_string(),
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                "_",
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::SLOW_BLINK),
+            ),
+        ]),
+        Line::from(""),
+        hint("name: dice [damage type | heal] [start | end] [rounds] [unless types]"),
+        hint("e.g. Burning: 1d6 fire start 3"),
+        hint("     Regeneration: 10 heal start unless fire acid"),
+    ];
+
+    let block = Block::default()
+        .title(" Add Ongoing Effect ")
+        .title_bottom(" [Enter] Add  [Esc] Back ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_rest_modal(f: &mut Frame, state: &RestState, app: &App) {
+    let area = centered_rect(70, 60, f.area());
+    let title = if state.long {
+        "Long Rest"
+    } else {
+        "Short Rest"
+    };
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            title,
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(vec![
+            Span::raw("Resting: "),
+            Span::styled(
+                if state.on_roster {
+                    "Party roster"
+                } else {
+                    "PCs in the encounter"
+                },
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(""),
+    ];
+
+    for (i, pc) in app.rest_targets(state.on_roster).iter().enumerate() {
+        let style = if i == state.selected_index {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        let prefix = if i == state.selected_index {
+            "> "
+        } else {
+            "  "
+        };
+        let hit_dice = match (HitDicePool::of(pc), hit_dice_remaining(pc)) {
+            (Some(pool), Some(remaining)) => {
+                format!("HD {}/{} {}", remaining, pool.count, pool.die())
+            }
+            _ => "No hit dice".to_string(),
+        };
+        let mut text = format!(
+            "{}{:<20} HP {}/{}  {}",
+            prefix, pc.name, pc.hp_current, pc.hp_max, hit_dice
+        );
+        if pc.exhaustion > 0 {
+            text.push_str(&format!("  Exhaustion {}", pc.exhaustion));
+        }
+        let mut spans = vec![Span::styled(text, style)];
+        if !state.long {
+            let spend = state.hit_dice.get(i).copied().unwrap_or(0);
+            if spend > 0 {
+                spans.push(Span::styled(
+                    format!("  spend {}", spend),
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+        }
+        lines.push(Line::from(spans));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        if state.long {
+            "Restores HP, regains half the hit dice, removes one exhaustion level, clears temp HP and death saves"
+        } else {
+            "Each hit die heals its roll plus CON (the bonus in the hit dice, e.g. 5d10+10 is +2)"
+        },
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let hints = if state.long {
+        " [Enter] Rest  [Tab] Roster/Encounter  [Esc] Cancel "
+    } else {
+        " [←/→] Hit dice  [Enter] Rest  [Tab] Roster/Encounter  [Esc] Cancel "
+    };
+    let block = Block::default()
+        .title(format!(" {} ", title))
+        .title_bottom(hints)
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_bestiary_modal(f: &mut Frame, state: &BestiaryState, app: &App) {
+    let area = centered_rect(90, 80, f.area());
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(area);
+
+    let results = search_bestiary(&app.bestiary, &state.query);
+    let selected_index = state.selected_index.min(results.len().saturating_sub(1));
+    // Visible rows left after the query, copies, hint and border lines
+    let max_visible = (columns[0].height as usize).saturating_sub(8).max(1);
+    let start = (selected_index + 1).saturating_sub(max_visible);
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::raw("Search: "),
+            Span::styled(
+                state.query.clone(),
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                "_",
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::SLOW_BLINK),
+            ),
+        ]),
+        Line::from(Span::styled(
+            "Filters: cr:1/4-2  type:dragon  tag:goblinoid",
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(vec![
+            Span::raw("Copies: "),
+            Span::styled(
+                format!("◀ {} ▶", state.copies),
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(if state.group_initiative {
+                "  group initiative"
+            } else {
+                ""
+            }),
+            Span::styled(
+                format!("   {} of {} monsters", results.len(), app.bestiary.len()),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(""),
+    ];
+
+    for (i, &index) in results.iter().enumerate().skip(start).take(max_visible) {
+        let monster = &app.bestiary[index];
+        let selected = i == selected_index;
+        let style = if selected {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        let prefix = if selected { "> " } else { "  " };
+        let cr = monster
+            .stat_block
+            .as_ref()
+            .filter(|b| !b.challenge_rating.is_empty())
+            .map(|b| format!("  CR {}", b.challenge_rating))
+            .unwrap_or_default();
+        lines.push(Line::from(Span::styled(
+            format!("{}{}{}", prefix, monster.name, cr),
+            style,
+        )));
+    }
+    if results.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No matching monsters",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    let list = Paragraph::new(lines).block(
+        Block::default()
+            .title(" Bestiary ")
+            .title_bottom(format!(
+                " [Enter] Add  [←/→] Copies  [Ctrl+G] Group init  [Ctrl+R] HP: {}  [Tab] Save template ",
+                hit_point_mode(app)
+            ))
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::Yellow)),
+    );
+
+    let preview_lines = results
+        .get(selected_index)
+        .map(|&index| template_lines(&app.bestiary[index]))
+        .unwrap_or_default();
+    let preview = Paragraph::new(preview_lines)
+        .block(
+            Block::default()
+                .title(" Preview ")
+                .borders(Borders::ALL)
+                .style(Style::default().fg(Color::White)),
+        )
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(list, columns[0]);
+    f.render_widget(preview, columns[1]);
+}
+
+pub fn render_damage_type_modal(f: &mut Frame, state: &DamageTypeState, app: &App) {
+    let area = centered_rect(50, 70, f.area());
+    let Some(target) = app.encounter.combatants.get(state.combatant_index) else {
+        return;
+    };
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!(
+                "Damage type for {}{} to {}:",
+                state.amount,
+                if state.critical { " (critical)" } else { "" },
+                target.name
+            ),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    for (i, damage_type) in DamageType::all().iter().enumerate() {
+        let (marker, style) = if i == state.selected_index {
+            (
+                "> ",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            ("  ", Style::default().fg(Color::White))
+        };
+        let note = if target.defenses.has(DefenseKind::Immunity, *damage_type) {
+            " (immune)"
+        } else if target.defenses.has(DefenseKind::Resistance, *damage_type) {
+            " (resistant)"
+        } else if target
+            .defenses
+            .has(DefenseKind::Vulnerability, *damage_type)
+        {
+            " (vulnerable)"
+        } else {
+            ""
+        };
+        lines.push(Line::from(Span::styled(
+            format!("{}{}{}", marker, damage_type.as_str(), note),
+            style,
+        )));
+    }
+
+    let block = Block::default()
+        .title(" Damage Type ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_attack_modal(f: &mut Frame, state: &AttackState, app: &App) {
+    let area = centered_rect(60, 60, f.area());
+    let highlight = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let title_style = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let name = |index: usize| {
+        app.encounter
+            .combatants
+            .get(index)
+            .map(|c| c.name.as_str())
+            .unwrap_or("")
+    };
+
+    let mut lines = Vec::new();
+    match state.step {
+        0 | 1 => {
+            let (prompt, selected) = if state.step == 0 {
+                ("Select the attacker:".to_string(), state.attacker)
+            } else {
+                (format!("{} attacks:", name(state.attacker)), state.target)
+            };
+            lines.push(Line::from(Span::styled(prompt, title_style)));
+            lines.push(Line::from(""));
+            for i in app.selectable_indices() {
+                let c = &app.encounter.combatants[i];
+                let (marker, style) = if i == selected {
+                    ("> ", highlight)
+                } else {
+                    ("  ", Style::default().fg(Color::White))
+                };
+                lines.push(Line::from(Span::styled(
+                    format!(
+                        "{}{} (AC {}, HP {}/{})",
+                        marker, c.name, c.armor_class, c.hp_current, c.hp_max
+                    ),
+                    style,
+                )));
+            }
+        }
+        step => {
+            let ac = app
+                .encounter
+                .combatants
+                .get(state.target)
+                .map_or(0, |c| c.armor_class);
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "{} attacks {} (AC {})",
+                    name(state.attacker),
+                    name(state.target),
+                    ac
+                ),
+                title_style,
+            )));
+            lines.push(Line::from(""));
+            lines.push(Line::from(if step == 2 {
+                "Attack bonus (e.g. +5) or roll (e.g. 1d20adv+5):"
+            } else if state.critical {
+                "Critical hit! Damage (dice are doubled):"
+            } else {
+                "Hit! Damage (number or dice):"
+            }));
+            lines.push(Line::from(vec![
+                Span::raw("> "),
+                Span::styled(state.input.clone(), Style::default().fg(Color::White)),
+                Span::styled(
+                    "_",
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::SLOW_BLINK),
+                ),
+            ]));
+        }
+    }
+
+    let block = Block::default()
+        .title(" Attack ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_area_damage_modal(f: &mut Frame, state: &AreaDamageState, app: &App) {
+    let area = centered_rect(60, 70, f.area());
+    let highlight = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let title_style = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+
+    let mut lines = Vec::new();
+    match state.step {
+        0 => {
+            lines.push(Line::from(Span::styled(
+                "Select targets and their save results:",
+                title_style,
+            )));
+            lines.push(Line::from(Span::styled(
+                "[Space] Toggle  [f/h/n] Full/Half/None  [Tab] Cycle  [a] All  [Enter] Next",
+                Style::default().fg(Color::DarkGray),
+            )));
+            lines.push(Line::from(""));
+            for i in app.selectable_indices() {
+                let c = &app.encounter.combatants[i];
+                let target = state.targets.iter().find(|(t, _)| *t == i);
+                let (check, outcome) = match target {
+                    Some((_, outcome)) => ("[x]", outcome.as_str()),
+                    None => ("[ ]", ""),
+                };
+                let marker = if i == state.cursor { "> " } else { "  " };
+                let style = if i == state.cursor {
+                    highlight
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                lines.push(Line::from(Span::styled(
+                    format!(
+                        "{}{} {} (HP: {}/{}) {}",
+                        marker, check, c.name, c.hp_current, c.hp_max, outcome
+                    ),
+                    style,
+                )));
+            }
+        }
+        1 => {
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "Damage before saves for {} target(s) (number or dice):",
+                    state.targets.len()
+                ),
+                title_style,
+            )));
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![
+                Span::raw("> "),
+                Span::styled(state.amount.clone(), Style::default().fg(Color::White)),
+                Span::styled(
+                    "_",
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::SLOW_BLINK),
+                ),
+            ]));
+        }
+        _ => {
+            lines.push(Line::from(Span::styled(
+                format!("Damage type for {}:", state.amount),
+                title_style,
+            )));
+            lines.push(Line::from(""));
+            for (i, damage_type) in DamageType::all().iter().enumerate() {
+                let (marker, style) = if i == state.type_index {
+                    ("> ", highlight)
+                } else {
+                    ("  ", Style::default().fg(Color::White))
+                };
+                lines.push(Line::from(Span::styled(
+                    format!("{}{}", marker, damage_type.as_str()),
+                    style,
+                )));
+            }
+        }
+    }
+
+    let block = Block::default()
+        .title(" Area Damage ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_edit_defenses_modal(f: &mut Frame, state: &EditDefensesState, app: &App) {
+    let area = centered_rect(50, 70, f.area());
+    let Some(target) = app.encounter.combatants.get(state.combatant_index) else {
+        return;
+    };
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("Damage defenses for {}", target.name),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            "[r] Resistance  [i] Immunity  [v] Vulnerability  [Enter] Done",
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(""),
+    ];
+    for (i, damage_type) in DamageType::defendable().iter().enumerate() {
+        let style = if i == state.selected_index {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        let marker = if i == state.selected_index {
+            "> "
+        } else {
+            "  "
+        };
+        let flag = |kind: DefenseKind, label: &'static str| {
+            if target.defenses.has(kind, *damage_type) {
+                label
+            } else {
+                "-"
+            }
+        };
+        lines.push(Line::from(Span::styled(
+            format!(
+                "{}{:<12} {} {} {}",
+                marker,
+                damage_type.as_str(),
+                flag(DefenseKind::Resistance, "R"),
+                flag(DefenseKind::Immunity, "I"),
+                flag(DefenseKind::Vulnerability, "V"),
+            ),
+            style,
+        )));
+    }
+
+    let block = Block::default()
+        .title(" Damage Defenses ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_edit_notes_modal(f: &mut Frame, state: &EditNotesState, app: &App) {
+    let area = centered_rect(60, 30, f.area());
+    let name = app
+        .encounter
+        .combatants
+        .get(state.combatant_index)
+        .map(|c| c.name.as_str())
+        .unwrap_or("");
+
+    let lines = vec![
+        Line::from(Span::styled(
+            format!("Notes for {}", name),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("> "),
+            Span::styled(state.input.clone(), Style::default().fg(Color::White)),
+            Span::styled(
+                "_",
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::SLOW_BLINK),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Shown in the details panel [p]; leave blank to clear",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+
+    let block = Block::default()
+        .title(" Edit Notes ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_add_copies_modal(f: &mut Frame, state: &CopiesState, app: &App) {
+    let area = centered_rect(50, 30, f.area());
+    let name = app
+        .templates
+        .get(state.template_index)
+        .map(|t| t.name.as_str())
+        .unwrap_or("");
+
+    let lines = vec![
+        Line::from(Span::styled(
+            format!("Add copies of {}", name),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("Copies: "),
+            Span::styled(
+                state.count.clone(),
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                "_",
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::SLOW_BLINK),
+            ),
+        ]),
+        Line::from(format!(
+            "Initiative: {}",
+            if state.group_initiative {
+                "one group roll"
+            } else {
+                "rolled per copy"
+            }
+        )),
+        Line::from(format!("Hit points: {}", hit_point_mode(app))),
+        Line::from(""),
+        Line::from(Span::styled(
+            "[Enter] Add  [g] Group initiative  [h] Average/rolled HP  [Esc] Cancel",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+
+    let block = Block::default()
+        .title(" Add from Template ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_ready_action_modal(f: &mut Frame, trigger: &str, app: &App) {
+    let area = centered_rect(60, 30, f.area());
+    let name = app
+        .encounter
+        .combatants
+        .get(app.encounter.current_turn_index)
+        .map(|c| c.name.as_str())
+        .unwrap_or("");
+
+    let lines = vec![
+        Line::from(Span::styled(
+            format!("Ready an action for {}", name),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from("Enter the trigger:"),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("> "),
+            Span::styled(trigger.to_string(), Style::default().fg(Color::White)),
+            Span::styled(
+                "_",
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::SLOW_BLINK),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Uses the reaction when triggered; lapses at the start of its next turn",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+
+    let block = Block::default()
+        .title(" Ready Action ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_choose_delayed_modal(f: &mut Frame, selected: usize, app: &App) {
+    let area = centered_rect(50, 40, f.area());
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            "Select the delayed creature to bring back:",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    for (i, c) in app.encounter.delayed.iter().enumerate() {
+        let (marker, style) = if i == selected {
+            (
+                "> ",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            ("  ", Style::default().fg(Color::White))
+        };
+        lines.push(Line::from(Span::styled(
+            format!("{}{} (HP: {}/{})", marker, c.name, c.hp_current, c.hp_max),
+            style,
+        )));
+    }
+
+    let block = Block::default()
+        .title(" Delayed ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_save_encounter_modal(f: &mut Frame, state: &SaveEncounterState) {
+    let area = centered_rect(60, 30, f.area());
+
+    let lines = vec![
+        Line::from(Span::styled(
+            "Save Encounter",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from("Enter filename (alphanumeric, underscore, hyphen only):"),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("> "),
+            Span::styled(state.input.clone(), Style::default().fg(Color::White)),
+            Span::styled(
+                "_",
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::SLOW_BLINK),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Press Enter to save, Esc to cancel",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+
+    let block = Block::default()
+        .title(" Save Encounter ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Yellow));
+
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+pub fn render_load_encounter_modal(f: &mut Frame, state: &SelectionState, app: &App) {
+    let area = centered_rect(60, 50, f.area());
+
+    let saved_encounters = app.list_saved_encounters();
+    let filtered: Vec<&String> = saved_encounters
+        .iter()
+        .filter(|name| name.to_lowercase().contains(&state.input.to_lowercase()))
+        .collect();
+
+    let selected_index = state.selected_index.min(filtered.len().saturating_sub(1));
+    let max_visible = 10;
+    let start = if selected_index + 1 > max_visible {
+        selected_index + 1 - max_visible
+    } else {
+        0
+    };
+    let end = (start + max_visible).min(filtered.len());
+
+    let mut lines = vec![Line::from(Span::styled(
+        "Load Encounter",
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    ))];
     lines.push(Line::from(""));
 
     if filtered.is_empty() {
@@ -617,13 +2066,20 @@ pub fn render_load_encounter_modal(f: &mut Frame, state: &SelectionState, app: &
     f.render_widget(paragraph, area);
 }
 
-pub fn render_save_library_modal(f: &mut Frame, state: &SaveLibraryState) {
+pub fn render_save_library_modal(f: &mut Frame, state: &SaveLibraryState, app: &App) {
     let area = centered_rect(60, 50, f.area());
 
+    let difficulty_prompt = match app.encounter_difficulty().rating {
+        Some(rating) => format!(
+            "Enter difficulty (blank uses computed: {}):",
+            rating.as_str()
+        ),
+        None => "Enter difficulty (optional, e.g., Easy, Medium, Hard):".to_string(),
+    };
     let prompts = [
         "Enter encounter name:",
         "Enter description:",
-        "Enter difficulty (optional, e.g., Easy, Medium, Hard):",
+        difficulty_prompt.as_str(),
     ];
 
     let values = [&state.name, &state.description, &state.difficulty];
@@ -742,6 +2198,18 @@ pub fn render_loading_library_modal(f: &mut Frame, state: &SelectionState, app:
         }
     }
 
+    // Difficulty of the highlighted encounter for the current party
+    if let Some(template) = filtered
+        .get(selected_index)
+        .and_then(|name| load_library_template(&app.data_dir, name).ok())
+    {
+        lines.push(Line::from(""));
+        if !template.difficulty.is_empty() {
+            lines.push(Line::from(format!("Saved as: {}", template.difficulty)));
+        }
+        lines.extend(difficulty_lines(&app.library_difficulty(&template)));
+    }
+
     lines.push(Line::from(""));
     lines.push(Line::from(vec![
         Span::raw("Filter: "),
@@ -755,6 +2223,7 @@ pub fn render_loading_library_modal(f: &mut Frame, state: &SelectionState, app:
 
     let block = Block::default()
         .title(" Load from Library ")
+        .title_bottom(format!(" [Ctrl+R] NPC HP: {} ", hit_point_mode(app)))
         .borders(Borders::ALL)
         .style(Style::default().fg(Color::Yellow));
 
@@ -770,10 +2239,20 @@ pub fn render_library_initiative_modal(f: &mut Frame, state: &LoadLibraryState,
     let current_combatant = &state.combatants_with_init[state.current_index].0;
     let current_input = &state.combatants_with_init[state.current_index].1;
 
+    // NPCs are rolled automatically, so progress only counts player characters
+    let pc_total = state
+        .combatants_with_init
+        .iter()
+        .filter(|(c, _)| c.is_player)
+        .count();
+    let pc_position = state.combatants_with_init[..state.current_index]
+        .iter()
+        .filter(|(c, _)| c.is_player)
+        .count()
+        + 1;
     let progress = format!(
-        "Setting initiative ({}/{})",
-        state.current_index + 1,
-        state.combatants_with_init.len()
+        "Setting player initiative ({}/{}), NPCs rolled automatically",
+        pc_position, pc_total
     );
 
     let lines = vec![
@@ -795,11 +2274,13 @@ pub fn render_library_initiative_modal(f: &mut Frame, state: &LoadLibraryState,
             ),
         ]),
         Line::from(format!(
-            "HP: {}, AC: {}",
-            current_combatant.hp_max, current_combatant.armor_class
+            "HP: {}, AC: {}, Init mod: {:+}",
+            current_combatant.hp_max,
+            current_combatant.armor_class,
+            current_combatant.initiative_modifier
         )),
         Line::from(""),
-        Line::from("Enter initiative:"),
+        Line::from("Enter initiative (number or dice, e.g. 1d20+1):"),
         Line::from(""),
         Line::from(vec![
             Span::raw("> "),